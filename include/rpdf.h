@@ -10,12 +10,17 @@
  *   - rpdf_last_error() returns a pointer valid until the next call on this
  *     thread – do NOT free it.
  *
- * ERROR CODES
- *   0  success
- *   1  null pointer argument
- *   2  invalid UTF-8 in input
- *   3  pipeline / layout error
- *   4  render / PDF error
+ * ERROR CODES (see RpdfError)
+ *   0  RpdfError_Ok               success
+ *   1  RpdfError_NullPointer      null pointer argument
+ *   2  RpdfError_InvalidUtf8      invalid UTF-8 in input
+ *   3  RpdfError_Pipeline         pipeline / layout error
+ *   4  RpdfError_Render           render / PDF error
+ *   5  RpdfError_CallbackAborted  write callback aborted
+ *   6  RpdfError_InvalidFont      font bytes could not be parsed
+ *
+ *   Undecodable <img> sources are skipped rather than failing the call;
+ *   rpdf_last_skipped_images() returns how many were left out.
  *
  * LINK FLAGS
  *   Windows MSVC  : pdf_forge.lib  Ws2_32.lib Bcrypt.lib Ntdll.lib Userenv.lib
@@ -31,11 +36,98 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * Line-height factor of text without a `line-height` style.
+ */
+#define DEFAULT_LINE_HEIGHT 1.4
+
+/**
+ * Non-breaking space (`&nbsp;`); drawn and measured as a plain space.
+ */
+#define NBSP '\u{a0}'
+
+/**
+ * Soft hyphen (`&shy;`): an invisible point where a word may be broken.
+ */
+#define SOFT_HYPHEN '\u{ad}'
+
+/**
+ * Resolution that maps raster image pixels to points by default: 96 DPI,
+ * the resolution of a CSS px, so a 96 px wide image is one inch (72 pt).
+ */
+#define DEFAULT_IMAGE_DPI 96.0
+
 /**
  * Default page margins in points.
  */
 #define PAGE_MARGIN_PT 40.0
 
+/**
+ * Default [`PaginationOptions::keep_with_next_pt`].
+ */
+#define KEEP_WITH_NEXT_PT 48.0
+
+/**
+ * Upper bound on the size of a downloaded image.
+ */
+#define REMOTE_IMAGE_MAX_BYTES ((10 * 1024) * 1024)
+
+/**
+ * Space between the columns of a multi-column block when `column-gap` is
+ * not set.
+ */
+#define MULTI_COLUMN_GAP 16.0
+
+/**
+ * `max-w-prose`: 65ch of 16 px Helvetica, whose "0" is 0.556 em wide.
+ */
+#define PROSE_MAX_WIDTH 578.0
+
+/**
+ * Default space between a list marker and its item, in points.
+ */
+#define LIST_MARKER_GAP 6.0
+
+/**
+ * Default rasterization resolution for SVG images, in dots per inch.
+ */
+#define DEFAULT_SVG_DPI 144.0
+
+/**
+ * Return codes of the `rpdf_*` functions.
+ *
+ */
+typedef enum RpdfError {
+  /**
+   * Success.
+   */
+  RpdfError_Ok = 0,
+  /**
+   * A required pointer argument was null.
+   */
+  RpdfError_NullPointer = 1,
+  /**
+   * The HTML or layout JSON input is not valid UTF-8.
+   */
+  RpdfError_InvalidUtf8 = 2,
+  /**
+   * Parsing, layout or pagination failed, or the layout JSON is invalid.
+   */
+  RpdfError_Pipeline = 3,
+  /**
+   * Rendering a layout to PDF failed.
+   */
+  RpdfError_Render = 4,
+  /**
+   * The write callback of `rpdf_generate_pdf_streamed` returned non-zero.
+   */
+  RpdfError_CallbackAborted = 5,
+  /**
+   * The bytes passed to `rpdf_register_font` are not a TTF/OTF font.
+   */
+  RpdfError_InvalidFont = 6,
+} RpdfError;
+
 /**
  * Page orientation for use in [`RpdfPipelineConfig`].
  */
@@ -83,6 +175,12 @@ typedef struct RpdfPipelineConfig {
   enum RpdfPageOrientation orientation;
 } RpdfPipelineConfig;
 
+/**
+ * Receives one chunk of PDF output. `chunk_ptr` is only valid for the
+ * duration of the call. Return `0` to continue, or non-zero to abort.
+ */
+typedef int (*RpdfWriteCallback)(const uint8_t *chunk_ptr, uint32_t chunk_len, void *user_data);
+
 
 
 
@@ -99,7 +197,7 @@ typedef struct RpdfPipelineConfig {
  * - `out_len`: on success, receives the length of the PDF buffer
  *
  * # Returns
- * `0` on success, non-zero on error. On error, call `rpdf_last_error`.
+ * `0` on success, otherwise an [`RpdfError`] code. On error, call `rpdf_last_error`.
  *
  * # Safety
  * - `html_ptr` must point to `html_len` valid bytes.
@@ -141,6 +239,11 @@ int rpdf_generate_pdf_with_layout(const uint8_t *html_ptr,
  *
  * # Returns
  * `0` on success.
+ *
+ * # Safety
+ * - `html_ptr` must point to `html_len` valid bytes.
+ * - `out_json_ptr` must be a valid pointer.
+ * - The caller must free `*out_json_ptr` with `rpdf_free_string`.
  */
 int rpdf_compute_layout(const uint8_t *html_ptr, uint32_t html_len, char **out_json_ptr);
 
@@ -168,6 +271,28 @@ int rpdf_generate_pdf_ex(const uint8_t *html_ptr,
                          uint8_t **out_buf,
                          uint32_t *out_len);
 
+/**
+ * Like [`rpdf_generate_pdf_ex`], but also reports how many pages the PDF has.
+ *
+ * # Parameters
+ * - `html_ptr`, `html_len`: UTF-8 HTML input
+ * - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+ * - `out_buf`, `out_len`: PDF output
+ * - `out_page_count`: receives the number of pages
+ *
+ * # Returns
+ * `0` on success.
+ *
+ * # Safety
+ * Same as `rpdf_generate_pdf_ex`; `out_page_count` must be a valid pointer.
+ */
+int rpdf_generate_pdf_ex2(const uint8_t *html_ptr,
+                          uint32_t html_len,
+                          const struct RpdfPipelineConfig *cfg,
+                          uint8_t **out_buf,
+                          uint32_t *out_len,
+                          uint32_t *out_page_count);
+
 /**
  * Generate a PDF and layout JSON from HTML with a custom [`RpdfPipelineConfig`].
  *
@@ -209,13 +334,137 @@ int rpdf_compute_layout_ex(const uint8_t *html_ptr,
                            const struct RpdfPipelineConfig *cfg,
                            char **out_json_ptr);
 
+/**
+ * Lay out HTML and return its text boxes as a flat JSON array.
+ *
+ * Each element is `{"page", "x", "y", "width", "height", "text"}`: the
+ * zero-based page index, the box in points from the page's top-left corner,
+ * and its wrapped lines joined by `\n`. Useful for comparing against OCR
+ * output or extracting coordinates without walking the layout JSON.
+ *
+ * # Parameters
+ * - `html_ptr`, `html_len`: UTF-8 HTML input
+ * - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+ * - `out_json_ptr`: text box JSON output (free with `rpdf_free_string`)
+ *
+ * # Returns
+ * `0` on success.
+ *
+ * # Safety
+ * Same as `rpdf_generate_pdf_ex`.
+ */
+int rpdf_get_text_boxes(const uint8_t *html_ptr,
+                        uint32_t html_len,
+                        const struct RpdfPipelineConfig *cfg,
+                        char **out_json_ptr);
+
 /**
  * Render a PDF from a layout config JSON string.
  *
  * This allows pre-computing the layout and rendering separately.
+ *
+ * # Safety
+ * - `json_ptr` must point to a valid null-terminated string.
+ * - `out_buf` and `out_len` must be valid pointers.
+ * - The caller must free `*out_buf` with `rpdf_free_buffer`.
  */
 int rpdf_render_from_layout(const char *json_ptr, uint8_t **out_buf, uint32_t *out_len);
 
+/**
+ * Like [`rpdf_render_from_layout`], but text whose `font_family` (and
+ * weight/style) matches a font registered with `rpdf_register_font` is drawn
+ * with, and embeds, that font.
+ *
+ * Safe to call from several threads at once; registering a font waits for
+ * in-flight renders to finish.
+ *
+ * # Safety
+ * Same as `rpdf_render_from_layout`.
+ */
+int rpdf_render_from_layout_with_fonts(const char *json_ptr, uint8_t **out_buf, uint32_t *out_len);
+
+/**
+ * Register a TTF/OTF font for `rpdf_render_from_layout_with_fonts`.
+ *
+ * Layout text whose `font_family`, bold and italic flags match is drawn with
+ * this font. Registering the same family/weight/style again replaces it.
+ *
+ * # Parameters
+ * - `family`: null-terminated UTF-8 family name, e.g. `"Inter"`
+ * - `bold`, `italic`: the face's weight and style
+ * - `font_ptr`, `font_len`: the font file bytes (copied)
+ *
+ * # Returns
+ * `0` on success, [`RpdfError::InvalidFont`] if the bytes cannot be parsed.
+ *
+ * # Safety
+ * - `family` must point to a valid null-terminated string.
+ * - `font_ptr` must point to `font_len` valid bytes.
+ */
+int rpdf_register_font(const char *family,
+                       bool bold,
+                       bool italic,
+                       const uint8_t *font_ptr,
+                       uint32_t font_len);
+
+/**
+ * Remove every font registered with `rpdf_register_font`.
+ */
+void rpdf_clear_fonts(void);
+
+/**
+ * Render several HTML documents into a single PDF, each starting on a fresh
+ * page.
+ *
+ * # Parameters
+ * - `htmls_ptr`: array of `count` pointers to UTF-8 HTML inputs
+ * - `lens_ptr`: array of `count` input lengths in bytes
+ * - `count`: number of inputs (at least 1)
+ * - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+ * - `out_buf`, `out_len`: PDF output
+ *
+ * # Returns
+ * `0` on success.
+ *
+ * # Safety
+ * - `htmls_ptr` and `lens_ptr` must each point to `count` valid elements,
+ *   and every `htmls_ptr[i]` must point to `lens_ptr[i]` valid bytes.
+ * - `cfg`, if non-null, must satisfy the requirements of `rpdf_generate_pdf_ex`.
+ * - The caller must free `*out_buf` with `rpdf_free_buffer`.
+ */
+int rpdf_generate_pdf_multi(const uint8_t *const *htmls_ptr,
+                            const uint32_t *lens_ptr,
+                            uint32_t count,
+                            const struct RpdfPipelineConfig *cfg,
+                            uint8_t **out_buf,
+                            uint32_t *out_len);
+
+/**
+ * Generate a PDF and pass it to `write_cb` in chunks instead of returning
+ * a buffer, so callers can write straight to a file or socket without
+ * holding a second copy.
+ *
+ * # Parameters
+ * - `html_ptr`, `html_len`: UTF-8 HTML input
+ * - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+ * - `write_cb`: called with each chunk of PDF bytes, in order
+ * - `user_data`: passed through to every `write_cb` call
+ *
+ * # Returns
+ * `0` on success. [`RpdfError::CallbackAborted`] if `write_cb` returned
+ * non-zero; no further chunks are delivered after that.
+ *
+ * # Safety
+ * - `html_ptr` must point to `html_len` valid bytes.
+ * - `cfg`, if non-null, must satisfy the requirements of `rpdf_generate_pdf_ex`.
+ * - `write_cb` must be safe to call with `user_data`.
+ */
+int rpdf_generate_pdf_streamed(const uint8_t *html_ptr,
+                               uint32_t html_len,
+                               const struct RpdfPipelineConfig *cfg,
+                               RpdfWriteCallback write_cb,
+                               void *user_data);
+
 /**
  * Free a PDF buffer returned by `rpdf_generate_pdf`.
  *
@@ -244,6 +493,18 @@ void rpdf_free_string(char *s);
  */
 const char *rpdf_last_error(void);
 
+/**
+ * Number of images left out of the last PDF generated on this thread
+ * because they could not be decoded or had no size.
+ *
+ * Set by every function that produces a PDF: `rpdf_generate_pdf`, its
+ * `_ex`, `_ex2`, `_multi` and `_streamed` variants, the `*_with_layout`
+ * variants and the `rpdf_render_from_layout*` functions. A non-zero count
+ * alongside a `0` return code means the PDF was produced without those
+ * images; callers decide whether that is acceptable.
+ */
+uint32_t rpdf_last_skipped_images(void);
+
 /**
  * Return the library version as a null-terminated string.
  * The caller must **not** free this pointer.
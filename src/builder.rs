@@ -0,0 +1,183 @@
+//! Fluent document builder – assemble a document from structured data
+//! without writing HTML.
+//!
+//! The builder produces the same DOM nodes the HTML parser would, so styling,
+//! layout and pagination behave identically to the equivalent markup:
+//!
+//! ```
+//! use pdf_forge::builder::Document;
+//! use pdf_forge::pipeline::PipelineConfig;
+//!
+//! let layout = Document::new()
+//!     .heading(1, "Invoice #42")
+//!     .paragraph("Thank you for your business.")
+//!     .table_with_header(&["Item", "Total"], &[vec!["Hosting", "$500.00"]])
+//!     .layout_config(&PipelineConfig::default());
+//! assert_eq!(layout.pages.len(), 1);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::dom::{DomNode, ElementNode, Tag};
+use crate::error::ForgeError;
+use crate::fonts::FontManager;
+use crate::images::ImageCache;
+use crate::layout_config::LayoutConfig;
+use crate::pipeline::{
+    compute_layout_config_from_styled, render_layout, style_dom, PipelineConfig,
+};
+use crate::style::StyledNode;
+
+/// A document under construction: an ordered list of top-level blocks.
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    blocks: Vec<DomNode>,
+}
+
+impl Document {
+    /// Create an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a heading. Levels outside `1..=3` are clamped.
+    pub fn heading(self, level: u8, text: &str) -> Self {
+        let tag = match level {
+            0 | 1 => Tag::H1,
+            2 => Tag::H2,
+            _ => Tag::H3,
+        };
+        self.push(element(tag, vec![text_node(text)]))
+    }
+
+    /// Append a paragraph of plain text.
+    pub fn paragraph(self, text: &str) -> Self {
+        self.push(element(Tag::P, vec![text_node(text)]))
+    }
+
+    /// Append a bulleted list.
+    pub fn list<S: AsRef<str>>(self, items: &[S]) -> Self {
+        self.push(element(Tag::Ul, list_items(items)))
+    }
+
+    /// Append a numbered list.
+    pub fn ordered_list<S: AsRef<str>>(self, items: &[S]) -> Self {
+        self.push(element(Tag::Ol, list_items(items)))
+    }
+
+    /// Append a table of `<td>` cells, one inner `Vec` per row.
+    pub fn table<S: AsRef<str>>(self, rows: &[Vec<S>]) -> Self {
+        self.push(element(Tag::Table, table_rows(rows)))
+    }
+
+    /// Append a table whose first row is a `<th>` header.
+    pub fn table_with_header<H: AsRef<str>, S: AsRef<str>>(
+        self,
+        header: &[H],
+        rows: &[Vec<S>],
+    ) -> Self {
+        let cells = header
+            .iter()
+            .map(|h| element(Tag::Th, vec![text_node(h.as_ref())]))
+            .collect();
+        let mut children = vec![element(Tag::Tr, cells)];
+        children.extend(table_rows(rows));
+        self.push(element(Tag::Table, children))
+    }
+
+    /// Append an image with an explicit size in points.
+    pub fn image(self, src: &str, width: f32, height: f32) -> Self {
+        let mut img = ElementNode::new(Tag::Img);
+        img.attributes.insert("src".to_string(), src.to_string());
+        img.attributes.insert(
+            "style".to_string(),
+            format!("width: {width}px; height: {height}px"),
+        );
+        self.push(DomNode::Element(img))
+    }
+
+    /// Force a page break after the content added so far.
+    pub fn page_break(self) -> Self {
+        self.push(element(Tag::Div, Vec::new()))
+            .with_class("page-break")
+    }
+
+    /// Add Tailwind-style classes to the most recently appended block.
+    pub fn with_class(mut self, classes: &str) -> Self {
+        if let Some(DomNode::Element(e)) = self.blocks.last_mut() {
+            let merged = match e.attributes.get("class") {
+                Some(existing) => format!("{existing} {classes}"),
+                None => classes.to_string(),
+            };
+            e.attributes.insert("class".to_string(), merged);
+        }
+        self
+    }
+
+    /// The document as DOM nodes, as `parse_html` would produce them.
+    pub fn dom(&self) -> &[DomNode] {
+        &self.blocks
+    }
+
+    /// Resolve styles for every block exactly as `layout_config` and
+    /// `generate_pdf` do, including the theme and base text settings.
+    pub fn styled_tree(&self, config: &PipelineConfig) -> Vec<StyledNode> {
+        style_dom(self.blocks.clone(), &config.style_options())
+    }
+
+    /// Lay out and paginate the document (no PDF rendering).
+    pub fn layout_config(&self, config: &PipelineConfig) -> LayoutConfig {
+        self.layout_with_images(config, &ImageCache::new(config.svg_dpi, config.image_dpi))
+    }
+
+    /// Render the document to PDF bytes, returning the layout as well.
+    pub fn generate_pdf(
+        &self,
+        config: &PipelineConfig,
+    ) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+        let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+        let layout = self.layout_with_images(config, &images);
+        render_layout(layout, config, &FontManager::default(), &images)
+    }
+
+    fn layout_with_images(&self, config: &PipelineConfig, images: &ImageCache) -> LayoutConfig {
+        let styled = self.styled_tree(config);
+        compute_layout_config_from_styled(&styled, config, &FontManager::default(), images)
+    }
+
+    fn push(mut self, node: DomNode) -> Self {
+        self.blocks.push(node);
+        self
+    }
+}
+
+fn element(tag: Tag, children: Vec<DomNode>) -> DomNode {
+    DomNode::Element(ElementNode {
+        tag,
+        attributes: HashMap::new(),
+        children,
+    })
+}
+
+fn text_node(text: &str) -> DomNode {
+    DomNode::Text(text.to_string())
+}
+
+fn list_items<S: AsRef<str>>(items: &[S]) -> Vec<DomNode> {
+    items
+        .iter()
+        .map(|item| element(Tag::Li, vec![text_node(item.as_ref())]))
+        .collect()
+}
+
+fn table_rows<S: AsRef<str>>(rows: &[Vec<S>]) -> Vec<DomNode> {
+    rows.iter()
+        .map(|row| {
+            let cells = row
+                .iter()
+                .map(|c| element(Tag::Td, vec![text_node(c.as_ref())]))
+                .collect();
+            element(Tag::Tr, cells)
+        })
+        .collect()
+}
@@ -0,0 +1,161 @@
+//! Memoised parsing and styling for workloads that render the same HTML
+//! repeatedly, e.g. a server producing one report template per request.
+//!
+//! ```
+//! use pdf_forge::cache::PipelineCache;
+//! use pdf_forge::pipeline::PipelineConfig;
+//!
+//! let cache = PipelineCache::new();
+//! let config = PipelineConfig::default();
+//! cache.compute_layout_config("<p>Hello</p>", &config);
+//! cache.compute_layout_config("<p>Hello</p>", &config);
+//! assert_eq!((cache.hits(), cache.misses()), (1, 1));
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::error::ForgeError;
+use crate::fonts::FontManager;
+use crate::images::ImageCache;
+use crate::layout_config::LayoutConfig;
+use crate::pipeline::{
+    compute_layout_config_from_styled, render_layout, style_html, PipelineConfig,
+};
+use crate::style::StyledNode;
+
+/// Styled trees keyed by a hash of the HTML and the config fields that affect
+/// styling (theme, base text settings, hyphenation and image embedding).
+/// Layout and pagination still run on every call, since they depend on the
+/// page geometry and fonts.
+///
+/// The cache is safe to share between threads; entries are never evicted
+/// unless [`clear`](Self::clear) is called.
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    styled: Mutex<HashMap<u64, Arc<Vec<StyledNode>>>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl PipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cached equivalent of [`crate::pipeline::compute_layout_config`].
+    pub fn compute_layout_config(&self, html: &str, config: &PipelineConfig) -> LayoutConfig {
+        self.compute_layout_config_with_fonts(html, config, &FontManager::default())
+    }
+
+    /// Cached equivalent of
+    /// [`crate::pipeline::compute_layout_config_with_fonts`].
+    pub fn compute_layout_config_with_fonts(
+        &self,
+        html: &str,
+        config: &PipelineConfig,
+        fonts: &FontManager,
+    ) -> LayoutConfig {
+        let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+        compute_layout_config_from_styled(&self.styled(html, config), config, fonts, &images)
+    }
+
+    /// Cached equivalent of [`crate::pipeline::generate_pdf_with_fonts`].
+    pub fn generate_pdf_with_fonts(
+        &self,
+        html: &str,
+        config: &PipelineConfig,
+        fonts: &FontManager,
+    ) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+        let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+        let layout =
+            compute_layout_config_from_styled(&self.styled(html, config), config, fonts, &images);
+        render_layout(layout, config, fonts, &images)
+    }
+
+    /// Number of calls that reused a cached styled tree.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of calls that had to parse and style their HTML.
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Number of cached styled trees.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every cached tree. The hit and miss counters are kept.
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    /// The styled tree for `html`, parsing and styling it on a miss.
+    fn styled(&self, html: &str, config: &PipelineConfig) -> Arc<Vec<StyledNode>> {
+        let key = cache_key(html, config);
+        if let Some(styled) = self.entries().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Arc::clone(styled);
+        }
+        // Style outside the lock so other threads are not blocked on it.
+        let styled = Arc::new(style_html(html, config));
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        Arc::clone(self.entries().entry(key).or_insert(styled))
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Arc<Vec<StyledNode>>>> {
+        // A panic while holding the lock cannot leave the map half-updated.
+        self.styled.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Hash of the inputs [`style_html`] depends on.
+fn cache_key(html: &str, config: &PipelineConfig) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    html.hash(&mut hasher);
+    config.style_options().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_html_reuses_the_styled_tree() {
+        let cache = PipelineCache::new();
+        let config = PipelineConfig::default();
+        let html = "<h1>Report</h1><p>Body text</p>";
+
+        let first = cache.compute_layout_config(html, &config);
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+        let second = cache.compute_layout_config(html, &config);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(first.to_json(), second.to_json());
+
+        cache.compute_layout_config("<p>Other</p>", &config);
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 2));
+    }
+
+    #[test]
+    fn theme_changes_miss_the_cache() {
+        let cache = PipelineCache::new();
+        let mut config = PipelineConfig::default();
+        cache.compute_layout_config("<ul><li>a</li></ul>", &config);
+        config.theme.list_indent = 40.0;
+        cache.compute_layout_config("<ul><li>a</li></ul>", &config);
+        config.base_font_size = 12.0;
+        cache.compute_layout_config("<ul><li>a</li></ul>", &config);
+        assert_eq!((cache.hits(), cache.misses()), (0, 3));
+    }
+}
@@ -1,11 +1,19 @@
 //! HTML parser – converts an HTML string into a simple DOM tree.
 //!
 //! We support a controlled subset of elements:
-//! - Structural: div, p, h1-h3, ul, ol, li, table, tr, td, th, img
-//! - Inline: span
+//! - Structural: div, p, h1-h3, ul, ol, li, blockquote, table, caption,
+//!   colgroup, col, tr, td, th, img
+//! - Inline: span, b, strong, i, em, sub, sup
+//! - Raw text: script (discarded), style (kept as a single text child)
 //! - Styling via `class` and `style` attributes
+//!
+//! Other standard HTML elements are kept as [`Tag::Unknown`]; void elements
+//! such as `<br>` and `<meta>` never have children.
+//!
+//! [`serialize`] writes a tree back out as HTML.
 
 use std::collections::HashMap;
+use std::fmt;
 
 // ---------------------------------------------------------------------------
 // DOM types
@@ -22,20 +30,68 @@ pub enum Tag {
     Ul,
     Ol,
     Li,
+    Blockquote,
+    Pre,
     Table,
+    Caption,
+    Colgroup,
+    Col,
     Tr,
     Td,
     Th,
     Span,
+    A,
+    B,
+    Strong,
+    I,
+    Em,
+    Sub,
+    Sup,
+    /// Deleted text, struck through.
+    Del,
+    /// Inserted text, underlined.
+    Ins,
+    /// Highlighted text, on a yellow background.
+    Mark,
+    /// Fine print, smaller than the surrounding text.
+    Small,
     Img,
     Body,
     Html,
     Head,
+    /// Raw-text element whose content is discarded.
+    Script,
+    /// Raw-text element whose CSS is kept verbatim as its only child.
+    Style,
     /// Catch-all for unknown tags – they are kept but treated as divs.
     Unknown(String),
 }
 
+/// Elements that never have content or a closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Standard HTML elements, which a strict parse accepts even when they are
+/// not in the supported subset.
+const HTML_ELEMENTS: &str =
+    "a abbr address area article aside audio b base bdi bdo blockquote body br button \
+    canvas caption cite code col colgroup data datalist dd del details dfn dialog div dl \
+    dt em embed fieldset figcaption figure footer form h1 h2 h3 h4 h5 h6 head header \
+    hgroup hr html i iframe img input ins kbd label legend li link main map mark menu \
+    meta meter nav noscript object ol optgroup option output p picture pre progress q rp \
+    rt ruby s samp script search section select slot small source span strong style sub \
+    summary sup table tbody td template textarea tfoot th thead time title tr track u ul \
+    var video wbr";
+
+/// Whether `name` is a void element such as `br` or `meta`.
+fn is_void_element(name: &str) -> bool {
+    VOID_ELEMENTS.contains(&name.to_ascii_lowercase().as_str())
+}
+
 impl Tag {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_ascii_lowercase().as_str() {
             "div" => Tag::Div,
@@ -46,19 +102,79 @@ impl Tag {
             "ul" => Tag::Ul,
             "ol" => Tag::Ol,
             "li" => Tag::Li,
+            "blockquote" => Tag::Blockquote,
+            "pre" => Tag::Pre,
             "table" => Tag::Table,
+            "caption" => Tag::Caption,
+            "colgroup" => Tag::Colgroup,
+            "col" => Tag::Col,
             "tr" => Tag::Tr,
             "td" => Tag::Td,
             "th" => Tag::Th,
             "span" => Tag::Span,
+            "a" => Tag::A,
+            "b" => Tag::B,
+            "strong" => Tag::Strong,
+            "i" => Tag::I,
+            "em" => Tag::Em,
+            "sub" => Tag::Sub,
+            "sup" => Tag::Sup,
+            "del" => Tag::Del,
+            "ins" => Tag::Ins,
+            "mark" => Tag::Mark,
+            "small" => Tag::Small,
             "img" => Tag::Img,
             "body" => Tag::Body,
             "html" => Tag::Html,
             "head" => Tag::Head,
+            "script" => Tag::Script,
+            "style" => Tag::Style,
             _ => Tag::Unknown(s.to_string()),
         }
     }
 
+    /// The tag name as written in HTML, e.g. `"div"`.
+    pub fn name(&self) -> &str {
+        match self {
+            Tag::Div => "div",
+            Tag::P => "p",
+            Tag::H1 => "h1",
+            Tag::H2 => "h2",
+            Tag::H3 => "h3",
+            Tag::Ul => "ul",
+            Tag::Ol => "ol",
+            Tag::Li => "li",
+            Tag::Blockquote => "blockquote",
+            Tag::Pre => "pre",
+            Tag::Table => "table",
+            Tag::Caption => "caption",
+            Tag::Colgroup => "colgroup",
+            Tag::Col => "col",
+            Tag::Tr => "tr",
+            Tag::Td => "td",
+            Tag::Th => "th",
+            Tag::Span => "span",
+            Tag::A => "a",
+            Tag::B => "b",
+            Tag::Strong => "strong",
+            Tag::I => "i",
+            Tag::Em => "em",
+            Tag::Sub => "sub",
+            Tag::Sup => "sup",
+            Tag::Del => "del",
+            Tag::Ins => "ins",
+            Tag::Mark => "mark",
+            Tag::Small => "small",
+            Tag::Img => "img",
+            Tag::Body => "body",
+            Tag::Html => "html",
+            Tag::Head => "head",
+            Tag::Script => "script",
+            Tag::Style => "style",
+            Tag::Unknown(name) => name,
+        }
+    }
+
     pub fn is_block(&self) -> bool {
         matches!(
             self,
@@ -70,7 +186,12 @@ impl Tag {
                 | Tag::Ul
                 | Tag::Ol
                 | Tag::Li
+                | Tag::Blockquote
+                | Tag::Pre
                 | Tag::Table
+                | Tag::Caption
+                | Tag::Colgroup
+                | Tag::Col
                 | Tag::Tr
                 | Tag::Td
                 | Tag::Th
@@ -81,23 +202,40 @@ impl Tag {
     }
 
     pub fn is_inline(&self) -> bool {
-        matches!(self, Tag::Span)
+        matches!(
+            self,
+            Tag::Span
+                | Tag::A
+                | Tag::B
+                | Tag::Strong
+                | Tag::I
+                | Tag::Em
+                | Tag::Sub
+                | Tag::Sup
+                | Tag::Del
+                | Tag::Ins
+                | Tag::Mark
+                | Tag::Small
+        )
     }
 
     pub fn is_table_part(&self) -> bool {
-        matches!(self, Tag::Table | Tag::Tr | Tag::Td | Tag::Th)
+        matches!(
+            self,
+            Tag::Table | Tag::Caption | Tag::Colgroup | Tag::Col | Tag::Tr | Tag::Td | Tag::Th
+        )
     }
 }
 
 /// A node in our DOM tree.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DomNode {
     Element(ElementNode),
     Text(String),
 }
 
 /// An element node carrying tag, attributes, and children.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ElementNode {
     pub tag: Tag,
     pub attributes: HashMap<String, String>,
@@ -127,6 +265,12 @@ impl ElementNode {
     pub fn src(&self) -> Option<&str> {
         self.attributes.get("src").map(|s| s.as_str())
     }
+
+    /// Whether the attribute is present, with or without a value, as for
+    /// boolean attributes like `hidden`.
+    pub fn has_attr(&self, name: &str) -> bool {
+        self.attributes.contains_key(name)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -138,28 +282,150 @@ impl ElementNode {
 /// We use a hand-written parser that handles the controlled subset. This keeps
 /// dependencies minimal and avoids the complexity of a full HTML5 parser for
 /// our constrained template inputs.
+///
+/// Parsing is lenient: malformed markup is repaired rather than rejected. Use
+/// [`parse_html_with_options`] to have it reported instead.
 pub fn parse_html(html: &str) -> Vec<DomNode> {
-    let mut parser = Parser::new(html);
+    let mut parser = Parser::new(html, ParseOptions::default());
     parser.parse_nodes()
 }
 
+/// Parse an HTML string, reporting malformed markup according to `options`.
+///
+/// In lenient mode this never fails and returns the same tree as
+/// [`parse_html`]. In strict mode the first unclosed element, mismatched or
+/// stray closing tag, or non-standard tag is returned as a [`ParseError`].
+pub fn parse_html_with_options(
+    html: &str,
+    options: ParseOptions,
+) -> Result<Vec<DomNode>, ParseError> {
+    let mut parser = Parser::new(html, options);
+    let nodes = parser.parse_nodes();
+    match parser.error {
+        Some(err) => Err(err),
+        None => Ok(nodes),
+    }
+}
+
+/// How strictly [`parse_html_with_options`] treats malformed markup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Reject unclosed elements, mismatched or stray closing tags and tags
+    /// that are not standard HTML elements instead of repairing them.
+    pub strict: bool,
+}
+
+impl ParseOptions {
+    /// Options that repair malformed markup, as [`parse_html`] does.
+    pub fn lenient() -> Self {
+        Self { strict: false }
+    }
+
+    /// Options that reject malformed markup.
+    pub fn strict() -> Self {
+        Self { strict: true }
+    }
+}
+
+/// Malformed markup found by a strict parse. Offsets are byte positions of
+/// the offending tag's `<` in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// An element was still open at the end of the input.
+    UnclosedTag { tag: String, offset: usize },
+    /// A closing tag does not match the element it closes.
+    MismatchedClose {
+        expected: String,
+        found: String,
+        offset: usize,
+    },
+    /// A closing tag appears where no element is open.
+    UnexpectedClose { tag: String, offset: usize },
+    /// A tag that is not a standard HTML element.
+    UnknownTag { tag: String, offset: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnclosedTag { tag, offset } => {
+                write!(f, "<{tag}> at byte {offset} is never closed")
+            }
+            ParseError::MismatchedClose {
+                expected,
+                found,
+                offset,
+            } => write!(f, "</{found}> at byte {offset} does not close <{expected}>"),
+            ParseError::UnexpectedClose { tag, offset } => {
+                write!(f, "</{tag}> at byte {offset} has no open element")
+            }
+            ParseError::UnknownTag { tag, offset } => {
+                write!(f, "unsupported tag <{tag}> at byte {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 struct Parser<'a> {
     input: &'a str,
     pos: usize,
+    options: ParseOptions,
+    /// Lower-cased names of the elements currently being parsed, outermost
+    /// first, so a closing tag can be matched against every open element.
+    open: Vec<String>,
+    /// The first problem found in strict mode.
+    error: Option<ParseError>,
 }
 
 impl<'a> Parser<'a> {
-    fn new(input: &'a str) -> Self {
-        Self { input, pos: 0 }
+    fn new(input: &'a str, options: ParseOptions) -> Self {
+        Self {
+            input,
+            pos: 0,
+            options,
+            open: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn report(&mut self, err: ParseError) {
+        if self.options.strict && self.error.is_none() {
+            self.error = Some(err);
+        }
     }
 
     fn parse_nodes(&mut self) -> Vec<DomNode> {
         let mut nodes = Vec::new();
         loop {
+            let before = self.pos;
             self.skip_whitespace_preserve();
-            if self.eof() || self.starts_with("</") {
+            if self.eof() {
                 break;
             }
+            // Whitespace between two inline elements separates their words,
+            // so it collapses to a single space instead of being dropped.
+            if self.pos > before
+                && nodes.last().is_some_and(is_inline_node)
+                && !self.starts_with("</")
+                && Tag::from_str(&self.peek_tag_name(1)).is_inline()
+            {
+                nodes.push(DomNode::Text(" ".to_string()));
+            }
+            if self.starts_with("</") {
+                let name = self.peek_close_name();
+                if self.open.contains(&name) {
+                    // Closes this element or one of its ancestors; the
+                    // element that owns it consumes it.
+                    break;
+                }
+                // A closer for nothing that is open: drop it and keep going.
+                let offset = self.pos;
+                self.skip_close_tag();
+                self.report(ParseError::UnexpectedClose { tag: name, offset });
+                continue;
+            }
             if let Some(node) = self.parse_node() {
                 nodes.push(node);
             }
@@ -200,9 +466,17 @@ impl<'a> Parser<'a> {
 
     fn parse_element(&mut self) -> DomNode {
         // Consume '<'
+        let offset = self.pos;
         self.advance(1);
         let tag_name = self.parse_tag_name();
         let tag = Tag::from_str(&tag_name);
+        let name = tag_name.to_ascii_lowercase();
+        if !HTML_ELEMENTS.split_whitespace().any(|known| known == name) {
+            self.report(ParseError::UnknownTag {
+                tag: tag_name.clone(),
+                offset,
+            });
+        }
         let mut elem = ElementNode::new(tag.clone());
 
         // Parse attributes
@@ -216,7 +490,7 @@ impl<'a> Parser<'a> {
         }
 
         // Self-closing tags
-        let self_closing = tag == Tag::Img;
+        let self_closing = is_void_element(&tag_name);
         if self.starts_with("/>") {
             self.advance(2);
             return DomNode::Element(elem);
@@ -227,18 +501,39 @@ impl<'a> Parser<'a> {
         if self_closing {
             return DomNode::Element(elem);
         }
+        if matches!(tag, Tag::Script | Tag::Style) {
+            let content = self.parse_raw_text(&tag_name, offset);
+            if tag == Tag::Style && !content.is_empty() {
+                elem.children.push(DomNode::Text(content));
+            }
+            return DomNode::Element(elem);
+        }
 
         // Parse children
+        self.open.push(tag_name.to_ascii_lowercase());
         elem.children = self.parse_nodes();
+        self.open.pop();
 
-        // Consume closing tag
+        // Consume our closing tag. A closer for an ancestor implicitly ends
+        // this element too and is left for the ancestor, so `<b><i>x</b>`
+        // closes both.
         if self.starts_with("</") {
-            self.advance(2);
-            self.parse_tag_name(); // skip tag name
-            self.skip_whitespace();
-            if self.starts_with(">") {
-                self.advance(1);
+            let close_offset = self.pos;
+            let close_name = self.peek_close_name();
+            if close_name.eq_ignore_ascii_case(&tag_name) {
+                self.skip_close_tag();
+            } else {
+                self.report(ParseError::MismatchedClose {
+                    expected: tag_name,
+                    found: close_name,
+                    offset: close_offset,
+                });
             }
+        } else {
+            self.report(ParseError::UnclosedTag {
+                tag: tag_name,
+                offset,
+            });
         }
 
         DomNode::Element(elem)
@@ -257,6 +552,49 @@ impl<'a> Parser<'a> {
         self.input[start..self.pos].to_string()
     }
 
+    /// Consume the verbatim content of a raw-text element up to and including
+    /// its closing tag; `<` and `>` inside it are not markup.
+    fn parse_raw_text(&mut self, tag_name: &str, offset: usize) -> String {
+        let closer = format!("</{}", tag_name.to_ascii_lowercase());
+        let rest = self.input[self.pos..].to_ascii_lowercase();
+        let Some(len) = rest.find(&closer) else {
+            let content = self.input[self.pos..].to_string();
+            self.pos = self.input.len();
+            self.report(ParseError::UnclosedTag {
+                tag: tag_name.to_string(),
+                offset,
+            });
+            return content;
+        };
+        let content = self.input[self.pos..self.pos + len].to_string();
+        self.pos += len;
+        self.skip_close_tag();
+        content
+    }
+
+    /// The lower-cased name of the closing tag at the cursor.
+    fn peek_close_name(&mut self) -> String {
+        self.peek_tag_name(2).to_ascii_lowercase()
+    }
+
+    /// The tag name starting `skip` characters past the cursor.
+    fn peek_tag_name(&mut self, skip: usize) -> String {
+        let saved = self.pos;
+        self.advance(skip);
+        let name = self.parse_tag_name();
+        self.pos = saved;
+        name
+    }
+
+    fn skip_close_tag(&mut self) {
+        self.advance(2);
+        self.parse_tag_name();
+        self.skip_whitespace();
+        if self.starts_with(">") {
+            self.advance(1);
+        }
+    }
+
     fn parse_attribute(&mut self) -> (String, String) {
         let key = self.parse_tag_name();
         self.skip_whitespace();
@@ -355,14 +693,115 @@ impl<'a> Parser<'a> {
     }
 }
 
+fn is_inline_node(node: &DomNode) -> bool {
+    match node {
+        DomNode::Text(_) => true,
+        DomNode::Element(e) => e.tag.is_inline(),
+    }
+}
+
+/// Named and numeric character references the parser understands.
+const ENTITIES: [(&str, char); 8] = [
+    ("&amp;", '&'),
+    ("&lt;", '<'),
+    ("&gt;", '>'),
+    ("&quot;", '"'),
+    ("&#39;", '\''),
+    ("&apos;", '\''),
+    ("&nbsp;", '\u{00A0}'),
+    ("&shy;", '\u{00AD}'),
+];
+
+/// Decode character references in one pass, so an escaped reference such as
+/// `&amp;lt;` yields `&lt;` rather than `<`.
 fn decode_entities(s: &str) -> String {
-    s.replace("&amp;", "&")
-        .replace("&lt;", "<")
-        .replace("&gt;", ">")
-        .replace("&quot;", "\"")
-        .replace("&#39;", "'")
-        .replace("&apos;", "'")
-        .replace("&nbsp;", "\u{00A0}")
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        match ENTITIES.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, ch)) => {
+                out.push(*ch);
+                rest = &rest[name.len()..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Serializer
+// ---------------------------------------------------------------------------
+
+/// Write a DOM tree back out as HTML that parses to the same tree.
+///
+/// Text and attribute values are escaped; `<style>` content is written
+/// verbatim. Attributes are sorted by name so the output is stable, and
+/// valueless attributes are written bare (`<div hidden>`). Void elements
+/// such as `<br>` are written without children or a closing tag.
+pub fn serialize(nodes: &[DomNode]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        serialize_node(node, &mut out);
+    }
+    out
+}
+
+fn serialize_node(node: &DomNode, out: &mut String) {
+    let e = match node {
+        DomNode::Text(text) => {
+            escape_into(text, false, out);
+            return;
+        }
+        DomNode::Element(e) => e,
+    };
+    out.push('<');
+    out.push_str(e.tag.name());
+    let mut attrs: Vec<_> = e.attributes.iter().collect();
+    attrs.sort();
+    for (key, value) in attrs {
+        out.push(' ');
+        out.push_str(key);
+        if !value.is_empty() {
+            out.push_str("=\"");
+            escape_into(value, true, out);
+            out.push('"');
+        }
+    }
+    out.push('>');
+    if is_void_element(e.tag.name()) {
+        return;
+    }
+    for child in &e.children {
+        match (child, &e.tag) {
+            (DomNode::Text(css), Tag::Style) => out.push_str(css),
+            _ => serialize_node(child, out),
+        }
+    }
+    out.push_str("</");
+    out.push_str(e.tag.name());
+    out.push('>');
+}
+
+/// Append `s` with markup characters escaped, and quotes too in attributes.
+fn escape_into(s: &str, attribute: bool, out: &mut String) {
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '\u{00A0}' => out.push_str("&nbsp;"),
+            '\u{00AD}' => out.push_str("&shy;"),
+            _ => out.push(ch),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -389,6 +828,25 @@ pub fn body_children(nodes: &[DomNode]) -> Vec<DomNode> {
     nodes.to_vec()
 }
 
+/// Collect the CSS text of every `<style>` element in document order,
+/// including those in `<head>`.
+pub fn style_sheets(nodes: &[DomNode]) -> Vec<String> {
+    let mut sheets = Vec::new();
+    for node in nodes {
+        let DomNode::Element(e) = node else { continue };
+        if e.tag == Tag::Style {
+            for child in &e.children {
+                if let DomNode::Text(css) = child {
+                    sheets.push(css.clone());
+                }
+            }
+        } else {
+            sheets.extend(style_sheets(&e.children));
+        }
+    }
+    sheets
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -407,6 +865,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn serialize_round_trips_through_the_parser() {
+        let html = r#"<div class="card" data-note='say "hi" &amp; wave' hidden>
+            <style>p > b { color: red }</style>
+            <h1>Tom &amp; Jerry &lt;3</h1>
+            <p>Hello <b>bold</b> <i>world</i>&nbsp;x&shy;y &amp;lt;</p>
+            <table><colgroup><col width="40"></colgroup><tr><td colspan="2">a</td></tr></table>
+            <img src="data:image/png;base64,AAAA">
+            <pre>  keep   this  </pre>
+        </div>"#;
+        let nodes = parse_html(html);
+        let out = serialize(&nodes);
+        assert_eq!(parse_html(&out), nodes, "{out}");
+        assert!(out.contains(r#"data-note="say &quot;hi&quot; &amp; wave" hidden>"#));
+        assert!(out.contains("<p>Hello <b>bold</b> <i>world</i>&nbsp;x&shy;y &amp;lt;</p>"));
+        assert!(out.contains("<style>p > b { color: red }</style>"));
+
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "html") {
+                continue;
+            }
+            let nodes = parse_html(&std::fs::read_to_string(&path).unwrap());
+            assert_eq!(parse_html(&serialize(&nodes)), nodes, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn serialize_writes_void_elements_without_a_close_tag() {
+        let html =
+            r#"<head><meta charset="utf-8"><title>T</title></head><p>a<br>b</p><hr><p>x</p>"#;
+        assert_eq!(serialize(&parse_html(html)), html);
+
+        // A void element built with children still has none in the output.
+        let mut br = ElementNode::new(Tag::Unknown("br".into()));
+        br.children.push(DomNode::Text("lost".into()));
+        assert_eq!(serialize(&[DomNode::Element(br)]), "<br>");
+    }
+
     #[test]
     fn parse_self_closing_img() {
         let html = r#"<img src="logo.png" />"#;
@@ -445,4 +943,114 @@ mod tests {
             panic!("Expected table");
         }
     }
+
+    #[test]
+    fn mismatched_closers_close_intermediate_elements() {
+        let nodes = parse_html("<p><b><i>x</b>y</i>z</p>");
+        assert_eq!(nodes.len(), 1);
+        let DomNode::Element(p) = &nodes[0] else {
+            panic!("Expected p element");
+        };
+        // <p><b><i>x</i></b>y z</p>: </b> closes the <i> it skipped over and
+        // the stray </i> is dropped, keeping every piece of text.
+        assert_eq!(p.children.len(), 3);
+        let DomNode::Element(b) = &p.children[0] else {
+            panic!("Expected b element");
+        };
+        assert_eq!(b.tag, Tag::B);
+        let DomNode::Element(i) = &b.children[0] else {
+            panic!("Expected i element");
+        };
+        assert_eq!(i.tag, Tag::I);
+        assert!(matches!(&i.children[..], [DomNode::Text(t)] if t == "x"));
+        assert!(matches!(&p.children[1], DomNode::Text(t) if t == "y"));
+        assert!(matches!(&p.children[2], DomNode::Text(t) if t == "z"));
+    }
+
+    #[test]
+    fn whitespace_between_inline_elements_is_kept() {
+        let nodes = parse_html("<p><span>a</span>\n  <span>b</span></p>\n<p>c</p>");
+        assert_eq!(nodes.len(), 2);
+        let DomNode::Element(p) = &nodes[0] else {
+            panic!("Expected p element");
+        };
+        assert_eq!(p.children.len(), 3);
+        assert!(matches!(&p.children[1], DomNode::Text(t) if t == " "));
+    }
+
+    #[test]
+    fn stray_closer_at_top_level_does_not_drop_content() {
+        let nodes = parse_html("<p>a</p></div><p>b</p>");
+        assert_eq!(nodes.len(), 2);
+    }
+
+    #[test]
+    fn style_and_script_bodies_are_raw_text() {
+        let html = "<style>a{content:'<'}</style><script>if (a < b && c > d) {}</script><p>x</p>";
+        let nodes = parse_html(html);
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(style_sheets(&nodes), vec!["a{content:'<'}".to_string()]);
+        let DomNode::Element(script) = &nodes[1] else {
+            panic!("Expected script element");
+        };
+        assert_eq!(script.tag, Tag::Script);
+        assert!(script.children.is_empty());
+        let DomNode::Element(p) = &nodes[2] else {
+            panic!("Expected p element");
+        };
+        assert_eq!(p.tag, Tag::P);
+        assert!(matches!(&p.children[..], [DomNode::Text(t)] if t == "x"));
+    }
+
+    #[test]
+    fn strict_parse_flags_an_unclosed_div() {
+        let html = "<div><p>Hello</p>";
+        assert_eq!(
+            parse_html_with_options(html, ParseOptions::strict()).unwrap_err(),
+            ParseError::UnclosedTag {
+                tag: "div".into(),
+                offset: 0
+            }
+        );
+        // Lenient parsing still repairs it.
+        let nodes = parse_html_with_options(html, ParseOptions::lenient()).unwrap();
+        assert_eq!(nodes.len(), 1);
+    }
+
+    #[test]
+    fn strict_parse_flags_mismatched_stray_and_unknown_tags() {
+        let strict = ParseOptions::strict();
+        assert!(matches!(
+            parse_html_with_options("<b><i>x</b></i>", strict),
+            Err(ParseError::MismatchedClose { .. })
+        ));
+        assert!(matches!(
+            parse_html_with_options("<p>x</p></div>", strict),
+            Err(ParseError::UnexpectedClose { offset: 8, .. })
+        ));
+        assert!(matches!(
+            parse_html_with_options("<marquee>x</marquee>", strict),
+            Err(ParseError::UnknownTag { .. })
+        ));
+        assert!(parse_html_with_options("<div><img src=\"a.png\"><p>x</p></div>", strict).is_ok());
+    }
+
+    #[test]
+    fn strict_parse_accepts_standard_elements() {
+        let html = "<html><head><meta charset=\"utf-8\"><title>T</title></head><body>\
+                    <p>a<br>b</p><hr><table><thead><tr><th>h</th></tr></thead>\
+                    <tbody><tr><td>d</td></tr></tbody></table></body></html>";
+        let nodes = parse_html_with_options(html, ParseOptions::strict()).unwrap();
+        let DomNode::Element(html) = &nodes[0] else {
+            panic!("expected <html>");
+        };
+        let DomNode::Element(head) = &html.children[0] else {
+            panic!("expected <head>");
+        };
+        let DomNode::Element(meta) = &head.children[0] else {
+            panic!("expected <meta>");
+        };
+        assert_eq!(meta.tag, Tag::Unknown("meta".into()));
+        assert!(meta.children.is_empty());
+    }
 }
@@ -0,0 +1,44 @@
+//! Error type returned by the crate's fallible public APIs.
+
+use std::fmt;
+
+/// Why a pipeline stage failed. Each variant carries a human-readable
+/// description; [`fmt::Display`] prefixes it with the kind of failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForgeError {
+    /// The input cannot be processed, e.g. an empty list of documents.
+    InvalidInput(String),
+    /// A serialised [`LayoutConfig`](crate::layout_config::LayoutConfig) is
+    /// not valid JSON or does not match the schema.
+    InvalidLayoutJson(String),
+    /// A [`LayoutConfig`](crate::layout_config::LayoutConfig) holds values
+    /// that cannot be rendered, such as NaN coordinates.
+    InvalidLayout(String),
+    /// Font bytes could not be parsed or embedded.
+    Font(String),
+    /// An image could not be read, downloaded or decoded.
+    ImageDecode(String),
+    /// printpdf or the PDF post-processing step failed.
+    Render(String),
+}
+
+impl fmt::Display for ForgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForgeError::InvalidInput(msg) => write!(f, "invalid input: {msg}"),
+            ForgeError::InvalidLayoutJson(msg) => write!(f, "invalid layout JSON: {msg}"),
+            ForgeError::InvalidLayout(msg) => write!(f, "invalid layout: {msg}"),
+            ForgeError::Font(msg) => write!(f, "font error: {msg}"),
+            ForgeError::ImageDecode(msg) => write!(f, "image error: {msg}"),
+            ForgeError::Render(msg) => write!(f, "render error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ForgeError {}
+
+impl From<ForgeError> for String {
+    fn from(err: ForgeError) -> Self {
+        err.to_string()
+    }
+}
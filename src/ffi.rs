@@ -11,8 +11,11 @@
 //! - Passing a null pointer to a free function is a no-op.
 //!
 //! ## Error handling
-//! - Functions that can fail return a `c_int` (0 = success, non-zero = error).
+//! - Functions that can fail return a `c_int` holding an [`RpdfError`] code
+//!   (0 = success, non-zero = error).
 //! - Error details can be retrieved via `rpdf_last_error`.
+//! - Images that fail to decode are skipped rather than failing the call;
+//!   `rpdf_last_skipped_images` reports how many were left out.
 //!
 //! ## Thread safety
 //! - The `rpdf_last_error` uses a thread-local, so it is safe to call from
@@ -30,28 +33,95 @@
 //! import "C"
 //! ```
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_int, c_void};
 use std::ptr;
 use std::slice;
+use std::sync::{LazyLock, RwLock};
 
-use crate::pipeline::{generate_pdf, PageOrientation, PipelineConfig};
+use crate::error::ForgeError;
+use crate::fonts::FontManager;
+use crate::layout_config::LayoutConfig;
+use crate::pipeline::{
+    generate_pdf_multi_verbose, generate_pdf_verbose, PageOrientation, PipelineConfig,
+};
+use crate::render::{Warning, WarningKind};
 
 thread_local! {
-    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+    static LAST_SKIPPED_IMAGES: Cell<u32> = const { Cell::new(0) };
 }
 
+/// Fonts registered with `rpdf_register_font`, shared by all threads.
+static FONT_REGISTRY: LazyLock<RwLock<FontManager>> =
+    LazyLock::new(|| RwLock::new(FontManager::new()));
+
 fn set_last_error(msg: &str) {
     LAST_ERROR.with(|e| {
         *e.borrow_mut() = CString::new(msg).ok();
     });
 }
 
+/// Record `e` for `rpdf_last_error` and return its code: render failures
+/// have their own code, everything else is a pipeline error.
+fn fail(e: &ForgeError) -> c_int {
+    set_last_error(&e.to_string());
+    let code = match e {
+        ForgeError::Render(_) => RpdfError::Render,
+        _ => RpdfError::Pipeline,
+    };
+    code as c_int
+}
+
+/// Record the number of images a render skipped for
+/// `rpdf_last_skipped_images`; a failed render skipped none.
+fn record_skipped_images<T>(result: &Result<(T, Vec<Warning>), ForgeError>) {
+    let skipped = match result {
+        Ok((_, warnings)) => warnings
+            .iter()
+            .filter(|w| w.kind == WarningKind::SkippedImage)
+            .count(),
+        Err(_) => 0,
+    };
+    LAST_SKIPPED_IMAGES.with(|n| n.set(skipped as u32));
+}
+
+/// Run the pipeline on one document, recording the number of images it
+/// skipped for `rpdf_last_skipped_images`.
+fn generate(html: &str, config: &PipelineConfig) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+    let result = generate_pdf_verbose(html, config, &FontManager::default())
+        .map(|(bytes, layout, warnings)| ((bytes, layout), warnings));
+    record_skipped_images(&result);
+    result.map(|(output, _)| output)
+}
+
 // ---------------------------------------------------------------------------
 // C-compatible configuration types
 // ---------------------------------------------------------------------------
 
+/// Return codes of the `rpdf_*` functions.
+///
+/// cbindgen:prefix-with-name
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpdfError {
+    /// Success.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// The HTML or layout JSON input is not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// Parsing, layout or pagination failed, or the layout JSON is invalid.
+    Pipeline = 3,
+    /// Rendering a layout to PDF failed.
+    Render = 4,
+    /// The write callback of `rpdf_generate_pdf_streamed` returned non-zero.
+    CallbackAborted = 5,
+    /// The bytes passed to `rpdf_register_font` are not a TTF/OTF font.
+    InvalidFont = 6,
+}
+
 /// Page orientation for use in [`RpdfPipelineConfig`].
 #[repr(C)]
 pub enum RpdfPageOrientation {
@@ -126,6 +196,7 @@ unsafe fn pipeline_config_from_c(cfg: &RpdfPipelineConfig) -> PipelineConfig {
         page_height,
         page_margin,
         orientation,
+        ..defaults
     }
 }
 
@@ -142,7 +213,7 @@ unsafe fn pipeline_config_from_c(cfg: &RpdfPipelineConfig) -> PipelineConfig {
 /// - `out_len`: on success, receives the length of the PDF buffer
 ///
 /// # Returns
-/// `0` on success, non-zero on error. On error, call `rpdf_last_error`.
+/// `0` on success, otherwise an [`RpdfError`] code. On error, call `rpdf_last_error`.
 ///
 /// # Safety
 /// - `html_ptr` must point to `html_len` valid bytes.
@@ -157,7 +228,7 @@ pub unsafe extern "C" fn rpdf_generate_pdf(
 ) -> c_int {
     if html_ptr.is_null() || out_buf.is_null() || out_len.is_null() {
         set_last_error("Null pointer argument");
-        return 1;
+        return RpdfError::NullPointer as c_int;
     }
 
     let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
@@ -165,23 +236,20 @@ pub unsafe extern "C" fn rpdf_generate_pdf(
         Ok(s) => s,
         Err(e) => {
             set_last_error(&format!("Invalid UTF-8: {e}"));
-            return 2;
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
-    match generate_pdf(html, &PipelineConfig::default()) {
+    match generate(html, &PipelineConfig::default()) {
         Ok((pdf_bytes, _config)) => {
             let len = pdf_bytes.len() as u32;
             let buf = pdf_bytes.into_boxed_slice();
             let raw = Box::into_raw(buf) as *mut u8;
             *out_buf = raw;
             *out_len = len;
-            0
-        }
-        Err(e) => {
-            set_last_error(&e);
-            3
+            RpdfError::Ok as c_int
         }
+        Err(e) => fail(&e),
     }
 }
 
@@ -212,7 +280,7 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout(
         || out_json_ptr.is_null()
     {
         set_last_error("Null pointer argument");
-        return 1;
+        return RpdfError::NullPointer as c_int;
     }
 
     let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
@@ -220,11 +288,11 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout(
         Ok(s) => s,
         Err(e) => {
             set_last_error(&format!("Invalid UTF-8: {e}"));
-            return 2;
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
-    match generate_pdf(html, &PipelineConfig::default()) {
+    match generate(html, &PipelineConfig::default()) {
         Ok((pdf_bytes, layout_config)) => {
             // PDF bytes
             let len = pdf_bytes.len() as u32;
@@ -244,12 +312,9 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout(
                 }
             }
 
-            0
-        }
-        Err(e) => {
-            set_last_error(&e);
-            3
+            RpdfError::Ok as c_int
         }
+        Err(e) => fail(&e),
     }
 }
 
@@ -261,6 +326,11 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout(
 ///
 /// # Returns
 /// `0` on success.
+///
+/// # Safety
+/// - `html_ptr` must point to `html_len` valid bytes.
+/// - `out_json_ptr` must be a valid pointer.
+/// - The caller must free `*out_json_ptr` with `rpdf_free_string`.
 #[no_mangle]
 pub unsafe extern "C" fn rpdf_compute_layout(
     html_ptr: *const u8,
@@ -269,7 +339,7 @@ pub unsafe extern "C" fn rpdf_compute_layout(
 ) -> c_int {
     if html_ptr.is_null() || out_json_ptr.is_null() {
         set_last_error("Null pointer argument");
-        return 1;
+        return RpdfError::NullPointer as c_int;
     }
 
     let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
@@ -277,7 +347,7 @@ pub unsafe extern "C" fn rpdf_compute_layout(
         Ok(s) => s,
         Err(e) => {
             set_last_error(&format!("Invalid UTF-8: {e}"));
-            return 2;
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
@@ -287,11 +357,11 @@ pub unsafe extern "C" fn rpdf_compute_layout(
     match CString::new(json) {
         Ok(cs) => {
             *out_json_ptr = cs.into_raw();
-            0
+            RpdfError::Ok as c_int
         }
         Err(_) => {
             set_last_error("JSON contained null byte");
-            3
+            RpdfError::Pipeline as c_int
         }
     }
 }
@@ -326,7 +396,7 @@ pub unsafe extern "C" fn rpdf_generate_pdf_ex(
 ) -> c_int {
     if html_ptr.is_null() || out_buf.is_null() || out_len.is_null() {
         set_last_error("Null pointer argument");
-        return 1;
+        return RpdfError::NullPointer as c_int;
     }
 
     let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
@@ -334,7 +404,7 @@ pub unsafe extern "C" fn rpdf_generate_pdf_ex(
         Ok(s) => s,
         Err(e) => {
             set_last_error(&format!("Invalid UTF-8: {e}"));
-            return 2;
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
@@ -344,18 +414,70 @@ pub unsafe extern "C" fn rpdf_generate_pdf_ex(
         pipeline_config_from_c(&*cfg)
     };
 
-    match generate_pdf(html, &config) {
+    match generate(html, &config) {
         Ok((pdf_bytes, _)) => {
             let len = pdf_bytes.len() as u32;
             let buf = pdf_bytes.into_boxed_slice();
             *out_buf = Box::into_raw(buf) as *mut u8;
             *out_len = len;
-            0
+            RpdfError::Ok as c_int
         }
+        Err(e) => fail(&e),
+    }
+}
+
+/// Like [`rpdf_generate_pdf_ex`], but also reports how many pages the PDF has.
+///
+/// # Parameters
+/// - `html_ptr`, `html_len`: UTF-8 HTML input
+/// - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+/// - `out_buf`, `out_len`: PDF output
+/// - `out_page_count`: receives the number of pages
+///
+/// # Returns
+/// `0` on success.
+///
+/// # Safety
+/// Same as `rpdf_generate_pdf_ex`; `out_page_count` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_generate_pdf_ex2(
+    html_ptr: *const u8,
+    html_len: u32,
+    cfg: *const RpdfPipelineConfig,
+    out_buf: *mut *mut u8,
+    out_len: *mut u32,
+    out_page_count: *mut u32,
+) -> c_int {
+    if html_ptr.is_null() || out_buf.is_null() || out_len.is_null() || out_page_count.is_null() {
+        set_last_error("Null pointer argument");
+        return RpdfError::NullPointer as c_int;
+    }
+
+    let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
+    let html = match std::str::from_utf8(html_bytes) {
+        Ok(s) => s,
         Err(e) => {
-            set_last_error(&e);
-            3
+            set_last_error(&format!("Invalid UTF-8: {e}"));
+            return RpdfError::InvalidUtf8 as c_int;
         }
+    };
+
+    let config = if cfg.is_null() {
+        PipelineConfig::default()
+    } else {
+        pipeline_config_from_c(&*cfg)
+    };
+
+    match generate(html, &config) {
+        Ok((pdf_bytes, layout_config)) => {
+            let len = pdf_bytes.len() as u32;
+            let buf = pdf_bytes.into_boxed_slice();
+            *out_buf = Box::into_raw(buf) as *mut u8;
+            *out_len = len;
+            *out_page_count = layout_config.pages.len() as u32;
+            RpdfError::Ok as c_int
+        }
+        Err(e) => fail(&e),
     }
 }
 
@@ -387,7 +509,7 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout_ex(
         || out_json_ptr.is_null()
     {
         set_last_error("Null pointer argument");
-        return 1;
+        return RpdfError::NullPointer as c_int;
     }
 
     let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
@@ -395,7 +517,7 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout_ex(
         Ok(s) => s,
         Err(e) => {
             set_last_error(&format!("Invalid UTF-8: {e}"));
-            return 2;
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
@@ -405,7 +527,7 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout_ex(
         pipeline_config_from_c(&*cfg)
     };
 
-    match generate_pdf(html, &config) {
+    match generate(html, &config) {
         Ok((pdf_bytes, layout_config)) => {
             let len = pdf_bytes.len() as u32;
             let buf = pdf_bytes.into_boxed_slice();
@@ -417,12 +539,9 @@ pub unsafe extern "C" fn rpdf_generate_pdf_with_layout_ex(
                 Ok(cs) => *out_json_ptr = cs.into_raw(),
                 Err(_) => *out_json_ptr = ptr::null_mut(),
             }
-            0
-        }
-        Err(e) => {
-            set_last_error(&e);
-            3
+            RpdfError::Ok as c_int
         }
+        Err(e) => fail(&e),
     }
 }
 
@@ -447,7 +566,7 @@ pub unsafe extern "C" fn rpdf_compute_layout_ex(
 ) -> c_int {
     if html_ptr.is_null() || out_json_ptr.is_null() {
         set_last_error("Null pointer argument");
-        return 1;
+        return RpdfError::NullPointer as c_int;
     }
 
     let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
@@ -455,7 +574,7 @@ pub unsafe extern "C" fn rpdf_compute_layout_ex(
         Ok(s) => s,
         Err(e) => {
             set_last_error(&format!("Invalid UTF-8: {e}"));
-            return 2;
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
@@ -471,11 +590,76 @@ pub unsafe extern "C" fn rpdf_compute_layout_ex(
     match CString::new(json) {
         Ok(cs) => {
             *out_json_ptr = cs.into_raw();
-            0
+            RpdfError::Ok as c_int
         }
         Err(_) => {
             set_last_error("JSON contained null byte");
-            3
+            RpdfError::Pipeline as c_int
+        }
+    }
+}
+
+/// Lay out HTML and return its text boxes as a flat JSON array.
+///
+/// Each element is `{"page", "x", "y", "width", "height", "text"}`: the
+/// zero-based page index, the box in points from the page's top-left corner,
+/// and its wrapped lines joined by `\n`. Useful for comparing against OCR
+/// output or extracting coordinates without walking the layout JSON.
+///
+/// # Parameters
+/// - `html_ptr`, `html_len`: UTF-8 HTML input
+/// - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+/// - `out_json_ptr`: text box JSON output (free with `rpdf_free_string`)
+///
+/// # Returns
+/// `0` on success.
+///
+/// # Safety
+/// Same as `rpdf_generate_pdf_ex`.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_get_text_boxes(
+    html_ptr: *const u8,
+    html_len: u32,
+    cfg: *const RpdfPipelineConfig,
+    out_json_ptr: *mut *mut c_char,
+) -> c_int {
+    if html_ptr.is_null() || out_json_ptr.is_null() {
+        set_last_error("Null pointer argument");
+        return RpdfError::NullPointer as c_int;
+    }
+
+    let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
+    let html = match std::str::from_utf8(html_bytes) {
+        Ok(s) => s,
+        Err(e) => {
+            set_last_error(&format!("Invalid UTF-8: {e}"));
+            return RpdfError::InvalidUtf8 as c_int;
+        }
+    };
+
+    let config = if cfg.is_null() {
+        PipelineConfig::default()
+    } else {
+        pipeline_config_from_c(&*cfg)
+    };
+
+    let layout = crate::pipeline::compute_layout_config(html, &config);
+    let json = match serde_json::to_string(&layout.text_boxes()) {
+        Ok(json) => json,
+        Err(e) => {
+            set_last_error(&e.to_string());
+            return RpdfError::Pipeline as c_int;
+        }
+    };
+
+    match CString::new(json) {
+        Ok(cs) => {
+            *out_json_ptr = cs.into_raw();
+            RpdfError::Ok as c_int
+        }
+        Err(_) => {
+            set_last_error("JSON contained null byte");
+            RpdfError::Pipeline as c_int
         }
     }
 }
@@ -483,6 +667,11 @@ pub unsafe extern "C" fn rpdf_compute_layout_ex(
 /// Render a PDF from a layout config JSON string.
 ///
 /// This allows pre-computing the layout and rendering separately.
+///
+/// # Safety
+/// - `json_ptr` must point to a valid null-terminated string.
+/// - `out_buf` and `out_len` must be valid pointers.
+/// - The caller must free `*out_buf` with `rpdf_free_buffer`.
 #[no_mangle]
 pub unsafe extern "C" fn rpdf_render_from_layout(
     json_ptr: *const c_char,
@@ -491,7 +680,7 @@ pub unsafe extern "C" fn rpdf_render_from_layout(
 ) -> c_int {
     if json_ptr.is_null() || out_buf.is_null() || out_len.is_null() {
         set_last_error("Null pointer argument");
-        return 1;
+        return RpdfError::NullPointer as c_int;
     }
 
     let json_cstr = CStr::from_ptr(json_ptr);
@@ -499,32 +688,281 @@ pub unsafe extern "C" fn rpdf_render_from_layout(
         Ok(s) => s,
         Err(e) => {
             set_last_error(&format!("Invalid UTF-8 in JSON: {e}"));
-            return 2;
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
-    let layout_config = match crate::layout_config::LayoutConfig::from_json(json) {
-        Ok(c) => c,
+    render_layout_json(json, &FontManager::default(), out_buf, out_len)
+}
+
+/// Like [`rpdf_render_from_layout`], but text whose `font_family` (and
+/// weight/style) matches a font registered with `rpdf_register_font` is drawn
+/// with, and embeds, that font.
+///
+/// Safe to call from several threads at once; registering a font waits for
+/// in-flight renders to finish.
+///
+/// # Safety
+/// Same as `rpdf_render_from_layout`.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_render_from_layout_with_fonts(
+    json_ptr: *const c_char,
+    out_buf: *mut *mut u8,
+    out_len: *mut u32,
+) -> c_int {
+    if json_ptr.is_null() || out_buf.is_null() || out_len.is_null() {
+        set_last_error("Null pointer argument");
+        return RpdfError::NullPointer as c_int;
+    }
+
+    let json_cstr = CStr::from_ptr(json_ptr);
+    let json = match json_cstr.to_str() {
+        Ok(s) => s,
         Err(e) => {
-            set_last_error(&format!("Invalid layout JSON: {e}"));
-            return 3;
+            set_last_error(&format!("Invalid UTF-8 in JSON: {e}"));
+            return RpdfError::InvalidUtf8 as c_int;
         }
     };
 
-    match crate::render::render_pdf(&layout_config) {
-        Ok(pdf_bytes) => {
+    let fonts = FONT_REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+    render_layout_json(json, &fonts, out_buf, out_len)
+}
+
+/// Parse layout JSON and render it with `fonts`.
+///
+/// # Safety
+/// `out_buf` and `out_len` must be valid pointers.
+unsafe fn render_layout_json(
+    json: &str,
+    fonts: &FontManager,
+    out_buf: *mut *mut u8,
+    out_len: *mut u32,
+) -> c_int {
+    let layout_config = match crate::layout_config::LayoutConfig::from_json(json) {
+        Ok(c) => c,
+        Err(e) => return fail(&e),
+    };
+
+    let result = crate::render::render_pdf_with_warnings(&layout_config, fonts);
+    record_skipped_images(&result);
+    match result {
+        Ok((pdf_bytes, _)) => {
             let len = pdf_bytes.len() as u32;
             let buf = pdf_bytes.into_boxed_slice();
             let raw = Box::into_raw(buf) as *mut u8;
             *out_buf = raw;
             *out_len = len;
-            0
+            RpdfError::Ok as c_int
+        }
+        Err(e) => fail(&e),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Font registry
+// ---------------------------------------------------------------------------
+
+/// Register a TTF/OTF font for `rpdf_render_from_layout_with_fonts`.
+///
+/// Layout text whose `font_family`, bold and italic flags match is drawn with
+/// this font. Registering the same family/weight/style again replaces it.
+///
+/// # Parameters
+/// - `family`: null-terminated UTF-8 family name, e.g. `"Inter"`
+/// - `bold`, `italic`: the face's weight and style
+/// - `font_ptr`, `font_len`: the font file bytes (copied)
+///
+/// # Returns
+/// `0` on success, [`RpdfError::InvalidFont`] if the bytes cannot be parsed.
+///
+/// # Safety
+/// - `family` must point to a valid null-terminated string.
+/// - `font_ptr` must point to `font_len` valid bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_register_font(
+    family: *const c_char,
+    bold: bool,
+    italic: bool,
+    font_ptr: *const u8,
+    font_len: u32,
+) -> c_int {
+    if family.is_null() || font_ptr.is_null() {
+        set_last_error("Null pointer argument");
+        return RpdfError::NullPointer as c_int;
+    }
+
+    let family = match CStr::from_ptr(family).to_str() {
+        Ok(s) => s,
+        Err(e) => {
+            set_last_error(&format!("Invalid UTF-8 in font family: {e}"));
+            return RpdfError::InvalidUtf8 as c_int;
+        }
+    };
+    let bytes = slice::from_raw_parts(font_ptr, font_len as usize).to_vec();
+
+    let mut fonts = FONT_REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    match fonts.load_font(family, bold, italic, bytes) {
+        Ok(()) => RpdfError::Ok as c_int,
+        Err(e) => {
+            set_last_error(&e.to_string());
+            RpdfError::InvalidFont as c_int
+        }
+    }
+}
+
+/// Remove every font registered with `rpdf_register_font`.
+#[no_mangle]
+pub extern "C" fn rpdf_clear_fonts() {
+    *FONT_REGISTRY.write().unwrap_or_else(|e| e.into_inner()) = FontManager::new();
+}
+
+/// Render several HTML documents into a single PDF, each starting on a fresh
+/// page.
+///
+/// # Parameters
+/// - `htmls_ptr`: array of `count` pointers to UTF-8 HTML inputs
+/// - `lens_ptr`: array of `count` input lengths in bytes
+/// - `count`: number of inputs (at least 1)
+/// - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+/// - `out_buf`, `out_len`: PDF output
+///
+/// # Returns
+/// `0` on success.
+///
+/// # Safety
+/// - `htmls_ptr` and `lens_ptr` must each point to `count` valid elements,
+///   and every `htmls_ptr[i]` must point to `lens_ptr[i]` valid bytes.
+/// - `cfg`, if non-null, must satisfy the requirements of `rpdf_generate_pdf_ex`.
+/// - The caller must free `*out_buf` with `rpdf_free_buffer`.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_generate_pdf_multi(
+    htmls_ptr: *const *const u8,
+    lens_ptr: *const u32,
+    count: u32,
+    cfg: *const RpdfPipelineConfig,
+    out_buf: *mut *mut u8,
+    out_len: *mut u32,
+) -> c_int {
+    if htmls_ptr.is_null() || lens_ptr.is_null() || out_buf.is_null() || out_len.is_null() {
+        set_last_error("Null pointer argument");
+        return RpdfError::NullPointer as c_int;
+    }
+
+    let ptrs = slice::from_raw_parts(htmls_ptr, count as usize);
+    let lens = slice::from_raw_parts(lens_ptr, count as usize);
+    let mut htmls = Vec::with_capacity(ptrs.len());
+    for (i, (&html_ptr, &html_len)) in ptrs.iter().zip(lens).enumerate() {
+        if html_ptr.is_null() {
+            set_last_error("Null pointer argument");
+            return RpdfError::NullPointer as c_int;
+        }
+        let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
+        match std::str::from_utf8(html_bytes) {
+            Ok(s) => htmls.push(s),
+            Err(e) => {
+                set_last_error(&format!("Invalid UTF-8 in input {i}: {e}"));
+                return RpdfError::InvalidUtf8 as c_int;
+            }
+        }
+    }
+
+    let config = if cfg.is_null() {
+        PipelineConfig::default()
+    } else {
+        pipeline_config_from_c(&*cfg)
+    };
+
+    let result =
+        generate_pdf_multi_verbose(&htmls, &config).map(|(bytes, _, warnings)| (bytes, warnings));
+    record_skipped_images(&result);
+    match result {
+        Ok((pdf_bytes, _)) => {
+            let len = pdf_bytes.len() as u32;
+            let buf = pdf_bytes.into_boxed_slice();
+            *out_buf = Box::into_raw(buf) as *mut u8;
+            *out_len = len;
+            RpdfError::Ok as c_int
         }
+        Err(e) => fail(&e),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming output
+// ---------------------------------------------------------------------------
+
+/// Size of the chunks handed to an [`RpdfWriteCallback`].
+const STREAM_CHUNK_LEN: usize = 64 * 1024;
+
+/// Receives one chunk of PDF output. `chunk_ptr` is only valid for the
+/// duration of the call. Return `0` to continue, or non-zero to abort.
+pub type RpdfWriteCallback = Option<
+    unsafe extern "C" fn(chunk_ptr: *const u8, chunk_len: u32, user_data: *mut c_void) -> c_int,
+>;
+
+/// Generate a PDF and pass it to `write_cb` in chunks instead of returning
+/// a buffer, so callers can write straight to a file or socket without
+/// holding a second copy.
+///
+/// # Parameters
+/// - `html_ptr`, `html_len`: UTF-8 HTML input
+/// - `cfg`: optional pointer to an [`RpdfPipelineConfig`]; pass `NULL` for defaults
+/// - `write_cb`: called with each chunk of PDF bytes, in order
+/// - `user_data`: passed through to every `write_cb` call
+///
+/// # Returns
+/// `0` on success. [`RpdfError::CallbackAborted`] if `write_cb` returned
+/// non-zero; no further chunks are delivered after that.
+///
+/// # Safety
+/// - `html_ptr` must point to `html_len` valid bytes.
+/// - `cfg`, if non-null, must satisfy the requirements of `rpdf_generate_pdf_ex`.
+/// - `write_cb` must be safe to call with `user_data`.
+#[no_mangle]
+pub unsafe extern "C" fn rpdf_generate_pdf_streamed(
+    html_ptr: *const u8,
+    html_len: u32,
+    cfg: *const RpdfPipelineConfig,
+    write_cb: RpdfWriteCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    let Some(write_cb) = write_cb else {
+        set_last_error("Null pointer argument");
+        return RpdfError::NullPointer as c_int;
+    };
+    if html_ptr.is_null() {
+        set_last_error("Null pointer argument");
+        return RpdfError::NullPointer as c_int;
+    }
+
+    let html_bytes = slice::from_raw_parts(html_ptr, html_len as usize);
+    let html = match std::str::from_utf8(html_bytes) {
+        Ok(s) => s,
         Err(e) => {
-            set_last_error(&e);
-            4
+            set_last_error(&format!("Invalid UTF-8: {e}"));
+            return RpdfError::InvalidUtf8 as c_int;
+        }
+    };
+
+    let config = if cfg.is_null() {
+        PipelineConfig::default()
+    } else {
+        pipeline_config_from_c(&*cfg)
+    };
+
+    let pdf_bytes = match generate(html, &config) {
+        Ok((pdf_bytes, _)) => pdf_bytes,
+        Err(e) => return fail(&e),
+    };
+
+    for chunk in pdf_bytes.chunks(STREAM_CHUNK_LEN) {
+        let rc = write_cb(chunk.as_ptr(), chunk.len() as u32, user_data);
+        if rc != 0 {
+            set_last_error(&format!("Write callback aborted with code {rc}"));
+            return RpdfError::CallbackAborted as c_int;
         }
     }
+    RpdfError::Ok as c_int
 }
 
 // ---------------------------------------------------------------------------
@@ -539,7 +977,7 @@ pub unsafe extern "C" fn rpdf_render_from_layout(
 #[no_mangle]
 pub unsafe extern "C" fn rpdf_free_buffer(buf: *mut u8, len: u32) {
     if !buf.is_null() {
-        let _ = Box::from_raw(slice::from_raw_parts_mut(buf, len as usize));
+        let _ = Box::from_raw(ptr::slice_from_raw_parts_mut(buf, len as usize));
     }
 }
 
@@ -572,12 +1010,25 @@ pub extern "C" fn rpdf_last_error() -> *const c_char {
     })
 }
 
+/// Number of images left out of the last PDF generated on this thread
+/// because they could not be decoded or had no size.
+///
+/// Set by every function that produces a PDF: `rpdf_generate_pdf`, its
+/// `_ex`, `_ex2`, `_multi` and `_streamed` variants, the `*_with_layout`
+/// variants and the `rpdf_render_from_layout*` functions. A non-zero count
+/// alongside a `0` return code means the PDF was produced without those
+/// images; callers decide whether that is acceptable.
+#[no_mangle]
+pub extern "C" fn rpdf_last_skipped_images() -> u32 {
+    LAST_SKIPPED_IMAGES.with(Cell::get)
+}
+
 /// Return the library version as a null-terminated string.
 /// The caller must **not** free this pointer.
 #[no_mangle]
 pub extern "C" fn rpdf_version() -> *const c_char {
     // Safe: the string is static
-    b"0.1.0\0".as_ptr() as *const c_char
+    c"0.1.0".as_ptr()
 }
 
 // ---------------------------------------------------------------------------
@@ -637,6 +1088,64 @@ mod tests {
         assert_ne!(rc, 0, "Should fail on null input");
     }
 
+    #[test]
+    fn ffi_reports_skipped_images() {
+        let html = br#"<p>Logo:</p><img src="data:image/png;base64,bm90IGFuIGltYWdl" alt="logo">"#;
+        let mut out_buf: *mut u8 = ptr::null_mut();
+        let mut out_len: u32 = 0;
+
+        let rc = unsafe {
+            rpdf_generate_pdf(html.as_ptr(), html.len() as u32, &mut out_buf, &mut out_len)
+        };
+
+        assert_eq!(rc, RpdfError::Ok as c_int, "a bad image is not an error");
+        assert_eq!(rpdf_last_skipped_images(), 1);
+        unsafe { rpdf_free_buffer(out_buf, out_len) };
+
+        let html = b"<p>No images</p>";
+        let rc = unsafe {
+            rpdf_generate_pdf(html.as_ptr(), html.len() as u32, &mut out_buf, &mut out_len)
+        };
+        assert_eq!(rc, RpdfError::Ok as c_int);
+        assert_eq!(rpdf_last_skipped_images(), 0);
+        unsafe { rpdf_free_buffer(out_buf, out_len) };
+
+        // Combining documents reports its own count, not the previous call's.
+        let bad = br#"<img src="data:image/png;base64,bm90IGFuIGltYWdl">"#;
+        let rc = unsafe {
+            rpdf_generate_pdf(bad.as_ptr(), bad.len() as u32, &mut out_buf, &mut out_len)
+        };
+        assert_eq!(rc, RpdfError::Ok as c_int);
+        unsafe { rpdf_free_buffer(out_buf, out_len) };
+        let (htmls, lens) = ([html.as_ptr()], [html.len() as u32]);
+        let rc = unsafe {
+            rpdf_generate_pdf_multi(
+                htmls.as_ptr(),
+                lens.as_ptr(),
+                1,
+                ptr::null(),
+                &mut out_buf,
+                &mut out_len,
+            )
+        };
+        assert_eq!(rc, RpdfError::Ok as c_int);
+        assert_eq!(rpdf_last_skipped_images(), 0);
+        unsafe { rpdf_free_buffer(out_buf, out_len) };
+    }
+
+    #[test]
+    fn ffi_error_codes_separate_render_failures() {
+        let code = |e: ForgeError| fail(&e);
+        assert_eq!(
+            code(ForgeError::Render("bad".into())),
+            RpdfError::Render as c_int
+        );
+        assert_eq!(
+            code(ForgeError::InvalidInput("bad".into())),
+            RpdfError::Pipeline as c_int
+        );
+    }
+
     #[test]
     fn ffi_version() {
         let v = rpdf_version();
@@ -702,9 +1211,184 @@ mod tests {
     }
 
     #[test]
-    fn ffi_compute_layout_ex_landscape() {
-        use std::ffi::CString;
+    fn ffi_generate_pdf_ex2_reports_page_count() {
+        let html: String = (0..80).map(|i| format!("<p>Paragraph {i}</p>")).collect();
+        let mut out_buf: *mut u8 = ptr::null_mut();
+        let mut out_len: u32 = 0;
+        let mut page_count: u32 = 0;
+
+        let rc = unsafe {
+            rpdf_generate_pdf_ex2(
+                html.as_ptr(),
+                html.len() as u32,
+                ptr::null(),
+                &mut out_buf,
+                &mut out_len,
+                &mut page_count,
+            )
+        };
+
+        assert_eq!(rc, RpdfError::Ok as c_int);
+        let bytes = unsafe { slice::from_raw_parts(out_buf, out_len as usize) };
+        let doc = lopdf::Document::load_mem(bytes).unwrap();
+        assert!(page_count > 1);
+        assert_eq!(page_count as usize, doc.get_pages().len());
+        unsafe { rpdf_free_buffer(out_buf, out_len) };
+
+        let rc = unsafe {
+            rpdf_generate_pdf_ex2(
+                ptr::null(),
+                0,
+                ptr::null(),
+                &mut out_buf,
+                &mut out_len,
+                &mut page_count,
+            )
+        };
+        assert_eq!(rc, RpdfError::NullPointer as c_int);
+    }
+
+    #[test]
+    fn ffi_generate_pdf_multi_starts_each_input_on_a_new_page() {
+        let first = b"<p>First record</p>";
+        let second = b"<p>Second record</p>";
+        let htmls = [first.as_ptr(), second.as_ptr()];
+        let lens = [first.len() as u32, second.len() as u32];
+        let mut out_buf: *mut u8 = ptr::null_mut();
+        let mut out_len: u32 = 0;
+
+        let rc = unsafe {
+            rpdf_generate_pdf_multi(
+                htmls.as_ptr(),
+                lens.as_ptr(),
+                2,
+                ptr::null(),
+                &mut out_buf,
+                &mut out_len,
+            )
+        };
+
+        assert_eq!(rc, 0);
+        let bytes = unsafe { slice::from_raw_parts(out_buf, out_len as usize) };
+        let doc = lopdf::Document::load_mem(bytes).unwrap();
+        assert_eq!(doc.get_pages().len(), 2);
+        unsafe { rpdf_free_buffer(out_buf, out_len) };
+    }
+
+    #[test]
+    fn ffi_render_from_layout_embeds_registered_font() {
+        let layout = crate::pipeline::compute_layout_config(
+            "<p>Registered typeface</p>",
+            &PipelineConfig::default(),
+        );
+        let json = layout.to_json().replace(
+            "\"font_family\": \"Helvetica\"",
+            "\"font_family\": \"Tuffy\"",
+        );
+        assert!(json.contains("Tuffy"));
+        let json = CString::new(json).unwrap();
+
+        let tuffy = std::fs::read(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/Tuffy.ttf"),
+        )
+        .unwrap();
+        let family = CString::new("Tuffy").unwrap();
+        let rc = unsafe {
+            rpdf_register_font(
+                family.as_ptr(),
+                false,
+                false,
+                tuffy.as_ptr(),
+                tuffy.len() as u32,
+            )
+        };
+        assert_eq!(rc, RpdfError::Ok as c_int);
+        let rc = unsafe { rpdf_register_font(family.as_ptr(), false, false, b"nope".as_ptr(), 4) };
+        assert_eq!(rc, RpdfError::InvalidFont as c_int);
+
+        let mut out_buf: *mut u8 = ptr::null_mut();
+        let mut out_len: u32 = 0;
+        let rc = unsafe {
+            rpdf_render_from_layout_with_fonts(json.as_ptr(), &mut out_buf, &mut out_len)
+        };
+        assert_eq!(rc, RpdfError::Ok as c_int);
+
+        let bytes = unsafe { slice::from_raw_parts(out_buf, out_len as usize) };
+        let doc = lopdf::Document::load_mem(bytes).unwrap();
+        let embedded = doc
+            .objects
+            .values()
+            .any(|obj| obj.as_dict().is_ok_and(|d| d.has(b"FontFile2")));
+        assert!(embedded, "the registered font is embedded");
+        unsafe { rpdf_free_buffer(out_buf, out_len) };
+        rpdf_clear_fonts();
+    }
+
+    unsafe extern "C" fn collect_chunk(ptr: *const u8, len: u32, user_data: *mut c_void) -> c_int {
+        let out = &mut *(user_data as *mut Vec<u8>);
+        out.extend_from_slice(slice::from_raw_parts(ptr, len as usize));
+        0
+    }
+
+    unsafe extern "C" fn reject_chunk(_: *const u8, _: u32, _: *mut c_void) -> c_int {
+        7
+    }
+
+    #[test]
+    fn ffi_generate_pdf_streamed_delivers_whole_pdf() {
+        let html = "<h1>Streamed</h1><p>Written through a callback</p>";
+        let mut out: Vec<u8> = Vec::new();
+
+        let rc = unsafe {
+            rpdf_generate_pdf_streamed(
+                html.as_ptr(),
+                html.len() as u32,
+                ptr::null(),
+                Some(collect_chunk),
+                &mut out as *mut Vec<u8> as *mut c_void,
+            )
+        };
+
+        assert_eq!(rc, 0);
+        assert_eq!(&out[0..5], b"%PDF-");
+        assert!(out.ends_with(b"%%EOF") || out.ends_with(b"%%EOF\n"));
+
+        let rc = unsafe {
+            rpdf_generate_pdf_streamed(
+                html.as_ptr(),
+                html.len() as u32,
+                ptr::null(),
+                Some(reject_chunk),
+                ptr::null_mut(),
+            )
+        };
+        assert_eq!(rc, 5, "a non-zero callback result aborts");
+    }
+
+    #[test]
+    fn ffi_get_text_boxes_finds_a_paragraph() {
+        let html = b"<h1>Title</h1><p>Find me here</p>";
+        let mut json_ptr: *mut c_char = ptr::null_mut();
+
+        let rc = unsafe {
+            rpdf_get_text_boxes(html.as_ptr(), html.len() as u32, ptr::null(), &mut json_ptr)
+        };
+
+        assert_eq!(rc, 0);
+        let json = unsafe { CStr::from_ptr(json_ptr) }.to_str().unwrap();
+        let boxes: Vec<crate::layout_config::TextBox> = serde_json::from_str(json).unwrap();
+        let para = boxes
+            .iter()
+            .find(|b| b.text == "Find me here")
+            .expect("paragraph box");
+        assert_eq!(para.page, 0);
+        assert!(para.width > 0.0 && para.height > 0.0);
+        assert!(para.y > boxes[0].y, "the paragraph follows the heading");
+        unsafe { rpdf_free_string(json_ptr) };
+    }
 
+    #[test]
+    fn ffi_compute_layout_ex_landscape() {
         let html = b"<p>Landscape layout</p>";
         let cfg = RpdfPipelineConfig {
             title: ptr::null(),
@@ -5,6 +5,8 @@
 
 use std::collections::HashMap;
 
+use crate::error::ForgeError;
+
 /// A loaded font face with metrics.
 #[derive(Clone)]
 pub struct FontData {
@@ -14,13 +16,42 @@ pub struct FontData {
     pub ascender: f32,
     pub descender: f32,
     pub line_gap: f32,
+    /// The font's `f` ligatures, longest first; empty for synthetic metrics.
+    pub ligatures: Vec<Ligature>,
+}
+
+/// An `f` ligature (`ff`, `fi`, `fl`, `ffi`, `ffl`) from a font's GSUB
+/// `liga` feature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ligature {
+    /// The characters the ligature glyph replaces, e.g. `"fi"`.
+    pub text: &'static str,
+    /// Unicode presentation form of `text` (e.g. U+FB01), so text copied
+    /// from the PDF still reads as the original letters.
+    pub ch: char,
+    /// Glyph id of the ligature.
+    pub glyph: u16,
+    /// Horizontal advance in font units.
+    pub advance: u16,
+}
+
+/// A piece of text after ligature substitution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shaped<'t, 'l> {
+    /// Characters drawn one glyph each.
+    Text(&'t str),
+    /// Characters replaced by a single ligature glyph.
+    Ligature(&'l Ligature),
 }
 
 /// Manages loaded fonts.
+#[derive(Clone)]
 pub struct FontManager {
     fonts: HashMap<FontKey, FontData>,
     /// Fallback metrics if no font is loaded.
     default_key: FontKey,
+    /// Whether text is measured (and drawn) with the fonts' ligatures.
+    ligatures: bool,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -39,19 +70,21 @@ impl FontManager {
                 bold: false,
                 italic: false,
             },
+            ligatures: false,
         }
     }
 
     /// Load a TTF/OTF font from bytes.
-    pub fn load_font(&mut self, family: &str, bold: bool, italic: bool, bytes: Vec<u8>) -> Result<(), String> {
+    pub fn load_font(&mut self, family: &str, bold: bool, italic: bool, bytes: Vec<u8>) -> Result<(), ForgeError> {
         let face = ttf_parser::Face::parse(&bytes, 0)
-            .map_err(|e| format!("Failed to parse font: {e}"))?;
+            .map_err(|e| ForgeError::Font(format!("failed to parse font: {e}")))?;
 
         let data = FontData {
             units_per_em: face.units_per_em() as f32,
             ascender: face.ascender() as f32,
             descender: face.descender() as f32,
             line_gap: face.line_gap() as f32,
+            ligatures: f_ligatures(&face),
             bytes,
         };
 
@@ -85,6 +118,7 @@ impl FontManager {
                     ascender: 750.0,
                     descender: -250.0,
                     line_gap: 0.0,
+                    ligatures: Vec::new(),
                 },
             );
             self.default_key = key;
@@ -103,11 +137,42 @@ impl FontManager {
                     ascender: 750.0,
                     descender: -250.0,
                     line_gap: 0.0,
+                    ligatures: Vec::new(),
                 },
             );
         }
     }
 
+    /// Measure and draw text with the fonts' `f` ligatures (off by default,
+    /// so output does not depend on which fonts happen to have them).
+    pub fn set_ligatures(&mut self, on: bool) {
+        self.ligatures = on;
+    }
+
+    /// Whether ligatures are applied; see [`FontManager::set_ligatures`].
+    pub fn ligatures(&self) -> bool {
+        self.ligatures
+    }
+
+    /// The ligatures applied to text in the given font: none when they are
+    /// switched off.
+    pub fn ligatures_for(&self, bold: bool, italic: bool, family: &str) -> &[Ligature] {
+        if !self.ligatures {
+            return &[];
+        }
+        let key = FontKey {
+            family: family.to_string(),
+            bold,
+            italic,
+        };
+        &self.get(&key).ligatures
+    }
+
+    /// Key used when a requested font is not loaded.
+    pub fn default_key(&self) -> &FontKey {
+        &self.default_key
+    }
+
     /// Get font data for a key, falling back to the default.
     pub fn get(&self, key: &FontKey) -> &FontData {
         self.fonts.get(key).unwrap_or_else(|| {
@@ -137,13 +202,25 @@ impl FontManager {
         if let Ok(face) = ttf_parser::Face::parse(&data.bytes, 0) {
             let scale = font_size / data.units_per_em;
             let mut width = 0.0f32;
-            for ch in text.chars() {
-                if let Some(gid) = face.glyph_index(ch) {
-                    let advance = face.glyph_hor_advance(gid).unwrap_or(0);
-                    width += advance as f32 * scale;
-                } else {
-                    // Fallback for missing glyph
-                    width += font_size * 0.5;
+            let ligatures = if self.ligatures { data.ligatures.as_slice() } else { &[] };
+            for piece in apply_ligatures(text, ligatures) {
+                let text = match piece {
+                    Shaped::Ligature(lig) => {
+                        width += lig.advance as f32 * scale;
+                        continue;
+                    }
+                    Shaped::Text(text) => text,
+                };
+                for ch in text.chars() {
+                    // A non-breaking space is rendered as a space.
+                    let ch = if ch == NBSP { ' ' } else { ch };
+                    if let Some(gid) = face.glyph_index(ch) {
+                        let advance = face.glyph_hor_advance(gid).unwrap_or(0);
+                        width += advance as f32 * scale;
+                    } else {
+                        // Fallback for missing glyph
+                        width += font_size * 0.5;
+                    }
                 }
             }
             width
@@ -157,6 +234,14 @@ impl FontManager {
         font_size * line_height_factor
     }
 
+    /// Height in px of `text` word-wrapped to `max_width`: the number of
+    /// wrapped lines times the line height at [`DEFAULT_LINE_HEIGHT`]. Lets
+    /// callers size a container before laying the text out.
+    pub fn measure_block_height(&self, text: &str, font_size: f32, bold: bool, italic: bool, family: &str, max_width: f32) -> f32 {
+        let lines = wrap_text(text, font_size, bold, italic, family, max_width, self);
+        lines.len() as f32 * self.line_height_px(font_size, DEFAULT_LINE_HEIGHT)
+    }
+
     /// Get the ascender in px for the given font.
     pub fn ascender_px(&self, font_size: f32, bold: bool, italic: bool, family: &str) -> f32 {
         let key = FontKey {
@@ -169,6 +254,18 @@ impl FontManager {
         data.ascender * scale
     }
 
+    /// Get the descender depth in px (positive) for the given font.
+    pub fn descender_px(&self, font_size: f32, bold: bool, italic: bool, family: &str) -> f32 {
+        let key = FontKey {
+            family: family.to_string(),
+            bold,
+            italic,
+        };
+        let data = self.get(&key);
+        let scale = font_size / data.units_per_em;
+        data.descender.abs() * scale
+    }
+
     /// Check if real font bytes are loaded for the default font.
     pub fn has_real_fonts(&self) -> bool {
         self.fonts
@@ -202,7 +299,98 @@ impl Default for FontManager {
     }
 }
 
+/// The `f` ligatures a font's GSUB `liga` feature defines, longest first.
+fn f_ligatures(face: &ttf_parser::Face) -> Vec<Ligature> {
+    use ttf_parser::gsub::SubstitutionSubtable;
+
+    const CANDIDATES: [(&str, char); 5] = [
+        ("ffi", '\u{FB03}'),
+        ("ffl", '\u{FB04}'),
+        ("ff", '\u{FB00}'),
+        ("fi", '\u{FB01}'),
+        ("fl", '\u{FB02}'),
+    ];
+    let Some(gsub) = face.tables().gsub else {
+        return Vec::new();
+    };
+    let lookups: Vec<u16> = gsub
+        .features
+        .into_iter()
+        .filter(|feature| feature.tag == ttf_parser::Tag::from_bytes(b"liga"))
+        .flat_map(|feature| feature.lookup_indices)
+        .collect();
+
+    let mut found = Vec::new();
+    for (text, ch) in CANDIDATES {
+        let Some(glyphs) = text.chars().map(|c| face.glyph_index(c)).collect::<Option<Vec<_>>>() else {
+            continue;
+        };
+        let glyph = lookups
+            .iter()
+            .filter_map(|&index| gsub.lookups.get(index))
+            .flat_map(|lookup| lookup.subtables.into_iter::<SubstitutionSubtable>())
+            .find_map(|subtable| {
+                let SubstitutionSubtable::Ligature(subst) = subtable else {
+                    return None;
+                };
+                let set = subst.ligature_sets.get(subst.coverage.get(glyphs[0])?)?;
+                set.into_iter()
+                    .find(|lig| lig.components.into_iter().eq(glyphs[1..].iter().copied()))
+                    .map(|lig| lig.glyph)
+            });
+        if let Some(glyph) = glyph {
+            found.push(Ligature {
+                text,
+                ch,
+                glyph: glyph.0,
+                advance: face.glyph_hor_advance(glyph).unwrap_or(0),
+            });
+        }
+    }
+    found
+}
+
+/// Split `text` into runs of plain characters and the `ligatures` that
+/// replace them, trying longer ligatures first.
+pub fn apply_ligatures<'t, 'l>(text: &'t str, ligatures: &'l [Ligature]) -> Vec<Shaped<'t, 'l>> {
+    if ligatures.is_empty() {
+        return vec![Shaped::Text(text)];
+    }
+    let mut pieces = Vec::new();
+    let (mut start, mut i) = (0, 0);
+    while i < text.len() {
+        match ligatures.iter().find(|lig| text[i..].starts_with(lig.text)) {
+            Some(lig) => {
+                if start < i {
+                    pieces.push(Shaped::Text(&text[start..i]));
+                }
+                pieces.push(Shaped::Ligature(lig));
+                i += lig.text.len();
+                start = i;
+            }
+            None => i += text[i..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    if start < text.len() {
+        pieces.push(Shaped::Text(&text[start..]));
+    }
+    pieces
+}
+
+/// Line-height factor of text without a `line-height` style.
+pub const DEFAULT_LINE_HEIGHT: f32 = 1.4;
+
+/// Non-breaking space (`&nbsp;`); drawn and measured as a plain space.
+pub const NBSP: char = '\u{00A0}';
+
+/// Soft hyphen (`&shy;`): an invisible point where a word may be broken.
+pub const SOFT_HYPHEN: char = '\u{00AD}';
+
 /// Word-wrap text to fit within `max_width` pixels. Returns a vec of lines.
+///
+/// A word that does not fit is broken at its last [`SOFT_HYPHEN`] that
+/// leaves a head (plus a visible `-`) fitting on the line; soft hyphens are
+/// removed from the returned lines.
 pub fn wrap_text(
     text: &str,
     font_size: f32,
@@ -212,41 +400,139 @@ pub fn wrap_text(
     max_width: f32,
     fonts: &FontManager,
 ) -> Vec<String> {
+    let width = |line: &str| fonts.measure_text_width(line, font_size, bold, italic, family);
+    wrap_words(text, max_width, false, &width)
+}
+
+/// Like [`wrap_text`], but a word too wide for a line of its own is broken
+/// between characters (CSS `overflow-wrap: break-word`). Words that fit on a
+/// line still move to the next one whole.
+pub fn wrap_text_break_word(
+    text: &str,
+    font_size: f32,
+    bold: bool,
+    italic: bool,
+    family: &str,
+    max_width: f32,
+    fonts: &FontManager,
+) -> Vec<String> {
+    let width = |line: &str| fonts.measure_text_width(line, font_size, bold, italic, family);
+    wrap_words(text, max_width, true, &width)
+}
+
+/// Wrap `text` to `max_width` as measured by `width`, breaking over-wide
+/// words between characters if `break_words` is set.
+pub(crate) fn wrap_words(text: &str, max_width: f32, break_words: bool, width: &dyn Fn(&str) -> f32) -> Vec<String> {
     if max_width <= 0.0 || text.is_empty() {
-        return vec![text.to_string()];
+        return vec![text.replace(SOFT_HYPHEN, "")];
     }
+    let width = |chars: &[char]| width(&chars.iter().collect::<String>());
 
     let mut lines: Vec<String> = Vec::new();
     // Split on existing newlines first
     for paragraph in text.split('\n') {
-        let words: Vec<&str> = paragraph.split_whitespace().collect();
+        let words: Vec<(char, Vec<char>)> = paragraph.split_whitespace().map(|word| (' ', word.chars().collect())).collect();
         if words.is_empty() {
             lines.push(String::new());
             continue;
         }
+        lines.extend(wrap_chars(words, max_width, break_words, &width).into_iter().map(|line| line.into_iter().collect::<String>()));
+    }
 
-        let mut current_line = String::new();
-        for word in &words {
-            let candidate = if current_line.is_empty() {
-                word.to_string()
-            } else {
-                format!("{} {}", current_line, word)
-            };
-            let w = fonts.measure_text_width(&candidate, font_size, bold, italic, family);
-            if w > max_width && !current_line.is_empty() {
-                lines.push(current_line);
-                current_line = word.to_string();
-            } else {
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// A character [`wrap_chars`] can lay out: a plain `char`, or one tagged with
+/// the text run it came from.
+pub(crate) trait WrapChar: Copy {
+    fn char(self) -> char;
+    /// `c` in place of this character, from the same run.
+    fn with_char(self, c: char) -> Self;
+}
+
+impl WrapChar for char {
+    fn char(self) -> char {
+        self
+    }
+
+    fn with_char(self, c: char) -> Self {
+        c
+    }
+}
+
+impl WrapChar for (char, usize) {
+    fn char(self) -> char {
+        self.0
+    }
+
+    fn with_char(self, c: char) -> Self {
+        (c, self.1)
+    }
+}
+
+/// Fill lines no wider than `max_width` as measured by `width` with `words`,
+/// each given with the space that separates it from the previous word.
+///
+/// A word that does not fit breaks at its last soft hyphen that leaves the
+/// head and a hyphen on the line, else moves to a fresh line; there, an
+/// over-wide word is broken between characters if `break_words` is set.
+/// Soft hyphens never appear in the returned lines.
+pub(crate) fn wrap_chars<T: WrapChar>(words: Vec<(T, Vec<T>)>, max_width: f32, break_words: bool, width: &dyn Fn(&[T]) -> f32) -> Vec<Vec<T>> {
+    let fits = |line: &[T]| width(line) <= max_width;
+    let visible = |chars: &[T]| -> Vec<T> { chars.iter().copied().filter(|c| c.char() != SOFT_HYPHEN).collect() };
+    let append = |line: &[T], space: T, word: Vec<T>| {
+        if line.is_empty() {
+            word
+        } else {
+            [line, &[space], &word].concat()
+        }
+    };
+
+    let mut lines: Vec<Vec<T>> = Vec::new();
+    let mut current_line: Vec<T> = Vec::new();
+    for (space, word) in &words {
+        let mut rest: &[T] = word;
+        loop {
+            let candidate = append(&current_line, *space, visible(rest));
+            if fits(&candidate) {
                 current_line = candidate;
+                break;
+            }
+            let head_line = |i: usize| {
+                let mut head = visible(&rest[..i]);
+                head.push(rest[i - 1].with_char('-'));
+                append(&current_line, *space, head)
+            };
+            let split = (1..rest.len()).rev().filter(|&i| rest[i].char() == SOFT_HYPHEN).find(|&i| fits(&head_line(i)));
+            match split {
+                Some(i) => {
+                    lines.push(head_line(i));
+                    current_line = Vec::new();
+                    rest = &rest[i + 1..];
+                }
+                // Retry the word at the start of a fresh line.
+                None if !current_line.is_empty() => {
+                    lines.push(std::mem::take(&mut current_line));
+                }
+                None => {
+                    current_line = visible(rest);
+                    // Break the word at the last character that fits,
+                    // keeping at least one per line.
+                    while break_words && !fits(&current_line) && current_line.len() > 1 {
+                        let split = (1..current_line.len()).take_while(|&i| fits(&current_line[..i])).last().unwrap_or(1);
+                        let rest = current_line.split_off(split);
+                        lines.push(std::mem::replace(&mut current_line, rest));
+                    }
+                    break;
+                }
             }
-        }
-        if !current_line.is_empty() {
-            lines.push(current_line);
         }
     }
-
-    if lines.is_empty() {
-        lines.push(String::new());
+    if !current_line.is_empty() {
+        lines.push(current_line);
     }
     lines
 }
@@ -269,4 +555,73 @@ mod tests {
         let lines = wrap_text("Hello world foo bar", 16.0, false, false, "Helvetica", 60.0, &mgr);
         assert!(lines.len() >= 2, "Expected wrapping, got {:?}", lines);
     }
+
+    #[test]
+    fn soft_hyphen_breaks_with_a_visible_hyphen() {
+        let mgr = FontManager::default();
+        let word = "Super\u{AD}cali\u{AD}fragilistic";
+        let width = mgr.measure_text_width("Supercalifragi", 16.0, false, false, "Helvetica");
+        let lines = wrap_text(word, 16.0, false, false, "Helvetica", width, &mgr);
+        assert_eq!(lines, ["Supercali-", "fragilistic"]);
+
+        let wide = wrap_text(word, 16.0, false, false, "Helvetica", 1000.0, &mgr);
+        assert_eq!(wide, ["Supercalifragilistic"]);
+    }
+
+    #[test]
+    fn break_word_only_splits_words_wider_than_the_line() {
+        let mgr = FontManager::default();
+        let width = mgr.measure_text_width("Hello", 16.0, false, false, "Helvetica");
+        let lines = wrap_text_break_word("Hello world", 16.0, false, false, "Helvetica", width, &mgr);
+        assert_eq!(lines, ["Hello", "world"]);
+
+        let lines = wrap_text_break_word("Hi abcdefghijkl yo", 16.0, false, false, "Helvetica", width, &mgr);
+        assert_eq!(lines, ["Hi", "abcde", "fghij", "kl yo"]);
+        let normal = wrap_text("Hi abcdefghijkl yo", 16.0, false, false, "Helvetica", width, &mgr);
+        assert_eq!(normal, ["Hi", "abcdefghijkl", "yo"]);
+    }
+
+    #[test]
+    fn ligatures_change_the_width_of_fi() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/RobotoMedium.ttf");
+        let mut mgr = FontManager::new();
+        mgr.load_font("Roboto", false, false, std::fs::read(path).unwrap()).unwrap();
+        let plain = mgr.measure_text_width("fi", 16.0, false, false, "Roboto");
+
+        mgr.set_ligatures(true);
+        let fi = &mgr.ligatures_for(false, false, "Roboto")[0];
+        assert_eq!((fi.text, fi.ch), ("fi", '\u{FB01}'));
+        let joined = mgr.measure_text_width("fi", 16.0, false, false, "Roboto");
+        assert_ne!(joined, plain);
+        assert_eq!(joined, fi.advance as f32 * 16.0 / 2048.0);
+    }
+
+    #[test]
+    fn apply_ligatures_splits_around_the_ligature() {
+        let lig = |text, ch| Ligature { text, ch, glyph: 1, advance: 1 };
+        let ligatures = [lig("ffi", '\u{FB03}'), lig("fi", '\u{FB01}')];
+        let pieces = apply_ligatures("office fit", &ligatures);
+        assert_eq!(
+            pieces,
+            [
+                Shaped::Text("o"),
+                Shaped::Ligature(&ligatures[0]),
+                Shaped::Text("ce "),
+                Shaped::Ligature(&ligatures[1]),
+                Shaped::Text("t"),
+            ]
+        );
+        assert_eq!(apply_ligatures("fit", &[]), [Shaped::Text("fit")]);
+    }
+
+    #[test]
+    fn block_height_counts_wrapped_lines() {
+        let mgr = FontManager::default();
+        let width = mgr.measure_text_width("Hello world", 16.0, false, false, "Helvetica");
+        let height = mgr.measure_block_height("Hello world foo bar", 16.0, false, false, "Helvetica", width);
+        assert_eq!(height, 2.0 * mgr.line_height_px(16.0, DEFAULT_LINE_HEIGHT));
+
+        let one = mgr.measure_block_height("Hello", 16.0, false, false, "Helvetica", width);
+        assert_eq!(one, mgr.line_height_px(16.0, DEFAULT_LINE_HEIGHT));
+    }
 }
@@ -0,0 +1,115 @@
+//! Language-based hyphenation: soft hyphens are inserted at the syllable
+//! boundaries of every word, so wrapping can break words there the same way
+//! it breaks at an authored `&shy;`.
+//!
+//! Syllable patterns come from the `hypher` crate and are only compiled in
+//! with the `hyphenation` cargo feature.
+
+use crate::style::{StyledNode, WhiteSpace};
+
+/// Insert soft hyphens at the syllable boundaries of the words in `text`,
+/// hyphenated as the language with ISO 639-1 code `lang` (e.g. `"en"`).
+///
+/// Returns `None` if the language is not supported, or if the crate was
+/// built without the `hyphenation` feature.
+pub fn hyphenate_text(text: &str, lang: &str) -> Option<String> {
+    let lang = language(lang)?;
+    let mut out = String::with_capacity(text.len());
+    let mut word_start = None;
+    for (i, ch) in text.char_indices() {
+        match (ch.is_alphabetic(), word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(start)) => {
+                push_word(&mut out, &text[start..i], lang);
+                word_start = None;
+                out.push(ch);
+            }
+            (false, None) => out.push(ch),
+            (true, Some(_)) => {}
+        }
+    }
+    if let Some(start) = word_start {
+        push_word(&mut out, &text[start..], lang);
+    }
+    Some(out)
+}
+
+/// Hyphenate the text of a styled tree in place, leaving preformatted text
+/// alone. Logs a warning and changes nothing if `lang` is not supported.
+pub(crate) fn hyphenate_tree(nodes: &mut [StyledNode], lang: &str) {
+    if language(lang).is_none() {
+        log::warn!(
+            "hyphenation for '{lang}' is not available; \
+             check the language code and the `hyphenation` feature"
+        );
+        return;
+    }
+    for node in nodes {
+        match node {
+            StyledNode::Element { children, .. } => hyphenate_tree(children, lang),
+            StyledNode::Text { text, style } if style.white_space == WhiteSpace::Normal => {
+                if let Some(hyphenated) = hyphenate_text(text, lang) {
+                    *text = hyphenated;
+                }
+            }
+            StyledNode::Text { .. } => {}
+        }
+    }
+}
+
+#[cfg(feature = "hyphenation")]
+type Lang = hypher::Lang;
+
+#[cfg(not(feature = "hyphenation"))]
+type Lang = std::convert::Infallible;
+
+#[cfg(feature = "hyphenation")]
+fn language(code: &str) -> Option<Lang> {
+    let code: [u8; 2] = code.to_ascii_lowercase().as_bytes().try_into().ok()?;
+    hypher::Lang::from_iso(code)
+}
+
+#[cfg(not(feature = "hyphenation"))]
+fn language(_code: &str) -> Option<Lang> {
+    None
+}
+
+/// Append `word` with a soft hyphen between each two syllables.
+#[cfg(feature = "hyphenation")]
+fn push_word(out: &mut String, word: &str, lang: Lang) {
+    for (i, syllable) in hypher::hyphenate(word, lang).enumerate() {
+        if i > 0 {
+            out.push(crate::fonts::SOFT_HYPHEN);
+        }
+        out.push_str(syllable);
+    }
+}
+
+#[cfg(not(feature = "hyphenation"))]
+fn push_word(_out: &mut String, _word: &str, lang: Lang) {
+    match lang {}
+}
+
+#[cfg(all(test, feature = "hyphenation"))]
+mod tests {
+    use super::*;
+    use crate::fonts::{wrap_text, FontManager, SOFT_HYPHEN};
+
+    #[test]
+    fn english_breaks_extraordinary_at_a_syllable() {
+        let text = hyphenate_text("an extraordinary day", "en").unwrap();
+        let syllables: Vec<&str> = text.split(SOFT_HYPHEN).collect();
+        assert_eq!(syllables, ["an ex", "tra", "or", "di", "nary day"]);
+
+        let fonts = FontManager::default();
+        let word = hyphenate_text("extraordinary", "en").unwrap();
+        let width = fonts.measure_text_width("extraor-", 16.0, false, false, "Helvetica");
+        let lines = wrap_text(&word, 16.0, false, false, "Helvetica", width, &fonts);
+        assert_eq!(lines, ["extraor-", "dinary"]);
+    }
+
+    #[test]
+    fn unknown_language_is_not_hyphenated() {
+        assert_eq!(hyphenate_text("extraordinary", "xx"), None);
+    }
+}
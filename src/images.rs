@@ -0,0 +1,164 @@
+//! Image sources: probing intrinsic sizes and decoding each distinct `src`
+//! once per document.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::rc::Rc;
+
+use printpdf::RawImage;
+
+use crate::render::{parse_data_uri, ImageIssue, ImageIssueKind};
+use crate::svg;
+
+/// Intrinsic `(width, height)` in pixels of a base64 `data:` URI image,
+/// reading only as much of it as needed to find the size (the image header
+/// for bitmaps). SVG sizes are rounded up to whole CSS px.
+///
+/// Returns `None` when `src` is not a base64 data URI or the format is not
+/// recognised. A `Some` result does not guarantee the image decodes; use
+/// [`check_image_src`](crate::render::check_image_src) for that.
+pub fn probe_data_uri(src: &str) -> Option<(u32, u32)> {
+    let (width, height) = probe_px(src)?;
+    Some((width.ceil() as u32, height.ceil() as u32))
+}
+
+/// Unrounded intrinsic size of `src` in px; SVG documents may be a
+/// fractional number of px.
+fn probe_px(src: &str) -> Option<(f32, f32)> {
+    let bytes = parse_data_uri(src).ok()?;
+    if svg::is_svg_data_uri(src) {
+        return svg::intrinsic_size(&bytes).ok();
+    }
+    let (width, height) = ::image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()?;
+    Some((width as f32, height as f32))
+}
+
+/// Resolution that maps raster image pixels to points by default: 96 DPI,
+/// the resolution of a CSS px, so a 96 px wide image is one inch (72 pt).
+pub const DEFAULT_IMAGE_DPI: f32 = 96.0;
+
+/// An image decoded once for rendering.
+pub(crate) struct DecodedImage {
+    /// Intrinsic width in points, as [`ImageCache::size`] reports it.
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    /// Pixels ready to embed as an image XObject.
+    pub(crate) raw: RawImage,
+}
+
+/// Image sizes and decoded images for one document, keyed by `src`.
+///
+/// Layout only needs sizes, which are probed from the image header; pixels
+/// are decoded on first use by the renderer. Failures are cached too, so a
+/// broken image is reported once per document rather than re-decoded.
+pub(crate) struct ImageCache {
+    svg_dpi: f32,
+    image_dpi: f32,
+    sizes: RefCell<HashMap<String, Option<(f32, f32)>>>,
+    entries: RefCell<HashMap<String, Result<Rc<DecodedImage>, ImageIssue>>>,
+    decodes: Cell<usize>,
+}
+
+impl ImageCache {
+    /// An empty cache that rasterizes SVG sources at `svg_dpi` and sizes
+    /// raster images at `image_dpi`.
+    pub(crate) fn new(svg_dpi: f32, image_dpi: f32) -> Self {
+        Self {
+            svg_dpi,
+            image_dpi,
+            sizes: RefCell::new(HashMap::new()),
+            entries: RefCell::new(HashMap::new()),
+            decodes: Cell::new(0),
+        }
+    }
+
+    /// Intrinsic `(width, height)` of `src` in points, from
+    /// [`probe_data_uri`]'s pixel size: raster pixels at the image DPI, SVG
+    /// CSS px one to one (1 px = 1 pt).
+    pub(crate) fn size(&self, src: &str) -> Option<(f32, f32)> {
+        if let Some(size) = self.sizes.borrow().get(src) {
+            return *size;
+        }
+        let pt_per_px = if svg::is_svg_data_uri(src) {
+            1.0
+        } else {
+            72.0 / self.image_dpi
+        };
+        let size = probe_px(src).map(|(w, h)| (w * pt_per_px, h * pt_per_px));
+        self.sizes.borrow_mut().insert(src.to_string(), size);
+        size
+    }
+
+    /// The decoded image for `src`, decoding it on first use.
+    pub(crate) fn get(&self, src: &str) -> Result<Rc<DecodedImage>, ImageIssue> {
+        if let Some(entry) = self.entries.borrow().get(src) {
+            return entry.clone();
+        }
+        self.decodes.set(self.decodes.get() + 1);
+        let entry = decode_image(src, self.svg_dpi).and_then(|raw| {
+            let (width, height) = self.size(src).ok_or_else(|| {
+                ImageIssue::new(src, ImageIssueKind::UnsupportedFormat, "unknown image size")
+            })?;
+            Ok(Rc::new(DecodedImage { width, height, raw }))
+        });
+        self.entries
+            .borrow_mut()
+            .insert(src.to_string(), entry.clone());
+        entry
+    }
+
+    /// Number of sources decoded so far.
+    #[cfg(test)]
+    pub(crate) fn decodes(&self) -> usize {
+        self.decodes.get()
+    }
+}
+
+/// Decode a data URI into embeddable pixels, rasterizing SVG sources at
+/// `svg_dpi`.
+pub(crate) fn decode_image(src: &str, svg_dpi: f32) -> Result<RawImage, ImageIssue> {
+    let mut bytes = parse_data_uri(src)?;
+    if svg::is_svg_data_uri(src) {
+        bytes = svg::rasterize_to_png(&bytes, svg_dpi)
+            .map_err(|e| ImageIssue::new(src, ImageIssueKind::UnsupportedFormat, e))?;
+    }
+    RawImage::decode_from_bytes(&bytes, &mut Vec::new()).map_err(|e| {
+        ImageIssue::new(
+            src,
+            ImageIssueKind::UnsupportedFormat,
+            format!("decode error: {e}"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3×2 red PNG.
+    const PNG_3X2: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAMAAAACCAIAAAASFvFNAAAAEElEQVR4nGP4z8AAQQxwFgBB0gX7h/C5SAAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn probe_reports_pixel_size() {
+        assert_eq!(probe_data_uri(PNG_3X2), Some((3, 2)));
+        let svg = "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciIHdpZHRoPSI0MCIgaGVpZ2h0PSIyMCIvPg==";
+        assert_eq!(probe_data_uri(svg), Some((40, 20)));
+        assert_eq!(probe_data_uri("photo.png"), None);
+        assert_eq!(probe_data_uri("data:image/png;base64,AAAA"), None);
+    }
+
+    #[test]
+    fn sizes_are_probed_pixels_in_points() {
+        let images = ImageCache::new(144.0, 96.0);
+        assert_eq!(images.size(PNG_3X2), Some((2.25, 1.5)));
+        assert_eq!(images.decodes(), 0);
+        let image = images.get(PNG_3X2).unwrap();
+        assert_eq!((image.width, image.height), (2.25, 1.5));
+        assert_eq!((image.raw.width, image.raw.height), (3, 2));
+    }
+}
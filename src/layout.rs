@@ -4,8 +4,11 @@
 use std::collections::HashMap;
 use taffy::prelude::*;
 
-use crate::fonts::{wrap_text, FontManager};
+use crate::fonts::{wrap_chars, wrap_words, FontManager, SOFT_HYPHEN};
+use crate::images::{ImageCache, DEFAULT_IMAGE_DPI};
+use crate::layout_config::TextRun;
 use crate::style::{self, ComputedStyle, FontStyle as CssFontStyle, FontWeight, StyledNode};
+use crate::svg::DEFAULT_SVG_DPI;
 
 // ---------------------------------------------------------------------------
 // Intermediate layout tree (pre-pagination)
@@ -24,6 +27,16 @@ pub struct PositionedBox {
     pub page_break_before: bool,
     pub page_break_after: bool,
     pub page_break_inside_avoid: bool,
+    pub keep_with_next: bool,
+    /// Horizontal shrink factor for over-wide `scale_to_fit` tables.
+    pub scale_x: Option<f32>,
+    /// `id`s (and `<a name>`s) of the element and of inline elements whose
+    /// text was merged into this box; internal links jump to them.
+    pub anchors: Vec<String>,
+    /// `<a href>` target of the element or of its merged inline text.
+    pub link: Option<String>,
+    /// 1–3 for `<h1>`–`<h3>` boxes.
+    pub heading_level: Option<u8>,
 }
 
 #[derive(Debug, Clone)]
@@ -32,13 +45,23 @@ pub enum BoxContent {
     Text {
         text: String,
         lines: Vec<String>,
+        /// Per-line runs for text mixing sizes or baselines; empty when the
+        /// whole box is set in one style.
+        runs: Vec<Vec<TextRun>>,
     },
     Image {
         src: String,
+        /// `alt` text, drawn in place of an image that cannot be embedded.
+        alt: Option<String>,
     },
     /// List item marker
     ListItem {
         marker: String,
+        /// Width of the marker as drawn.
+        width: f32,
+        /// Space between the marker and the item: on the left, or on the
+        /// right for right-to-left lists.
+        gap: f32,
     },
 }
 
@@ -49,18 +72,35 @@ pub enum BoxContent {
 struct LayoutBuilder<'a> {
     taffy: TaffyTree<()>,
     fonts: &'a FontManager,
+    /// Images decoded for their intrinsic size, reused by the renderer.
+    images: &'a ImageCache,
     node_styles: HashMap<NodeId, ComputedStyle>,
     node_content: HashMap<NodeId, BoxContent>,
+    node_scale_x: HashMap<NodeId, f32>,
+    node_anchors: HashMap<NodeId, Vec<String>>,
+    node_links: HashMap<NodeId, String>,
+    node_headings: HashMap<NodeId, u8>,
+    /// `<col>` widths of the table whose rows are being built.
+    table_columns: Vec<style::Dimension>,
+    /// Number of `<ul>`/`<ol>` elements enclosing the children being built.
+    list_depth: usize,
     available_width: f32,
 }
 
 impl<'a> LayoutBuilder<'a> {
-    fn new(fonts: &'a FontManager, available_width: f32) -> Self {
+    fn new(fonts: &'a FontManager, images: &'a ImageCache, available_width: f32) -> Self {
         Self {
             taffy: TaffyTree::new(),
             fonts,
+            images,
             node_styles: HashMap::new(),
             node_content: HashMap::new(),
+            node_scale_x: HashMap::new(),
+            node_anchors: HashMap::new(),
+            node_links: HashMap::new(),
+            node_headings: HashMap::new(),
+            table_columns: Vec::new(),
+            list_depth: 0,
             available_width,
         }
     }
@@ -95,6 +135,16 @@ impl<'a> LayoutBuilder<'a> {
         })
     }
 
+    /// Remember the link anchors and target of `node` for [`Self::extract`].
+    fn record_targets(&mut self, node: NodeId, anchors: Vec<String>, link: Option<String>) {
+        if !anchors.is_empty() {
+            self.node_anchors.entry(node).or_default().extend(anchors);
+        }
+        if let Some(link) = link {
+            self.node_links.entry(node).or_insert(link);
+        }
+    }
+
     fn build_node(&mut self, styled: &StyledNode, parent_width: f32) -> NodeId {
         match styled {
             StyledNode::Text { text, style } => self.build_text_node(text, style, parent_width),
@@ -138,36 +188,115 @@ impl<'a> LayoutBuilder<'a> {
     }
 
     fn build_text_node(&mut self, text: &str, style: &ComputedStyle, parent_width: f32) -> NodeId {
-        let bold = style.font_weight == FontWeight::Bold;
-        let italic = style.font_style == CssFontStyle::Italic;
-        let family = &style.font_family;
-        let font_size = style.font_size;
-        let line_height_px = self.fonts.line_height_px(font_size, style.line_height);
+        if style.white_space == style::WhiteSpace::Pre {
+            let lines = self.preformatted_lines(text, style);
+            return self.build_text_leaf(text.trim(), lines, style);
+        }
+        let lines = self.wrap_lines(text, style, parent_width);
+        self.build_text_leaf(text.trim(), lines, style)
+    }
+
+    /// Split preformatted `text` at its line breaks, dropping the newline
+    /// right after `<pre>` and trailing whitespace, and expand tabs.
+    fn preformatted_lines(&self, text: &str, style: &ComputedStyle) -> Vec<String> {
+        let text = text.strip_prefix('\n').unwrap_or(text).trim_end();
+        text.lines()
+            .map(|line| self.expand_tabs(line, style))
+            .collect()
+    }
 
-        // Word-wrap the text
+    /// Replace each tab with enough spaces to reach the next tab stop; stops
+    /// are `style.tab_size` spaces apart in `style`'s font.
+    fn expand_tabs(&self, line: &str, style: &ComputedStyle) -> String {
+        if !line.contains('\t') {
+            return line.to_string();
+        }
+        let mut out = String::new();
+        for (i, piece) in line.split('\t').enumerate() {
+            if i > 0 {
+                out.extend(std::iter::repeat_n(' ', self.tab_spaces(&out, style)));
+            }
+            out.push_str(piece);
+        }
+        out
+    }
+
+    /// Number of spaces a tab after `before` expands to: enough to reach the
+    /// next tab stop, and at least one.
+    fn tab_spaces(&self, before: &str, style: &ComputedStyle) -> usize {
+        let space = self.text_width(" ", style);
+        if space <= 0.0 {
+            return 1;
+        }
+        let stop = space * style.tab_size.max(1) as f32;
+        let width = self.text_width(before, style);
+        let next = ((width / stop).floor() + 1.0) * stop;
+        ((next - width) / space).round().max(1.0) as usize
+    }
+
+    /// Word-wrap `text` in `style`'s font to `parent_width`.
+    fn wrap_lines(&self, text: &str, style: &ComputedStyle, parent_width: f32) -> Vec<String> {
         let max_w = if parent_width > 0.0 {
             parent_width
         } else {
             self.available_width
         };
-        let lines = wrap_text(
-            text.trim(),
-            font_size,
-            bold,
-            italic,
-            family,
-            max_w,
-            self.fonts,
+        let break_words = style.overflow_wrap == style::OverflowWrap::BreakWord;
+        wrap_words(text.trim(), max_w, break_words, &|line| {
+            self.text_width(line, style)
+        })
+    }
+
+    /// Width of `text` set in `style`, letter spacing included.
+    fn text_width(&self, text: &str, style: &ComputedStyle) -> f32 {
+        let glyphs = self.fonts.measure_text_width(
+            text,
+            style.font_size,
+            style.font_weight == FontWeight::Bold,
+            style.font_style == CssFontStyle::Italic,
+            &style.font_family,
         );
+        let spacing = style.letter_spacing * style.font_size;
+        glyphs + spacing * text.chars().filter(|&c| c != SOFT_HYPHEN).count() as f32
+    }
+
+    /// Height of `line_count` lines of text in `style`.
+    fn text_height(&self, line_count: usize, style: &ComputedStyle) -> f32 {
+        let heights = line_heights(&[], line_count, style, self.fonts);
+        self.lines_height(&heights, style)
+    }
 
+    /// Height of lines of text in `style` that are `heights` tall.
+    fn lines_height(&self, heights: &[f32], style: &ComputedStyle) -> f32 {
+        let bold = style.font_weight == FontWeight::Bold;
+        let italic = style.font_style == CssFontStyle::Italic;
+        let family = &style.font_family;
+        let line_height_px = self
+            .fonts
+            .line_height_px(style.font_size, style.line_height);
+        // The last line's glyphs extend a full ascender + descender below its
+        // top, which overflows `line_height_px` when the line-height is tight.
+        let glyph_height = self
+            .fonts
+            .ascender_px(style.font_size, bold, italic, family)
+            + self
+                .fonts
+                .descender_px(style.font_size, bold, italic, family);
+        if heights.is_empty() {
+            0.0
+        } else {
+            let total: f32 = heights.iter().sum();
+            total.max(total - line_height_px + glyph_height)
+        }
+    }
+
+    /// A leaf node sized to already-wrapped `lines`.
+    fn build_text_leaf(&mut self, text: &str, lines: Vec<String>, style: &ComputedStyle) -> NodeId {
         let text_width = lines
             .iter()
-            .map(|l| {
-                self.fonts
-                    .measure_text_width(l, font_size, bold, italic, family)
-            })
+            .map(|l| self.text_width(l, style))
             .fold(0.0f32, f32::max);
-        let text_height = lines.len() as f32 * line_height_px;
+        let text_height = self.text_height(lines.len(), style);
 
         let taffy_style = Style {
             size: Size {
@@ -182,8 +311,9 @@ impl<'a> LayoutBuilder<'a> {
         self.node_content.insert(
             node,
             BoxContent::Text {
-                text: text.trim().to_string(),
+                text: text.to_string(),
                 lines,
+                runs: Vec::new(),
             },
         );
         node
@@ -197,34 +327,118 @@ impl<'a> LayoutBuilder<'a> {
         attrs: &HashMap<String, String>,
         parent_width: f32,
     ) -> NodeId {
+        // List markers hang in the list's start padding (the right one for
+        // right-to-left lists), which grows when the widest marker and its
+        // gap would not fit.
+        let is_list = matches!(tag, crate::dom::Tag::Ul | crate::dom::Tag::Ol);
+        let markers = self.list_item_markers(tag, attrs, children);
+        let widened;
+        let style = if is_list {
+            let gutter = markers
+                .iter()
+                .flatten()
+                .map(|(_, width, gap)| width + gap)
+                .fold(0.0, f32::max);
+            let rtl = style.direction == style::Direction::Rtl;
+            let start = if rtl {
+                style.padding_right
+            } else {
+                style.padding_left
+            };
+            if gutter > start {
+                widened = match rtl {
+                    true => ComputedStyle {
+                        padding_right: gutter,
+                        ..style.clone()
+                    },
+                    false => ComputedStyle {
+                        padding_left: gutter,
+                        ..style.clone()
+                    },
+                };
+                &widened
+            } else {
+                style
+            }
+        } else {
+            style
+        };
+        let mut markers = markers.into_iter();
+
+        // Compute the width available for children the way Taffy will size
+        // the box: auto widths lose the margins, and padding and border are
+        // inside the width, so nested percentages get the real content width.
+        // Content-sized boxes wrap their text at its narrowest
+        // (`min-content`) or not at all (`max-content`) and are measured once
+        // their children are built.
+        let insets = style.padding_left
+            + style.padding_right
+            + style.border_left_width
+            + style.border_right_width;
+        let my_width = match style.width {
+            crate::style::Dimension::Px(w) => w,
+            crate::style::Dimension::Percent(p) => parent_width * p / 100.0,
+            crate::style::Dimension::Auto | crate::style::Dimension::FitContent => {
+                parent_width - style.margin_left - style.margin_right
+            }
+            crate::style::Dimension::MinContent => insets + 1.0,
+            crate::style::Dimension::MaxContent => f32::INFINITY,
+        };
+        let my_width = my_width
+            .min(resolve_length(style.max_width, parent_width).unwrap_or(f32::INFINITY))
+            .max(resolve_length(style.min_width, parent_width).unwrap_or(0.0));
+        let inner_width = my_width - insets;
+
+        // Multi-column blocks lay their children out at the column width and
+        // then distribute them over side-by-side column nodes.
+        let column_count = style.column_count.max(1) as usize;
+        let column_gap = style.column_gap.unwrap_or(style::MULTI_COLUMN_GAP);
+        let column_width =
+            ((inner_width - column_gap * (column_count - 1) as f32) / column_count as f32).max(1.0);
+
         // Paragraph-like block elements whose children are all inline get their
         // text merged into a single wrapped text node so spans flow correctly.
         let is_paragraph = matches!(
             tag,
             crate::dom::Tag::P | crate::dom::Tag::H1 | crate::dom::Tag::H2 | crate::dom::Tag::H3
         );
-        if is_paragraph && !children.is_empty() && Self::all_inline(children) {
+        if (is_paragraph || column_count > 1)
+            && style.white_space == style::WhiteSpace::Normal
+            && !children.is_empty()
+            && Self::all_inline(children)
+        {
             let raw: String = children.iter().map(Self::collect_inline_text).collect();
             // Normalise runs of whitespace/newlines to single spaces.
             let combined: String = raw.split_whitespace().collect::<Vec<_>>().join(" ");
             if !combined.is_empty() {
-                return self.build_text_node_with_para_style(&combined, style, parent_width);
+                if column_count > 1 {
+                    return self.build_text_columns(&combined, style, column_width);
+                }
+                let wrap_width = match style.width {
+                    crate::style::Dimension::MinContent => 1.0,
+                    crate::style::Dimension::MaxContent => f32::INFINITY,
+                    _ => parent_width.min(
+                        resolve_length(style.max_width, parent_width).unwrap_or(f32::INFINITY),
+                    ),
+                };
+                let node = self.build_text_node_with_para_style(&combined, style, wrap_width);
+                self.split_runs(node, children, style, wrap_width);
+                let (mut anchors, mut link) = element_targets(tag, attrs);
+                inline_targets(children, &mut anchors, &mut link);
+                self.record_targets(node, anchors, link);
+                if let Some(level) = heading_level(tag) {
+                    self.node_headings.insert(node, level);
+                }
+                return node;
             }
         }
 
-        // Compute the width available for children
-        let my_width = match style.width {
-            crate::style::Dimension::Px(w) => w,
-            crate::style::Dimension::Percent(p) => parent_width * p / 100.0,
-            crate::style::Dimension::Auto => parent_width,
-        };
-        let inner_width = my_width - style.padding_left - style.padding_right;
-
         // Estimate per-child width for flex-row containers and table rows so
         // that text is word-wrapped to the right column width at build time.
         let is_flex_row = style.display == style::Display::Flex
             && style.flex_direction == style::FlexDirection::Row;
         let is_table_row = *tag == crate::dom::Tag::Tr;
+        let is_cell = matches!(tag, crate::dom::Tag::Td | crate::dom::Tag::Th);
 
         let elem_child_count = children
             .iter()
@@ -232,46 +446,186 @@ impl<'a> LayoutBuilder<'a> {
             .count()
             .max(1);
 
-        let child_build_width = if is_flex_row || is_table_row {
-            let gap_total = style.gap * (elem_child_count.saturating_sub(1)) as f32;
-            ((inner_width - gap_total) / elem_child_count as f32).max(1.0)
+        // A row's cells cover `colspan` columns each; the row lays out over
+        // the total so spanning cells line up with the grid of other rows.
+        let spans: Vec<usize> = if is_table_row {
+            children
+                .iter()
+                .filter(|c| matches!(c, StyledNode::Element { .. }))
+                .map(col_span)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let track_count = if is_table_row {
+            spans.iter().sum::<usize>().max(1)
+        } else {
+            elem_child_count
+        };
+
+        // Cells in a column with an explicit `<col>` width get exactly that
+        // width; the others share the space that is left.
+        let cell_widths: Vec<Option<f32>> = if is_table_row {
+            self.table_columns
+                .iter()
+                .take(track_count)
+                .map(|w| match *w {
+                    style::Dimension::Px(w) => Some(w),
+                    style::Dimension::Percent(p) => Some(inner_width * p / 100.0),
+                    _ => None,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let child_build_width = if column_count > 1 {
+            column_width
+        } else if is_flex_row || is_table_row {
+            let gap_total =
+                style.column_gap.unwrap_or(0.0) * (track_count.saturating_sub(1)) as f32;
+            let fixed: f32 = cell_widths.iter().flatten().sum();
+            let shared = track_count - cell_widths.iter().flatten().count();
+            ((inner_width - gap_total - fixed) / shared.max(1) as f32).max(1.0)
         } else {
             inner_width
         };
 
         // Build child nodes
         let mut child_nodes = Vec::new();
-        let mut list_counter = 0u32;
+        let mut bottom_captions = Vec::new();
+        let mut cell_index = 0;
+        let mut column = 0;
 
-        for child in children {
-            // For list items, compute and record the marker string so it can
-            // be rendered as a bullet / number in the left gutter.
-            let li_marker: Option<String> =
-                if let StyledNode::Element { tag: child_tag, .. } = child {
-                    if *child_tag == crate::dom::Tag::Li {
-                        list_counter += 1;
-                        Some(if *tag == crate::dom::Tag::Ol {
-                            format!("{}. ", list_counter)
-                        } else {
-                            "\u{2022} ".to_string()
-                        })
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
+        // `<col>` widths apply to this table's rows, not to tables nested in
+        // its cells.
+        let outer_columns = (*tag == crate::dom::Tag::Table)
+            .then(|| std::mem::replace(&mut self.table_columns, column_widths(children)));
+        if is_list {
+            self.list_depth += 1;
+        }
+
+        // In block flow, consecutive inline children between block children
+        // wrap together in an anonymous text box rather than stacking as one
+        // box each.
+        let flows_inline = column_count == 1
+            && matches!(
+                style.display,
+                style::Display::Block | style::Display::ListItem | style::Display::TableCell
+            );
+        let groups: Vec<&[StyledNode]> = if flows_inline {
+            inline_runs(children)
+        } else {
+            children.iter().map(std::slice::from_ref).collect()
+        };
+
+        for group in groups {
+            if group.len() > 1 {
+                let text_style = style.clone().text_style();
+                if let Some(node) = self.build_inline_run(group, &text_style, child_build_width) {
+                    let (mut anchors, mut link) = (Vec::new(), None);
+                    inline_targets(group, &mut anchors, &mut link);
+                    self.record_targets(node, anchors, link);
+                    child_nodes.push(node);
+                }
+                continue;
+            }
+            let child = &group[0];
+            if let StyledNode::Element {
+                tag: crate::dom::Tag::Colgroup | crate::dom::Tag::Col,
+                ..
+            } = child
+            {
+                continue;
+            }
+
+            // List items take their markers, computed above, in order.
+            let li_marker = match child {
+                StyledNode::Element {
+                    tag: crate::dom::Tag::Li,
+                    ..
+                } => markers.next().flatten(),
+                _ => None,
+            };
 
-            let child_id = self.build_node(child, child_build_width);
+            // A cell's flex basis and grow factor: fixed `<col>` widths plus
+            // the gaps it spans, growing by one share per other column. A
+            // spanning cell is one box, so its border runs unbroken across
+            // the columns it covers.
+            let cell_flex = match child {
+                StyledNode::Element { .. } if is_table_row => {
+                    let span = spans.get(cell_index).copied().unwrap_or(1);
+                    cell_index += 1;
+                    let covered: Vec<Option<f32>> = (column..column + span)
+                        .map(|c| cell_widths.get(c).copied().flatten())
+                        .collect();
+                    column += span;
+                    let fixed: f32 = covered.iter().flatten().sum::<f32>()
+                        + style.column_gap.unwrap_or(0.0) * (span - 1) as f32;
+                    let shares = covered.iter().filter(|w| w.is_none()).count();
+                    (span > 1 || shares == 0).then_some((fixed, shares as f32))
+                }
+                _ => None,
+            };
+
+            let build_width = match cell_flex {
+                Some((fixed, shares)) => fixed + shares * child_build_width,
+                None => child_build_width,
+            };
+            let child_id = self.build_node(child, build_width);
+
+            if let Some((basis, grow)) = cell_flex {
+                let mut cell_style = self.taffy.style(child_id).unwrap().clone();
+                cell_style.flex_grow = grow;
+                cell_style.flex_basis = taffy::Dimension::Length(basis);
+                self.taffy.set_style(child_id, cell_style).unwrap();
+            }
 
             // Attach the marker to the taffy node so pagination can render it.
-            if let Some(marker) = li_marker {
+            if let Some((marker, width, gap)) = li_marker {
                 self.node_content
-                    .insert(child_id, BoxContent::ListItem { marker });
+                    .insert(child_id, BoxContent::ListItem { marker, width, gap });
+            }
+
+            // Cells align their items by `text-align`, which would shrink a
+            // nested table to its min-content width; a nested table always
+            // spans the cell's content box like a top-level one.
+            if is_cell
+                && matches!(
+                    child,
+                    StyledNode::Element {
+                        tag: crate::dom::Tag::Table,
+                        ..
+                    }
+                )
+            {
+                let mut table_style = self.taffy.style(child_id).unwrap().clone();
+                table_style.align_self = Some(taffy::AlignSelf::Stretch);
+                self.taffy.set_style(child_id, table_style).unwrap();
+            }
+
+            // `caption-side: bottom` captions stack below the table's rows.
+            if let StyledNode::Element {
+                tag: crate::dom::Tag::Caption,
+                style: caption_style,
+                ..
+            } = child
+            {
+                if caption_style.caption_side == style::CaptionSide::Bottom {
+                    bottom_captions.push(child_id);
+                    continue;
+                }
             }
 
             child_nodes.push(child_id);
         }
+        child_nodes.extend(bottom_captions);
+        if let Some(columns) = outer_columns {
+            self.table_columns = columns;
+        }
+        if is_list {
+            self.list_depth -= 1;
+        }
 
         // For <img> elements, resolve Auto width/height to concrete pixel dimensions
         // using the image's intrinsic size decoded from the base64 data URI.
@@ -282,28 +636,414 @@ impl<'a> LayoutBuilder<'a> {
                 || matches!(style.height, crate::style::Dimension::Auto))
         {
             let src = attrs.get("src").map(|s| s.as_str()).unwrap_or("");
-            resolve_img_auto_dimensions(src, style, parent_width)
+            resolve_img_auto_dimensions(src, style, parent_width, self.images)
         } else {
             None
         };
 
         let effective_style = style_override.as_ref().unwrap_or(style);
-        let taffy_style = self.computed_to_taffy(effective_style, tag);
+        let mut taffy_style = self.computed_to_taffy(effective_style, tag);
+        if column_count > 1 {
+            child_nodes = self.balance_columns(child_nodes, column_count, column_width);
+            set_column_row(&mut taffy_style, style);
+        }
         let node = self
             .taffy
             .new_with_children(taffy_style, &child_nodes)
             .unwrap();
         self.node_styles.insert(node, effective_style.clone());
+        let (anchors, link) = element_targets(tag, attrs);
+        self.record_targets(node, anchors, link);
+        if let Some(level) = heading_level(tag) {
+            self.node_headings.insert(node, level);
+        }
+
+        if matches!(
+            style.width,
+            crate::style::Dimension::MinContent
+                | crate::style::Dimension::MaxContent
+                | crate::style::Dimension::FitContent
+        ) {
+            let mut width = self.measure_content_width(node);
+            if style.width == crate::style::Dimension::FitContent && parent_width > 0.0 {
+                width = width.min(parent_width);
+            }
+            let mut sized = self.taffy.style(node).unwrap().clone();
+            sized.size.width = taffy::Dimension::Length(width);
+            self.taffy.set_style(node, sized).unwrap();
+        }
+
+        // Tables opting into `scale_to_fit` shrink horizontally at render time
+        // when their fixed width exceeds the space they were laid out in.
+        if *tag == crate::dom::Tag::Table
+            && style.scale_to_fit
+            && parent_width > 0.0
+            && my_width > parent_width
+        {
+            self.node_scale_x.insert(node, parent_width / my_width);
+        }
 
         // Handle images
         if *tag == crate::dom::Tag::Img {
             let src = attrs.get("src").cloned().unwrap_or_default();
-            self.node_content.insert(node, BoxContent::Image { src });
+            let alt = attrs.get("alt").filter(|a| !a.trim().is_empty()).cloned();
+            self.node_content
+                .insert(node, BoxContent::Image { src, alt });
+        }
+
+        node
+    }
+
+    /// The marker of each `<li>` among a list's `children`, in order, with
+    /// its width and the gap between it and the item. Items with
+    /// `list-style-type: none` get `None`.
+    fn list_item_markers(
+        &self,
+        tag: &crate::dom::Tag,
+        attrs: &HashMap<String, String>,
+        children: &[StyledNode],
+    ) -> Vec<Option<(String, f32, f32)>> {
+        // `<ol start>` numbers the first item; the counter is bumped before
+        // each `<li>` is labelled.
+        let mut counter = attrs
+            .get("start")
+            .filter(|_| *tag == crate::dom::Tag::Ol)
+            .and_then(|s| s.trim().parse::<i32>().ok())
+            .map_or(0, |start| start.saturating_sub(1));
+        let mut markers = Vec::new();
+        for child in children {
+            let StyledNode::Element {
+                tag: crate::dom::Tag::Li,
+                style,
+                attrs,
+                ..
+            } = child
+            else {
+                continue;
+            };
+            // `<li value>` renumbers this item and the ones after it.
+            counter = attrs
+                .get("value")
+                .and_then(|v| v.trim().parse::<i32>().ok())
+                .unwrap_or(counter.saturating_add(1));
+            // A list's own level is counted once its children are built.
+            let depth = self.list_depth
+                + usize::from(matches!(tag, crate::dom::Tag::Ul | crate::dom::Tag::Ol));
+            let marker = list_marker(style.list_style_type, counter, depth);
+            markers.push(marker.map(|marker| {
+                let width = match marker.trim_end() {
+                    // Drawn as shapes rather than glyphs; see `render`.
+                    "\u{25E6}" | "\u{25AA}" => style.font_size * 0.35,
+                    glyphs => self.fonts.measure_text_width(
+                        glyphs,
+                        style.font_size,
+                        false,
+                        false,
+                        "Helvetica",
+                    ),
+                };
+                (marker, width, style.list_marker_gap)
+            }));
+        }
+        markers
+    }
+
+    /// Merge a run of inline siblings into one wrapped text node set in
+    /// `text_style`, the style of text directly inside their parent.
+    fn build_inline_run(
+        &mut self,
+        nodes: &[StyledNode],
+        text_style: &ComputedStyle,
+        width: f32,
+    ) -> Option<NodeId> {
+        let raw: String = nodes.iter().map(Self::collect_inline_text).collect();
+        let combined = match text_style.white_space {
+            style::WhiteSpace::Pre => raw,
+            style::WhiteSpace::Normal => raw.split_whitespace().collect::<Vec<_>>().join(" "),
+        };
+        if combined.trim().is_empty() {
+            return None;
         }
+        let node = self.build_text_node(&combined, text_style, width);
+        self.split_runs(node, nodes, text_style, width);
+        Some(node)
+    }
 
+    /// Re-wrap a merged paragraph whose inline children change the font
+    /// size, weight, style, colour, decoration or letter spacing or the baseline (`<b>`,
+    /// `<sub>`, `<del>`…) with each word measured in its own font, splitting
+    /// every line into runs of uniformly styled text. Preformatted text keeps
+    /// its line breaks and is never re-wrapped.
+    fn split_runs(
+        &mut self,
+        node: NodeId,
+        children: &[StyledNode],
+        block: &ComputedStyle,
+        width: f32,
+    ) {
+        let mut segments = Vec::new();
+        for child in children {
+            inline_segments(child, None, &mut segments);
+        }
+        let differs = |s: &ComputedStyle| {
+            s.font_size != block.font_size
+                || s.baseline_shift != block.baseline_shift
+                || s.font_weight != block.font_weight
+                || s.font_style != block.font_style
+                || s.color != block.color
+                || s.text_decoration != block.text_decoration
+                || s.letter_spacing != block.letter_spacing
+        };
+        if !segments.iter().any(|(_, s, bg)| differs(s) || bg.is_some()) {
+            return;
+        }
+        let lines = match block.white_space {
+            style::WhiteSpace::Pre => self.preformatted_runs(&segments, block),
+            style::WhiteSpace::Normal => self.wrap_runs(&segments, block, width),
+        };
+        let measure = |text: &str, s: &ComputedStyle| self.text_width(text, s);
+
+        let mut text_lines = Vec::new();
+        let mut line_runs = Vec::new();
+        let mut text_width = 0.0f32;
+        for line in &lines {
+            let mut x = 0.0;
+            let mut out = Vec::new();
+            for piece in line.chunk_by(|a, b| a.1 == b.1) {
+                let (_, s, background) = segments[piece[0].1];
+                let text: String = piece.iter().map(|&(c, _)| c).collect();
+                let run_width = measure(&text, s);
+                out.push(TextRun {
+                    text,
+                    x_offset: x,
+                    font_size: s.font_size,
+                    baseline_shift: s.baseline_shift - block.baseline_shift,
+                    bold: (s.font_weight != block.font_weight)
+                        .then_some(s.font_weight == FontWeight::Bold),
+                    italic: (s.font_style != block.font_style)
+                        .then_some(s.font_style == CssFontStyle::Italic),
+                    color: (s.color != block.color)
+                        .then_some([s.color.r, s.color.g, s.color.b, s.color.a]),
+                    underline: (s.text_decoration != block.text_decoration)
+                        .then_some(s.text_decoration == style::TextDecoration::Underline),
+                    line_through: (s.text_decoration != block.text_decoration)
+                        .then_some(s.text_decoration == style::TextDecoration::LineThrough),
+                    background: background.map(|c| [c.r, c.g, c.b, c.a]),
+                    letter_spacing: (s.letter_spacing != block.letter_spacing)
+                        .then_some(s.letter_spacing * s.font_size),
+                    width: run_width,
+                });
+                x += run_width;
+            }
+            text_width = text_width.max(x);
+            text_lines.push(line.iter().map(|&(c, _)| c).collect::<String>());
+            line_runs.push(out);
+        }
+
+        let heights = line_heights(&line_runs, text_lines.len(), block, self.fonts);
+        let text_height = self.lines_height(&heights, block);
+        let Some(BoxContent::Text { lines, runs, .. }) = self.node_content.get_mut(&node) else {
+            return;
+        };
+        *lines = text_lines;
+        *runs = line_runs;
+        let mut sized = self.taffy.style(node).unwrap().clone();
+        sized.size = Size {
+            width: Dimension::Length(text_width),
+            height: Dimension::Length(text_height),
+        };
+        self.taffy.set_style(node, sized).unwrap();
+    }
+
+    /// Collapse the whitespace of `segments` the way merged text is built and
+    /// word-wrap them to `width` with `wrap_chars`, measuring each piece in
+    /// its own font, so soft hyphens and `break-words` behave as in
+    /// `wrap_lines`.
+    fn wrap_runs(
+        &self,
+        segments: &[Segment],
+        block: &ComputedStyle,
+        width: f32,
+    ) -> Vec<Vec<SegmentChar>> {
+        let width_of = |chars: &[SegmentChar]| -> f32 {
+            chars
+                .chunk_by(|a, b| a.1 == b.1)
+                .map(|piece| {
+                    let text: String = piece.iter().map(|&(c, _)| c).collect();
+                    self.text_width(&text, segments[piece[0].1].1)
+                })
+                .sum()
+        };
+        let break_words = block.overflow_wrap == style::OverflowWrap::BreakWord;
+
+        // Collapse whitespace the same way the merged text was built, keeping
+        // the segment each character came from, and split it into words.
+        let mut words: Vec<(SegmentChar, Vec<SegmentChar>)> = Vec::new();
+        let mut space = None;
+        for (i, (text, ..)) in segments.iter().enumerate() {
+            for ch in text.chars() {
+                if ch.is_whitespace() {
+                    space.get_or_insert(i);
+                } else {
+                    match words.last_mut() {
+                        Some((_, word)) if space.is_none() => word.push((ch, i)),
+                        _ => words.push(((' ', space.take().unwrap_or(i)), vec![(ch, i)])),
+                    }
+                }
+            }
+        }
+
+        let max_w = if width > 0.0 {
+            width
+        } else {
+            self.available_width
+        };
+        wrap_chars(words, max_w, break_words, &width_of)
+    }
+
+    /// Split preformatted `segments` at their line breaks like
+    /// `preformatted_lines`, expanding tabs in `block`'s font.
+    fn preformatted_runs(
+        &self,
+        segments: &[Segment],
+        block: &ComputedStyle,
+    ) -> Vec<Vec<SegmentChar>> {
+        let mut chars: Vec<SegmentChar> = segments
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (text, ..))| text.chars().map(move |c| (c, i)))
+            .filter(|&(c, _)| c != '\r' && c != SOFT_HYPHEN)
+            .collect();
+        if chars.first().is_some_and(|&(c, _)| c == '\n') {
+            chars.remove(0);
+        }
+        while chars.last().is_some_and(|&(c, _)| c.is_whitespace()) {
+            chars.pop();
+        }
+        chars
+            .split(|&(c, _)| c == '\n')
+            .map(|line| {
+                let mut out: Vec<SegmentChar> = Vec::new();
+                for &(c, i) in line {
+                    if c == '\t' {
+                        let before: String = out.iter().map(|&(c, _)| c).collect();
+                        out.extend(std::iter::repeat_n(
+                            (' ', i),
+                            self.tab_spaces(&before, block),
+                        ));
+                    } else {
+                        out.push((c, i));
+                    }
+                }
+                out
+            })
+            .collect()
+    }
+
+    /// Wrap `text` to `column_width` and split its lines evenly over
+    /// `style.column_count` columns, each a single text leaf.
+    fn build_text_columns(
+        &mut self,
+        text: &str,
+        style: &ComputedStyle,
+        column_width: f32,
+    ) -> NodeId {
+        let lines = self.wrap_lines(text, style, column_width);
+        let per_column = lines
+            .len()
+            .div_ceil(style.column_count.max(1) as usize)
+            .max(1);
+        let columns: Vec<NodeId> = lines
+            .chunks(per_column)
+            .map(|chunk| {
+                let leaf = self.build_text_leaf(&chunk.join(" "), chunk.to_vec(), style);
+                self.column_node(vec![leaf], column_width)
+            })
+            .collect();
+
+        let mut ts = self.computed_to_taffy(style, &crate::dom::Tag::Div);
+        set_column_row(&mut ts, style);
+        let node = self.taffy.new_with_children(ts, &columns).unwrap();
+        self.node_styles.insert(node, style.clone());
         node
     }
 
+    /// Distribute already-built children over `count` columns so that each
+    /// column holds roughly the same height. Children are never split.
+    fn balance_columns(
+        &mut self,
+        nodes: Vec<NodeId>,
+        count: usize,
+        column_width: f32,
+    ) -> Vec<NodeId> {
+        let heights: Vec<f32> = nodes
+            .iter()
+            .map(|&node| self.measure_height(node, column_width))
+            .collect();
+        let target = heights.iter().sum::<f32>() / count as f32;
+
+        let mut groups: Vec<Vec<NodeId>> = vec![Vec::new()];
+        let mut filled = 0.0;
+        for (node, height) in nodes.into_iter().zip(heights) {
+            // Start the next column once this child would mostly overflow
+            // the current one.
+            if filled > 0.0 && filled + height / 2.0 > target && groups.len() < count {
+                groups.push(Vec::new());
+                filled = 0.0;
+            }
+            groups.last_mut().unwrap().push(node);
+            filled += height;
+        }
+        groups
+            .into_iter()
+            .map(|group| self.column_node(group, column_width))
+            .collect()
+    }
+
+    /// Outer height of `node` (including vertical margins) at `width`.
+    fn measure_height(&mut self, node: NodeId, width: f32) -> f32 {
+        self.taffy
+            .compute_layout(
+                node,
+                Size {
+                    width: AvailableSpace::Definite(width),
+                    height: AvailableSpace::MaxContent,
+                },
+            )
+            .unwrap();
+        let layout = self.taffy.layout(node).unwrap();
+        layout.size.height + layout.margin.top + layout.margin.bottom
+    }
+
+    /// Border-box width of `node` laid out without any width constraint.
+    fn measure_content_width(&mut self, node: NodeId) -> f32 {
+        self.taffy
+            .compute_layout(
+                node,
+                Size {
+                    width: AvailableSpace::MaxContent,
+                    height: AvailableSpace::MaxContent,
+                },
+            )
+            .unwrap();
+        self.taffy.layout(node).unwrap().size.width
+    }
+
+    /// A fixed-width flex column holding one column's worth of children.
+    fn column_node(&mut self, children: Vec<NodeId>, width: f32) -> NodeId {
+        let style = Style {
+            display: taffy::Display::Flex,
+            flex_direction: taffy::FlexDirection::Column,
+            flex_shrink: 0.0,
+            size: Size {
+                width: taffy::Dimension::Length(width),
+                height: taffy::Dimension::Auto,
+            },
+            ..Default::default()
+        };
+        self.taffy.new_with_children(style, &children).unwrap()
+    }
+
     fn computed_to_taffy(&self, s: &ComputedStyle, tag: &crate::dom::Tag) -> Style {
         let mut ts = Style::default();
 
@@ -331,9 +1071,27 @@ impl<'a> LayoutBuilder<'a> {
                 };
                 return ts;
             }
+            crate::dom::Tag::Caption => {
+                ts.display = taffy::Display::Flex;
+                ts.flex_direction = taffy::FlexDirection::Column;
+                ts.align_items = text_align_items(s.text_align);
+                ts.padding = Rect {
+                    top: LengthPercentage::Length(s.padding_top),
+                    right: LengthPercentage::Length(s.padding_right),
+                    bottom: LengthPercentage::Length(s.padding_bottom),
+                    left: LengthPercentage::Length(s.padding_left),
+                };
+                ts.margin = Rect {
+                    top: LengthPercentageAuto::Length(s.margin_top),
+                    right: LengthPercentageAuto::Length(s.margin_right),
+                    bottom: LengthPercentageAuto::Length(s.margin_bottom),
+                    left: LengthPercentageAuto::Length(s.margin_left),
+                };
+                return ts;
+            }
             crate::dom::Tag::Tr => {
                 ts.display = taffy::Display::Flex;
-                ts.flex_direction = taffy::FlexDirection::Row;
+                ts.flex_direction = row_direction(s);
                 ts.align_items = Some(taffy::AlignItems::Stretch);
                 ts.size.width = taffy::Dimension::Percent(1.0);
                 ts.min_size.width = taffy::Dimension::Length(0.0);
@@ -352,6 +1110,15 @@ impl<'a> LayoutBuilder<'a> {
                 ts.flex_shrink = 1.0;
                 ts.flex_basis = taffy::Dimension::Length(0.0); // equal columns
                 ts.min_size.width = taffy::Dimension::Length(0.0);
+                // Text boxes are sized to their content, so `text-align` on the
+                // cell has to place them within the cell's content box.
+                ts.align_items = text_align_items(s.text_align);
+                // Cells are flex columns, so the main axis is vertical.
+                ts.justify_content = Some(match s.vertical_align {
+                    style::VerticalAlign::Top => taffy::JustifyContent::Start,
+                    style::VerticalAlign::Middle => taffy::JustifyContent::Center,
+                    style::VerticalAlign::Bottom => taffy::JustifyContent::End,
+                });
                 ts.padding = Rect {
                     top: LengthPercentage::Length(s.padding_top),
                     right: LengthPercentage::Length(s.padding_right),
@@ -359,10 +1126,10 @@ impl<'a> LayoutBuilder<'a> {
                     left: LengthPercentage::Length(s.padding_left),
                 };
                 ts.border = Rect {
-                    top: LengthPercentage::Length(s.border_width),
-                    right: LengthPercentage::Length(s.border_width),
-                    bottom: LengthPercentage::Length(s.border_width),
-                    left: LengthPercentage::Length(s.border_width),
+                    top: LengthPercentage::Length(s.border_top_width),
+                    right: LengthPercentage::Length(s.border_right_width),
+                    bottom: LengthPercentage::Length(s.border_bottom_width),
+                    left: LengthPercentage::Length(s.border_left_width),
                 };
                 return ts;
             }
@@ -374,16 +1141,17 @@ impl<'a> LayoutBuilder<'a> {
             style::Display::Flex => {
                 ts.display = taffy::Display::Flex;
                 ts.flex_direction = match s.flex_direction {
-                    style::FlexDirection::Row => taffy::FlexDirection::Row,
+                    style::FlexDirection::Row => row_direction(s),
                     style::FlexDirection::Column => taffy::FlexDirection::Column,
                 };
                 ts.flex_wrap = match s.flex_wrap {
                     style::FlexWrap::NoWrap => taffy::FlexWrap::NoWrap,
                     style::FlexWrap::Wrap => taffy::FlexWrap::Wrap,
                 };
+                // Flex-relative so that right-to-left rows pack from the right.
                 ts.justify_content = Some(match s.justify_content {
-                    style::JustifyContent::Start => taffy::JustifyContent::Start,
-                    style::JustifyContent::End => taffy::JustifyContent::End,
+                    style::JustifyContent::Start => taffy::JustifyContent::FlexStart,
+                    style::JustifyContent::End => taffy::JustifyContent::FlexEnd,
                     style::JustifyContent::Center => taffy::JustifyContent::Center,
                     style::JustifyContent::SpaceBetween => taffy::JustifyContent::SpaceBetween,
                     style::JustifyContent::SpaceAround => taffy::JustifyContent::SpaceAround,
@@ -398,12 +1166,12 @@ impl<'a> LayoutBuilder<'a> {
             }
             style::Display::Grid => {
                 ts.display = taffy::Display::Grid;
-                let cols = if !s.grid_template_columns.is_empty() {
-                    s.grid_template_columns.len()
+                ts.grid_template_columns = if s.grid_template_columns.is_empty() {
+                    vec![taffy::TrackSizingFunction::from_flex(1.0)]
                 } else {
-                    1
+                    grid_tracks(&s.grid_template_columns)
                 };
-                ts.grid_template_columns = vec![taffy::TrackSizingFunction::from_flex(1.0); cols];
+                ts.grid_template_rows = grid_tracks(&s.grid_template_rows);
             }
             style::Display::Block
             | style::Display::ListItem
@@ -424,14 +1192,21 @@ impl<'a> LayoutBuilder<'a> {
             }
         }
 
+        // Grid item placement
+        ts.grid_column = span(s.grid_column_span);
+        ts.grid_row = span(s.grid_row_span);
+
         // Sizing
         ts.size = Size {
             width: self.dim_to_taffy(s.width),
             height: self.dim_to_taffy(s.height),
         };
-        // Allow flex/shrink items to compress below their natural content size
+        // Allow flex/shrink items to compress below their natural content
+        // size unless they set a minimum width of their own.
         ts.min_size = Size {
-            width: if s.flex_shrink > 0.0 || s.flex_grow > 0.0 {
+            width: if s.min_width == style::Dimension::Auto
+                && (s.flex_shrink > 0.0 || s.flex_grow > 0.0)
+            {
                 taffy::Dimension::Length(0.0)
             } else {
                 self.dim_to_taffy(s.min_width)
@@ -442,6 +1217,15 @@ impl<'a> LayoutBuilder<'a> {
             width: self.dim_to_taffy(s.max_width),
             height: taffy::Dimension::Auto,
         };
+        ts.aspect_ratio = s.aspect_ratio;
+        if s.overflow == style::Overflow::Hidden {
+            // Clipped content does not hold the box open, so it may shrink
+            // below its content size like in CSS.
+            ts.overflow = taffy::Point {
+                x: taffy::Overflow::Hidden,
+                y: taffy::Overflow::Hidden,
+            };
+        }
 
         // Flex properties
         ts.flex_grow = s.flex_grow;
@@ -465,16 +1249,16 @@ impl<'a> LayoutBuilder<'a> {
 
         // Border
         ts.border = Rect {
-            top: LengthPercentage::Length(s.border_width),
-            right: LengthPercentage::Length(s.border_width),
-            bottom: LengthPercentage::Length(s.border_width),
-            left: LengthPercentage::Length(s.border_width),
+            top: LengthPercentage::Length(s.border_top_width),
+            right: LengthPercentage::Length(s.border_right_width),
+            bottom: LengthPercentage::Length(s.border_bottom_width),
+            left: LengthPercentage::Length(s.border_left_width),
         };
 
         // Gap
         ts.gap = Size {
-            width: LengthPercentage::Length(s.gap),
-            height: LengthPercentage::Length(s.gap),
+            width: LengthPercentage::Length(s.column_gap.unwrap_or(0.0)),
+            height: LengthPercentage::Length(s.row_gap),
         };
 
         ts
@@ -485,6 +1269,11 @@ impl<'a> LayoutBuilder<'a> {
             crate::style::Dimension::Auto => taffy::Dimension::Auto,
             crate::style::Dimension::Px(v) => taffy::Dimension::Length(v),
             crate::style::Dimension::Percent(v) => taffy::Dimension::Percent(v / 100.0),
+            // Taffy has no intrinsic size keywords; `build_element_node`
+            // measures these boxes and pins their width.
+            crate::style::Dimension::MinContent
+            | crate::style::Dimension::MaxContent
+            | crate::style::Dimension::FitContent => taffy::Dimension::Auto,
         }
     }
 
@@ -517,6 +1306,11 @@ impl<'a> LayoutBuilder<'a> {
             page_break_before: style.page_break_before,
             page_break_after: style.page_break_after,
             page_break_inside_avoid: style.page_break_inside_avoid,
+            keep_with_next: style.keep_with_next,
+            scale_x: self.node_scale_x.get(&node).copied(),
+            anchors: self.node_anchors.get(&node).cloned().unwrap_or_default(),
+            link: self.node_links.get(&node).cloned(),
+            heading_level: self.node_headings.get(&node).copied(),
             style,
             content,
             children,
@@ -524,31 +1318,316 @@ impl<'a> LayoutBuilder<'a> {
     }
 }
 
+/// A character of merged inline text and the index of the inline segment
+/// it came from.
+type SegmentChar = (char, usize);
+
+/// A fixed or percentage length in points; `None` for `auto` and the
+/// content-sized keywords.
+fn resolve_length(d: style::Dimension, parent_width: f32) -> Option<f32> {
+    match d {
+        style::Dimension::Px(v) => Some(v),
+        style::Dimension::Percent(p) => Some(parent_width * p / 100.0),
+        _ => None,
+    }
+}
+
+/// The anchor names (`id`, or `name` on `<a>`) and `href` of an element.
+fn element_targets(
+    tag: &crate::dom::Tag,
+    attrs: &HashMap<String, String>,
+) -> (Vec<String>, Option<String>) {
+    let mut anchors: Vec<String> = attrs.get("id").cloned().into_iter().collect();
+    let mut link = None;
+    if *tag == crate::dom::Tag::A {
+        anchors.extend(attrs.get("name").cloned());
+        link = attrs.get("href").cloned();
+    }
+    (anchors, link)
+}
+
+/// Add the anchors of every element in `nodes` and the first `href` among
+/// them, for inline content merged into a single text box.
+fn inline_targets(nodes: &[StyledNode], anchors: &mut Vec<String>, link: &mut Option<String>) {
+    for node in nodes {
+        if let StyledNode::Element {
+            tag,
+            attrs,
+            children,
+            ..
+        } = node
+        {
+            let (own_anchors, own_link) = element_targets(tag, attrs);
+            anchors.extend(own_anchors);
+            if link.is_none() {
+                *link = own_link;
+            }
+            inline_targets(children, anchors, link);
+        }
+    }
+}
+
+fn heading_level(tag: &crate::dom::Tag) -> Option<u8> {
+    match tag {
+        crate::dom::Tag::H1 => Some(1),
+        crate::dom::Tag::H2 => Some(2),
+        crate::dom::Tag::H3 => Some(3),
+        _ => None,
+    }
+}
+
+/// Cross-axis alignment that places content-sized text boxes in a flex
+/// column according to `text-align`.
+fn text_align_items(align: style::TextAlign) -> Option<taffy::AlignItems> {
+    match align {
+        style::TextAlign::Left => None,
+        style::TextAlign::Center => Some(taffy::AlignItems::Center),
+        style::TextAlign::Right => Some(taffy::AlignItems::End),
+    }
+}
+
+/// Height of each of `line_count` wrapped lines of text in `style`. A line
+/// holding a run set larger than the block's font grows to that run's line
+/// height, so it does not overlap its neighbours; lines without `runs` use
+/// the block's.
+pub(crate) fn line_heights(
+    runs: &[Vec<TextRun>],
+    line_count: usize,
+    style: &ComputedStyle,
+    fonts: &FontManager,
+) -> Vec<f32> {
+    (0..line_count)
+        .map(|i| {
+            let size = runs
+                .get(i)
+                .into_iter()
+                .flatten()
+                .map(|run| run.font_size)
+                .fold(style.font_size, f32::max);
+            fonts.line_height_px(size, style.line_height)
+        })
+        .collect()
+}
+
+/// Split `children` into runs of consecutive inline text content (text nodes
+/// and inline elements containing only such content); every other child is a
+/// run of its own.
+fn inline_runs(children: &[StyledNode]) -> Vec<&[StyledNode]> {
+    fn is_inline_text(node: &StyledNode) -> bool {
+        match node {
+            StyledNode::Text { .. } => true,
+            StyledNode::Element {
+                style, children, ..
+            } => style.display == style::Display::Inline && children.iter().all(is_inline_text),
+        }
+    }
+
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < children.len() {
+        let mut end = start + 1;
+        if is_inline_text(&children[start]) {
+            while end < children.len() && is_inline_text(&children[end]) {
+                end += 1;
+            }
+        }
+        runs.push(&children[start..end]);
+        start = end;
+    }
+    runs
+}
+
+/// A text node of merged inline content, its style, and the background of
+/// its nearest inline ancestor that has one (e.g. `<mark>`).
+type Segment<'s> = (&'s str, &'s ComputedStyle, Option<style::Color>);
+
+/// Text nodes of an all-inline subtree, in document order, with their styles
+/// and the `background` painted behind them.
+fn inline_segments<'s>(
+    node: &'s StyledNode,
+    background: Option<style::Color>,
+    out: &mut Vec<Segment<'s>>,
+) {
+    match node {
+        StyledNode::Text { text, style } => out.push((text, style, background)),
+        StyledNode::Element {
+            style, children, ..
+        } => {
+            let background = Some(style.background_color)
+                .filter(|c| !c.is_transparent())
+                .or(background);
+            for child in children {
+                inline_segments(child, background, out);
+            }
+        }
+    }
+}
+
+/// The marker string for the `n`th item (1-based) of a list nested `depth`
+/// lists deep, or `None` for `list-style-type: none`. `disc` bullets turn
+/// into circles and then squares as lists nest, like browsers' defaults.
+fn list_marker(kind: style::ListStyleType, n: i32, depth: usize) -> Option<String> {
+    use style::ListStyleType;
+    let kind = match (kind, depth) {
+        (ListStyleType::Disc, 2) => ListStyleType::Circle,
+        (ListStyleType::Disc, 3..) => ListStyleType::Square,
+        _ => kind,
+    };
+    Some(match kind {
+        ListStyleType::Disc => "\u{2022} ".to_string(),
+        ListStyleType::Circle => "\u{25E6} ".to_string(),
+        ListStyleType::Square => "\u{25AA} ".to_string(),
+        ListStyleType::Decimal => format!("{}. ", n),
+        ListStyleType::LowerAlpha => format!("{}. ", alpha_numeral(n)),
+        ListStyleType::UpperAlpha => format!("{}. ", alpha_numeral(n).to_uppercase()),
+        ListStyleType::LowerRoman => format!("{}. ", roman_numeral(n)),
+        ListStyleType::UpperRoman => format!("{}. ", roman_numeral(n).to_uppercase()),
+        ListStyleType::None => return None,
+    })
+}
+
+/// Bijective base-26 letters: 1 → `a`, 26 → `z`, 27 → `aa`; values below 1
+/// fall back to decimal.
+fn alpha_numeral(mut n: i32) -> String {
+    if n < 1 {
+        return n.to_string();
+    }
+    let mut letters = Vec::new();
+    while n > 0 {
+        n -= 1;
+        letters.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap()
+}
+
+/// Lowercase Roman numerals; values outside 1–3999 fall back to decimal.
+fn roman_numeral(n: i32) -> String {
+    if !(1..4000).contains(&n) {
+        return n.to_string();
+    }
+    const NUMERALS: [(i32, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut rest = n;
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while rest >= value {
+            out.push_str(numeral);
+            rest -= value;
+        }
+    }
+    out
+}
+
+/// Column widths declared by a table's `<colgroup>`/`<col>` children, one
+/// entry per column (`span` repeats an entry).
+fn column_widths(children: &[StyledNode]) -> Vec<style::Dimension> {
+    let mut widths = Vec::new();
+    for child in children {
+        let StyledNode::Element {
+            tag,
+            style,
+            children,
+            attrs,
+        } = child
+        else {
+            continue;
+        };
+        let span = attrs
+            .get("span")
+            .and_then(|s| s.trim().parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(1);
+        match tag {
+            crate::dom::Tag::Col => widths.extend(std::iter::repeat_n(style.width, span)),
+            crate::dom::Tag::Colgroup if children.is_empty() => {
+                widths.extend(std::iter::repeat_n(style.width, span))
+            }
+            crate::dom::Tag::Colgroup => widths.extend(column_widths(children)),
+            _ => {}
+        }
+    }
+    widths
+}
+
+/// Number of table columns a cell covers, from its `colspan` attribute.
+fn col_span(node: &StyledNode) -> usize {
+    match node {
+        StyledNode::Element {
+            tag: crate::dom::Tag::Td | crate::dom::Tag::Th,
+            attrs,
+            ..
+        } => attrs
+            .get("colspan")
+            .and_then(|s| s.trim().parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(1),
+        _ => 1,
+    }
+}
+
+/// Taffy track sizing functions for a parsed grid template.
+fn grid_tracks(tracks: &[style::GridTrack]) -> Vec<taffy::TrackSizingFunction> {
+    tracks
+        .iter()
+        .map(|track| match *track {
+            style::GridTrack::Px(v) => taffy::TrackSizingFunction::from_length(v),
+            style::GridTrack::Fr(v) => taffy::TrackSizingFunction::from_flex(v),
+            style::GridTrack::Auto => taffy::TrackSizingFunction::AUTO,
+        })
+        .collect()
+}
+
+/// The main axis of a row: right-to-left rows lay out their children from
+/// the right edge.
+fn row_direction(s: &ComputedStyle) -> taffy::FlexDirection {
+    match s.direction {
+        style::Direction::Ltr => taffy::FlexDirection::Row,
+        style::Direction::Rtl => taffy::FlexDirection::RowReverse,
+    }
+}
+
+/// Turn a block's Taffy style into a row of columns separated by
+/// `column-gap`.
+fn set_column_row(ts: &mut Style, s: &ComputedStyle) {
+    ts.display = taffy::Display::Flex;
+    ts.flex_direction = taffy::FlexDirection::Row;
+    ts.flex_wrap = taffy::FlexWrap::NoWrap;
+    ts.align_items = Some(taffy::AlignItems::Start);
+    ts.gap.width = LengthPercentage::Length(s.column_gap.unwrap_or(style::MULTI_COLUMN_GAP));
+}
+
 // ---------------------------------------------------------------------------
 // Image intrinsic-size helper
 // ---------------------------------------------------------------------------
 
-/// Attempt to decode a base64 data-URI image and return a cloned
-/// [`ComputedStyle`] with any `Auto` width/height replaced by concrete pixel
-/// values derived from the image's intrinsic dimensions.
+/// Look up a data-URI image in `images` (decoding it on first use) and return
+/// a cloned [`ComputedStyle`] with any `Auto` width/height replaced by
+/// concrete pixel values derived from the image's intrinsic dimensions.
 ///
-/// Returns `None` when the src is not a parseable base64 data URI, when image
-/// decoding fails, or when both dimensions are already specified (no fix needed).
+/// Returns `None` when the src is not a data URI, when image decoding fails,
+/// or when both dimensions are already specified (no fix needed).
 fn resolve_img_auto_dimensions(
     src: &str,
     style: &crate::style::ComputedStyle,
     parent_width: f32,
+    images: &ImageCache,
 ) -> Option<crate::style::ComputedStyle> {
-    use base64::{engine::general_purpose::STANDARD as BASE64_STD, Engine as _};
-
-    if !src.starts_with("data:") || !src.contains(";base64,") {
-        return None;
-    }
-    let comma = src.find(',')?;
-    let b64 = src[comma + 1..].trim();
-    let bytes = BASE64_STD.decode(b64).ok()?;
-    let img = ::image::load_from_memory(&bytes).ok()?;
-    let (px_w, px_h) = (img.width() as f32, img.height() as f32);
+    let (px_w, px_h) = images.size(src)?;
     if px_w == 0.0 || px_h == 0.0 {
         return None;
     }
@@ -557,7 +1636,7 @@ fn resolve_img_auto_dimensions(
     let known_w: Option<f32> = match style.width {
         crate::style::Dimension::Px(v) => Some(v),
         crate::style::Dimension::Percent(p) => Some(parent_width * p / 100.0),
-        crate::style::Dimension::Auto => None,
+        _ => None,
     };
     let known_h: Option<f32> = match style.height {
         crate::style::Dimension::Px(v) => Some(v),
@@ -570,7 +1649,7 @@ fn resolve_img_auto_dimensions(
         (Some(w), None) => s.height = crate::style::Dimension::Px((w / aspect).max(1.0)),
         // Height known → derive width from aspect ratio.
         (None, Some(h)) => s.width = crate::style::Dimension::Px((h * aspect).max(1.0)),
-        // Both Auto → use intrinsic pixel dimensions at 1 px = 1 pt.
+        // Both Auto → use the intrinsic size.
         (None, None) => {
             s.width = crate::style::Dimension::Px(px_w);
             s.height = crate::style::Dimension::Px(px_h);
@@ -592,9 +1671,22 @@ pub fn compute_layout(
     page_width: f32,
     page_margin: f32,
     fonts: &FontManager,
+) -> Vec<PositionedBox> {
+    let images = ImageCache::new(DEFAULT_SVG_DPI, DEFAULT_IMAGE_DPI);
+    compute_layout_with_images(styled_nodes, page_width, page_margin, fonts, &images)
+}
+
+/// Like [`compute_layout`], but decodes `<img>` sources through `images` so
+/// the renderer can reuse them.
+pub(crate) fn compute_layout_with_images(
+    styled_nodes: &[StyledNode],
+    page_width: f32,
+    page_margin: f32,
+    fonts: &FontManager,
+    images: &ImageCache,
 ) -> Vec<PositionedBox> {
     let content_width = page_width - 2.0 * page_margin;
-    let mut builder = LayoutBuilder::new(fonts, content_width);
+    let mut builder = LayoutBuilder::new(fonts, images, content_width);
 
     // Wrap all nodes in a root flex-column container
     let mut child_ids = Vec::new();
@@ -663,4 +1755,463 @@ mod tests {
         let boxes = compute_layout(&styled, 595.0, 40.0, &fonts);
         assert!(!boxes.is_empty());
     }
+
+    #[test]
+    fn inline_runs_flow_around_block_children() {
+        let html = "<div>text <b>bold</b> <p>para</p> more text</div>";
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let texts: Vec<&str> = boxes[0]
+            .children
+            .iter()
+            .map(|child| match &child.content {
+                BoxContent::Text { text, .. } => text.as_str(),
+                other => panic!("expected text, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(texts, ["text bold", "para", "more text"]);
+        let ys: Vec<f32> = boxes[0].children.iter().map(|c| c.y).collect();
+        assert!(ys[0] < ys[1] && ys[1] < ys[2]);
+    }
+
+    #[test]
+    fn whitespace_between_spans_keeps_words_apart() {
+        for html in [
+            "<p><span>a</span> <span>b</span></p>",
+            "<div><span>a</span>\n  <b>b</b></div>",
+        ] {
+            let styled = build_styled_tree(&parse_html(html), None);
+            let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+            let text = match &boxes[0].content {
+                BoxContent::Text { text, .. } => text,
+                _ => match &boxes[0].children[0].content {
+                    BoxContent::Text { text, .. } => text,
+                    other => panic!("expected text, got {other:?}"),
+                },
+            };
+            assert_eq!(text, "a b", "{html}");
+        }
+        // Flex items are laid out side by side; the space is not an item.
+        let styled = build_styled_tree(
+            &parse_html("<div class=\"flex\"><span>a</span> <span>b</span></div>"),
+            None,
+        );
+        let StyledNode::Element { children, .. } = &styled[0] else {
+            panic!("expected an element");
+        };
+        assert_eq!(children.len(), 2);
+    }
+
+    #[test]
+    fn subscript_is_a_smaller_lowered_run() {
+        let styled = build_styled_tree(&parse_html("<p>H<sub>2</sub>O</p>"), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let BoxContent::Text { lines, runs, .. } = &boxes[0].content else {
+            panic!("expected a merged text box");
+        };
+        assert_eq!(lines, &["H2O"]);
+        let texts: Vec<&str> = runs[0].iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["H", "2", "O"]);
+
+        let (h, two, o) = (&runs[0][0], &runs[0][1], &runs[0][2]);
+        assert!(two.font_size < h.font_size);
+        assert!(two.baseline_shift < 0.0);
+        assert_eq!(h.baseline_shift, 0.0);
+        assert!(two.x_offset > h.x_offset && o.x_offset > two.x_offset);
+    }
+
+    fn list_markers(html: &str) -> Vec<String> {
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        boxes[0]
+            .children
+            .iter()
+            .filter_map(|item| match &item.content {
+                BoxContent::ListItem { marker, .. } => Some(marker.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn alpha_list_markers() {
+        let html = r#"<ol class="list-[a]"><li>One</li><li>Two</li></ol>"#;
+        assert_eq!(list_markers(html), ["a. ", "b. "]);
+        assert_eq!(alpha_numeral(27), "aa");
+    }
+
+    #[test]
+    fn roman_list_markers() {
+        let html = r#"<ol style="list-style-type: lower-roman"><li>One</li><li>Two</li><li>Three</li><li>Four</li></ol>"#;
+        assert_eq!(list_markers(html), ["i. ", "ii. ", "iii. ", "iv. "]);
+        assert_eq!(
+            list_markers(r#"<ul class="list-[i]"><li>One</li></ul>"#),
+            ["i. "]
+        );
+    }
+
+    #[test]
+    fn large_markers_widen_the_list_gutter() {
+        let html = r#"<ol class="text-4xl" start="9"><li>Nine</li><li>Ten</li></ol>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let fonts = FontManager::default();
+        let boxes = compute_layout(&styled, 595.0, 40.0, &fonts);
+        let list = &boxes[0];
+        for item in &list.children {
+            let BoxContent::ListItem { marker, width, gap } = &item.content else {
+                panic!("expected a list item");
+            };
+            let measured =
+                fonts.measure_text_width(marker.trim_end(), 36.0, false, false, "Helvetica");
+            assert_eq!((*width, *gap), (measured, style::LIST_MARKER_GAP));
+            // The marker starts inside the list and ends a gap before the
+            // item's text.
+            let marker_x = item.x - width - gap;
+            assert!(marker_x >= list.x, "{marker:?} at {marker_x} < {}", list.x);
+            let text_x = item.children[0].x;
+            assert!(marker_x + width + style::LIST_MARKER_GAP <= text_x + 0.01);
+        }
+        // Small markers keep the theme's indent.
+        let styled = build_styled_tree(&parse_html("<ul><li>One</li></ul>"), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &fonts);
+        assert_eq!(boxes[0].children[0].x - boxes[0].x, 24.0);
+    }
+
+    #[test]
+    fn rtl_paragraph_aligns_right_and_mirrors_flex_rows() {
+        let html = r#"<p dir="rtl">Shalom</p>
+            <div class="flex" dir="rtl"><div class="w-16 h-4"></div><div class="w-16 h-4"></div></div>
+            <ul style="direction: rtl"><li>One</li></ul>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        assert_eq!(boxes[0].style.text_align, style::TextAlign::Right);
+        let row = &boxes[1];
+        assert!(row.children[0].x > row.children[1].x);
+        assert_eq!(row.children[0].x + row.children[0].width, row.x + row.width);
+        // The marker gutter moves to the right of the items.
+        let list = &boxes[2];
+        let item = &list.children[0];
+        assert_eq!(item.x, list.x);
+        assert_eq!(list.x + list.width - (item.x + item.width), 24.0);
+    }
+
+    #[test]
+    fn nested_lists_step_bullets_and_indent_markers() {
+        let html = "<ul><li>One<ul><li>Two<ul><li>Three</li></ul></li></ul></li></ul>";
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let mut markers = Vec::new();
+        let mut item = &boxes[0].children[0];
+        while let BoxContent::ListItem { marker, .. } = &item.content {
+            markers.push((marker.as_str(), item.x));
+            match item.children.iter().find_map(|c| c.children.first()) {
+                Some(nested) => item = nested,
+                None => break,
+            }
+        }
+        let glyphs: Vec<&str> = markers.iter().map(|(m, _)| *m).collect();
+        assert_eq!(glyphs, ["\u{2022} ", "\u{25E6} ", "\u{25AA} "]);
+        assert!(markers[1].1 > markers[0].1 && markers[2].1 > markers[1].1);
+    }
+
+    #[test]
+    fn ol_start_and_li_value_set_numbering() {
+        let html = r#"<ol start="3"><li>a</li><li>b</li></ol>"#;
+        assert_eq!(list_markers(html), ["3. ", "4. "]);
+        let html = r#"<ol><li>a</li><li value="10">b</li><li>c</li></ol>"#;
+        assert_eq!(list_markers(html), ["1. ", "10. ", "11. "]);
+        let html = r#"<ol start="2" class="list-[i]"><li>a</li></ol>"#;
+        assert_eq!(list_markers(html), ["ii. "]);
+    }
+
+    #[test]
+    fn list_style_none_suppresses_markers() {
+        assert!(list_markers(r#"<ul style="list-style: none"><li>One</li></ul>"#).is_empty());
+        assert!(list_markers(r#"<ol class="list-none"><li>One</li></ol>"#).is_empty());
+        assert_eq!(list_markers("<ul><li>One</li></ul>"), ["\u{2022} "]);
+    }
+
+    #[test]
+    fn gap_x_and_gap_y_space_the_two_axes() {
+        let item = r#"<div class="w-16 h-4"></div>"#;
+        let html = format!(
+            r#"<div class="flex flex-wrap gap-x-4 gap-y-2 w-40">{}</div>"#,
+            item.repeat(3)
+        );
+        let styled = build_styled_tree(&parse_html(&html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let items = &boxes[0].children;
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[1].x - (items[0].x + items[0].width), 16.0);
+        assert_eq!(items[1].y, items[0].y);
+        assert_eq!(items[2].y - (items[0].y + items[0].height), 8.0);
+    }
+
+    #[test]
+    fn grid_mixes_fixed_and_fraction_tracks() {
+        let cell = "<div>cell</div>";
+        for grid in [
+            r#"<div class="grid grid-cols-[200px_1fr]">"#,
+            r#"<div class="grid" style="grid-template-columns: 200px 1fr">"#,
+        ] {
+            let html = format!("{grid}{}</div>", cell.repeat(2));
+            let styled = build_styled_tree(&parse_html(&html), None);
+            let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+            let cells = &boxes[0].children;
+            assert_eq!(cells[0].width, 200.0);
+            assert_eq!(cells[1].width, 515.0 - 200.0);
+        }
+    }
+
+    #[test]
+    fn grid_rows_take_their_track_heights() {
+        let cell = "<div>cell</div>";
+        for grid in [
+            r#"<div class="grid grid-rows-[60px_30px]">"#,
+            r#"<div class="grid" style="grid-template-rows: 60px 30px">"#,
+        ] {
+            let html = format!("{grid}{}</div>", cell.repeat(2));
+            let styled = build_styled_tree(&parse_html(&html), None);
+            let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+            let cells = &boxes[0].children;
+            assert_eq!(cells[0].height, 60.0);
+            assert_eq!(cells[1].height, 30.0);
+            assert_eq!(cells[1].y - cells[0].y, 60.0);
+        }
+    }
+
+    #[test]
+    fn col_span_item_covers_two_columns() {
+        let html = r#"<div class="grid grid-cols-3 gap-x-3">
+            <div class="col-span-2">wide</div><div>a</div>
+            <div>b</div><div style="grid-column: span 2">c</div>
+        </div>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let cells = &boxes[0].children;
+        // Taffy rounds track edges to whole points.
+        let column = (515.0 - 2.0 * 12.0) / 3.0;
+        assert!((cells[0].width - (2.0 * column + 12.0)).abs() <= 1.0);
+        assert!((cells[1].width - column).abs() <= 1.0);
+        assert_eq!(cells[2].y, cells[3].y);
+        assert_eq!(cells[3].width, cells[0].width);
+    }
+
+    #[test]
+    fn aspect_video_derives_height_from_width() {
+        let html = r#"<div class="w-64 aspect-video"></div>
+            <div style="width: 200px; aspect-ratio: 4 / 3"></div>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        assert_eq!(boxes[0].width, 256.0);
+        assert!((boxes[0].height - 144.0).abs() < 0.5, "{}", boxes[0].height);
+        assert!((boxes[1].height - 150.0).abs() < 0.5, "{}", boxes[1].height);
+    }
+
+    #[test]
+    fn fit_content_span_shrinks_to_its_text() {
+        let html = r#"<span style="width: fit-content">Badge</span>
+            <div class="w-min">Two words</div>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let fonts = FontManager::default();
+        let boxes = compute_layout(&styled, 595.0, 40.0, &fonts);
+        let text_width = fonts.measure_text_width("Badge", 16.0, false, false, "");
+        assert!(
+            boxes[0].width <= text_width + 0.5,
+            "{} > {text_width}",
+            boxes[0].width
+        );
+        assert!(boxes[0].width > 0.0);
+        let word_width = fonts.measure_text_width("words", 16.0, false, false, "");
+        assert!(
+            boxes[1].width <= word_width + 0.5,
+            "{} > {word_width}",
+            boxes[1].width
+        );
+    }
+
+    #[test]
+    fn max_w_prose_limits_paragraph_width() {
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(10);
+        let html = format!(
+            r#"<p class="max-w-prose">{text}</p><p>{text}</p>
+            <div class="max-w-40 min-w-0">{text}</div>"#
+        );
+        let styled = build_styled_tree(&parse_html(&html), None);
+        let boxes = compute_layout(&styled, 1200.0, 40.0, &FontManager::default());
+        assert!(
+            boxes[0].width <= style::PROSE_MAX_WIDTH,
+            "{}",
+            boxes[0].width
+        );
+        assert!(
+            boxes[1].width > style::PROSE_MAX_WIDTH,
+            "{}",
+            boxes[1].width
+        );
+        assert_eq!(boxes[2].width, 160.0);
+    }
+
+    #[test]
+    fn soft_hyphen_entity_breaks_a_long_word() {
+        let html = r#"<div style="width: 120px">Inter&shy;national&shy;isation</div>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let BoxContent::Text { lines, .. } = &boxes[0].children[0].content else {
+            panic!("expected text");
+        };
+        assert_eq!(lines, &["International-", "isation"]);
+    }
+
+    #[test]
+    fn soft_hyphen_breaks_a_word_beside_a_styled_span() {
+        let html = r#"<div style="width: 120px">Inter&shy;national&shy;isation <b>now</b></div>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let BoxContent::Text { lines, runs, .. } = &boxes[0].children[0].content else {
+            panic!("expected text");
+        };
+        assert_eq!(lines, &["International-", "isation now"]);
+        assert_eq!(runs[1].last().unwrap().bold, Some(true));
+    }
+
+    #[test]
+    fn soft_hyphen_in_a_styled_span_takes_the_span_style() {
+        let html = r#"<div style="width: 120px">An <b>Inter&shy;national&shy;isation</b></div>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let BoxContent::Text { lines, runs, .. } = &boxes[0].children[0].content else {
+            panic!("expected text");
+        };
+        assert_eq!(lines, &["An Inter-", "national-", "isation"]);
+        let hyphen = runs[0].last().unwrap();
+        assert!(hyphen.text.ends_with('-'), "{:?}", runs[0]);
+        assert_eq!(hyphen.bold, Some(true));
+    }
+
+    #[test]
+    fn break_words_breaks_a_long_word_beside_a_styled_span() {
+        let word = "a".repeat(47);
+        let html =
+            format!(r#"<div class="break-words" style="width: 100px">{word} <b>b</b></div>"#);
+        let styled = build_styled_tree(&parse_html(&html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let BoxContent::Text { lines, runs, .. } = &boxes[0].children[0].content else {
+            panic!("expected text");
+        };
+        assert!(lines.len() > 2, "{lines:?}");
+        assert_eq!(lines.concat().replace(' ', ""), format!("{word}b"));
+        for line in runs {
+            let width: f32 = line.iter().map(|r| r.width).sum();
+            assert!(width <= 100.0, "{width}");
+        }
+        assert_eq!(runs.last().unwrap().last().unwrap().bold, Some(true));
+    }
+
+    #[test]
+    fn pre_expands_a_leading_tab_to_the_first_tab_stop() {
+        let html = "<pre>\n\tindented\nab\tc</pre>";
+        let styled = build_styled_tree(&parse_html(html), None);
+        let fonts = FontManager::default();
+        let boxes = compute_layout(&styled, 595.0, 40.0, &fonts);
+        let BoxContent::Text { lines, .. } = &boxes[0].children[0].content else {
+            panic!("expected text");
+        };
+        assert_eq!(lines, &["    indented", "ab  c"]);
+        let indent = lines[0].len() - lines[0].trim_start().len();
+        let space = fonts.measure_text_width(" ", 16.0, false, false, "Helvetica");
+        let indent_width = fonts.measure_text_width(&lines[0][..indent], 16.0, false, false, "");
+        assert_eq!(indent_width, 4.0 * space);
+    }
+
+    #[test]
+    fn pre_with_bold_span_keeps_its_lines() {
+        let html = "<pre>fn main() {\n    let <b>x</b> = 1;\n\treturn;\n}</pre>";
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let BoxContent::Text { lines, runs, .. } = &boxes[0].children[0].content else {
+            panic!("expected text");
+        };
+        assert_eq!(
+            lines,
+            &["fn main() {", "    let x = 1;", "    return;", "}"]
+        );
+        let texts: Vec<&str> = runs[1].iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["    let ", "x", " = 1;"]);
+        assert_eq!(runs[1][1].bold, Some(true));
+    }
+
+    #[test]
+    fn nested_percentage_widths_resolve_against_their_parent() {
+        let text = "word ".repeat(60);
+        let html = format!(
+            r#"<div class="mx-8 border-4"><div class="w-1/2 p-2"><div class="w-1/2">{text}</div></div></div>"#
+        );
+        let styled = build_styled_tree(&parse_html(&html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let outer = &boxes[0];
+        let middle = &outer.children[0];
+        let inner = &middle.children[0];
+        assert_eq!(outer.width, 451.0);
+        // (451 - 2 × 4 border) / 2, then (221.5 - 2 × 8 padding) / 2.
+        assert!((middle.width - 221.5).abs() <= 0.5, "{}", middle.width);
+        assert!((inner.width - 102.75).abs() <= 0.5, "{}", inner.width);
+        assert!(inner.children[0].width <= inner.width, "text overflows");
+    }
+
+    #[test]
+    fn two_column_block_flows_text_side_by_side() {
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(12);
+        let html = format!(r#"<div class="columns-2">{text}</div>"#);
+        let styled = build_styled_tree(&parse_html(&html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let columns = &boxes[0].children;
+        assert_eq!(columns.len(), 2);
+        let (left, right) = (&columns[0], &columns[1]);
+        assert_eq!(left.y, right.y, "columns should start on the same line");
+        assert!(right.x >= left.x + left.width + 16.0 - 0.5);
+        for column in columns {
+            assert!(column.width < 515.0 / 2.0);
+            assert!(matches!(
+                &column.children[0].content,
+                BoxContent::Text { lines, .. } if lines.len() > 1
+            ));
+        }
+    }
+
+    #[test]
+    fn middle_aligned_cell_centres_its_text() {
+        let html = r#"<table><tr>
+            <td style="vertical-align: middle">Centred</td>
+            <td><div style="height: 100px"></div></td>
+        </tr></table>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let boxes = compute_layout(&styled, 595.0, 40.0, &FontManager::default());
+        let cell = &boxes[0].children[0].children[0];
+        let text = &cell.children[0];
+        assert!(cell.height > 100.0);
+        let above = text.y - cell.y;
+        let below = cell.y + cell.height - (text.y + text.height);
+        assert!(
+            (above - below).abs() < 1.0,
+            "text should be centred: {above} above vs {below} below"
+        );
+    }
+
+    #[test]
+    fn tight_line_height_leaves_room_for_descenders() {
+        let fonts = FontManager::default();
+        let glyph_height = fonts.ascender_px(16.0, false, false, "Helvetica")
+            + fonts.descender_px(16.0, false, false, "Helvetica");
+        for lh in ["1.0", "0.8"] {
+            let html = format!(r#"<p style="font-size: 16px; line-height: {lh}">gyp</p>"#);
+            let styled = build_styled_tree(&parse_html(&html), None);
+            let boxes = compute_layout(&styled, 595.0, 40.0, &fonts);
+            assert!(
+                boxes[0].height >= glyph_height,
+                "line-height {lh}: box height {} < glyph height {glyph_height}",
+                boxes[0].height
+            );
+        }
+    }
 }
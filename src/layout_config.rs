@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::ForgeError;
+
 /// A complete document layout ready for rendering.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayoutConfig {
@@ -14,10 +16,78 @@ pub struct LayoutConfig {
     pub page_width_pt: f32,
     /// Height of each page in PDF points.
     pub page_height_pt: f32,
+    /// Resolution at which SVG images are rasterized, in DPI.
+    #[serde(default = "LayoutConfig::default_svg_dpi")]
+    pub svg_dpi: f32,
+    /// Resolution that maps raster image pixels to points, in DPI.
+    #[serde(default = "LayoutConfig::default_image_dpi")]
+    pub image_dpi: f32,
+    /// View a PDF viewer should open the document with (default: viewer's own).
+    #[serde(default)]
+    pub default_zoom: Option<ZoomMode>,
+    /// Extra paper around each page, in points, that content and the page
+    /// background may run into before trimming.
+    #[serde(default)]
+    pub bleed_pt: f32,
+    /// Draw trim marks at the page corners, outside the bleed.
+    #[serde(default)]
+    pub crop_marks: bool,
+    /// RGBA fill of every page, margins included; `None` leaves pages blank.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_background: Option<[f32; 4]>,
+    /// Text drawn diagonally behind the content of every page.
+    #[serde(default)]
+    pub watermark: Option<Watermark>,
+    /// Produce byte-identical PDFs for identical input: fixed dates and
+    /// sequential resource IDs instead of random ones.
+    #[serde(default)]
+    pub deterministic: bool,
+    /// Flate-compress content, font and image streams (default: `true`).
+    #[serde(default = "LayoutConfig::default_compress")]
+    pub compress: bool,
     /// Ordered list of pages.
     pub pages: Vec<PageLayout>,
 }
 
+/// Initial view declared via the PDF's `/OpenAction` on the first page.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ZoomMode {
+    /// Fit the whole page in the window.
+    FitPage,
+    /// Fit the page width to the window.
+    FitWidth,
+    /// 100 % zoom.
+    ActualSize,
+    /// A zoom percentage, e.g. `150.0`.
+    Percent(f32),
+}
+
+/// A line of text centred on each page, rotated and drawn beneath the
+/// content, e.g. "DRAFT".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watermark {
+    pub text: String,
+    /// Counter-clockwise rotation in degrees.
+    pub angle: f32,
+    pub font_size: f32,
+    pub color: [f32; 4],
+    /// Fill opacity from 0.0 (invisible) to 1.0.
+    pub opacity: f32,
+}
+
+impl Watermark {
+    /// A large light-grey watermark at 45°.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            angle: 45.0,
+            font_size: 96.0,
+            color: [0.5, 0.5, 0.5, 1.0],
+            opacity: 0.2,
+        }
+    }
+}
+
 /// One page of content.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageLayout {
@@ -36,20 +106,81 @@ pub struct LayoutBox {
 
     /// Visual styling
     pub background_color: Option<[f32; 4]>,
+    /// Two-stop `linear-gradient` painted over `background_color`.
+    #[serde(default)]
+    pub background_gradient: Option<LinearGradient>,
     pub border: Option<BorderStyle>,
 
     /// Content (mutually exclusive in practice)
     pub text: Option<TextContent>,
     pub image: Option<ImageContent>,
 
+    /// Horizontal scale applied to this box and its children at render time.
+    #[serde(default)]
+    pub scale_x: Option<ScaleX>,
+    /// Clip text, images and children to the box (CSS `overflow: hidden`).
+    /// The box's own background and border are not clipped.
+    #[serde(default)]
+    pub clip: bool,
+    /// Keep the box's space but draw none of its own background, border,
+    /// text or image (CSS `visibility: hidden`). Children draw unless they
+    /// are hidden too.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Names internal links can jump to (HTML `id`s and `<a name>`s).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anchors: Vec<String>,
+    /// `href` of a link covering the box; `#name` jumps to the box with
+    /// that anchor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// 1–3 when the box is an `<h1>`–`<h3>` heading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading_level: Option<u8>,
+
     /// Children (nested boxes)
     pub children: Vec<LayoutBox>,
 }
 
+/// A two-stop CSS `linear-gradient`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinearGradient {
+    /// Direction in CSS degrees: `0` points up, `90` points right.
+    pub angle: f32,
+    pub from: [f32; 4],
+    pub to: [f32; 4],
+}
+
+/// A horizontal scale transform anchored at `origin_x` (page-absolute).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ScaleX {
+    pub factor: f32,
+    pub origin_x: f32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BorderStyle {
     pub width: f32,
     pub color: [f32; 4],
+    /// Individual edges; when set, only these are drawn instead of a
+    /// uniform `width` × `color` outline.
+    #[serde(default)]
+    pub sides: Option<BorderSides>,
+}
+
+/// Per-side borders of a box; `None` leaves that edge undrawn.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BorderSides {
+    pub top: Option<BorderEdge>,
+    pub right: Option<BorderEdge>,
+    pub bottom: Option<BorderEdge>,
+    pub left: Option<BorderEdge>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BorderEdge {
+    pub width: f32,
+    pub color: [f32; 4],
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,8 +195,24 @@ pub struct TextContent {
     pub line_height: f32,
     pub text_align: String,
     pub underline: bool,
+    /// Strike a line through the text (`<del>`, `line-through`).
+    #[serde(default)]
+    pub line_through: bool,
+    /// Extra space added after every character, in points.
+    #[serde(default)]
+    pub letter_spacing: f32,
     /// List bullet/number prefix (e.g. "• " or "1. ")
     pub list_marker: Option<String>,
+    /// Distance from the box's left edge back to where the marker starts.
+    #[serde(default = "TextContent::default_list_marker_offset")]
+    pub list_marker_offset: f32,
+}
+
+impl TextContent {
+    /// The fixed offset list markers were drawn at before it was stored.
+    fn default_list_marker_offset() -> f32 {
+        16.0
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,13 +222,68 @@ pub struct TextLine {
     pub x_offset: f32,
     /// Y offset from the top of the text content area
     pub y_offset: f32,
+    /// Stretches of the line set in their own size or baseline (e.g.
+    /// `<sub>`); when non-empty they are drawn instead of `text`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runs: Vec<TextRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextRun {
+    pub text: String,
+    /// X offset from the start of the line.
+    pub x_offset: f32,
+    pub font_size: f32,
+    /// Baseline offset in points; positive raises the run.
+    pub baseline_shift: f32,
+    /// Weight and slant of the run; `None` keeps the box's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    /// RGBA fill of the run; `None` keeps the box's colour.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<[f32; 4]>,
+    /// Decorations of the run; `None` keeps the box's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underline: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_through: Option<bool>,
+    /// RGBA highlight painted behind the run (`<mark>`, an inline element's
+    /// background).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<[f32; 4]>,
+    /// Letter spacing of the run in points; `None` keeps the box's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub letter_spacing: Option<f32>,
+    /// Advance width of the run in points, the length of its decorations.
+    #[serde(default)]
+    pub width: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageContent {
     pub src: String,
+    /// Text drawn inside the box when the image cannot be embedded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
     pub width: f32,
     pub height: f32,
+    /// How the image is scaled into its `width` × `height` box.
+    #[serde(default)]
+    pub object_fit: ObjectFit,
+}
+
+/// CSS `object-fit` for images whose box differs from their aspect ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ObjectFit {
+    /// Stretch to the box, ignoring the aspect ratio (default).
+    #[default]
+    Fill,
+    /// Scale to fit inside the box, centred (letterboxed).
+    Contain,
+    /// Scale to cover the box, centred and clipped to it.
+    Cover,
 }
 
 impl LayoutConfig {
@@ -92,23 +294,187 @@ impl LayoutConfig {
             // A4: 210mm × 297mm = 595.28 × 841.89 points
             page_width_pt: 595.28,
             page_height_pt: 841.89,
+            svg_dpi: Self::default_svg_dpi(),
+            image_dpi: Self::default_image_dpi(),
+            bleed_pt: 0.0,
+            crop_marks: false,
+            page_background: None,
+            default_zoom: None,
+            watermark: None,
+            deterministic: false,
+            compress: Self::default_compress(),
             pages: Vec::new(),
         }
     }
 
+    fn default_compress() -> bool {
+        true
+    }
+
     fn default_title() -> String {
         "rpdf output".to_string()
     }
 
+    fn default_svg_dpi() -> f32 {
+        crate::svg::DEFAULT_SVG_DPI
+    }
+
+    fn default_image_dpi() -> f32 {
+        crate::images::DEFAULT_IMAGE_DPI
+    }
+
     /// Serialise to JSON.
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(self).unwrap_or_default()
     }
 
-    /// Deserialise from JSON.
-    pub fn from_json(json: &str) -> Result<Self, String> {
-        serde_json::from_str(json).map_err(|e| e.to_string())
+    /// Deserialise from JSON and [`validate`](Self::validate) the result.
+    pub fn from_json(json: &str) -> Result<Self, ForgeError> {
+        let config: Self =
+            serde_json::from_str(json).map_err(|e| ForgeError::InvalidLayoutJson(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the layout can be rendered: at least one page, positive
+    /// page dimensions, finite coordinates, non-negative sizes and colour
+    /// channels within `0.0..=1.0`. The error names the offending field.
+    pub fn validate(&self) -> Result<(), ForgeError> {
+        positive("page_width_pt", self.page_width_pt)?;
+        positive("page_height_pt", self.page_height_pt)?;
+        positive("svg_dpi", self.svg_dpi)?;
+        positive("image_dpi", self.image_dpi)?;
+        non_negative("bleed_pt", self.bleed_pt)?;
+        if self.pages.is_empty() {
+            return Err(ForgeError::InvalidLayout("layout has no pages".to_string()));
+        }
+        if let Some(bg) = &self.page_background {
+            color("page_background", bg)?;
+        }
+        if let Some(wm) = &self.watermark {
+            finite("watermark.angle", wm.angle)?;
+            non_negative("watermark.font_size", wm.font_size)?;
+            color("watermark.color", &wm.color)?;
+            unit("watermark.opacity", wm.opacity)?;
+        }
+        for (p, page) in self.pages.iter().enumerate() {
+            for (i, b) in page.boxes.iter().enumerate() {
+                b.validate(&format!("pages[{p}].boxes[{i}]"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The text of every heading box with the index of the page it is on,
+    /// in document order — the entries of a table of contents.
+    pub fn headings(&self) -> Vec<(String, usize)> {
+        fn collect(b: &LayoutBox, page: usize, out: &mut Vec<(String, usize)>) {
+            if b.heading_level.is_some() {
+                let text: Vec<String> = b.text_lines().map(str::to_string).collect();
+                if !text.is_empty() {
+                    out.push((text.join(" "), page));
+                    return;
+                }
+            }
+            for child in &b.children {
+                collect(child, page, out);
+            }
+        }
+        let mut out = Vec::new();
+        for (page, layout) in self.pages.iter().enumerate() {
+            for b in &layout.boxes {
+                collect(b, page, &mut out);
+            }
+        }
+        out
+    }
+
+    /// Every box that draws text, flattened in document order with the
+    /// index of the page it is on. Hidden boxes are left out.
+    pub fn text_boxes(&self) -> Vec<TextBox> {
+        fn collect(b: &LayoutBox, page: usize, out: &mut Vec<TextBox>) {
+            if let Some(text) = b.text.as_ref().filter(|_| !b.hidden) {
+                let lines: Vec<&str> = text.lines.iter().map(|l| l.text.as_str()).collect();
+                if !lines.is_empty() {
+                    out.push(TextBox {
+                        page,
+                        x: b.x,
+                        y: b.y,
+                        width: b.width,
+                        height: b.height,
+                        text: lines.join("\n"),
+                    });
+                }
+            }
+            for child in &b.children {
+                collect(child, page, out);
+            }
+        }
+        let mut out = Vec::new();
+        for (page, layout) in self.pages.iter().enumerate() {
+            for b in &layout.boxes {
+                collect(b, page, &mut out);
+            }
+        }
+        out
+    }
+}
+
+/// A box of text on a page, as returned by [`LayoutConfig::text_boxes`].
+/// Coordinates are in points from the page's top-left corner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextBox {
+    /// Zero-based page index.
+    pub page: usize,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// The box's wrapped lines, joined by newlines.
+    pub text: String,
+}
+
+fn invalid(field: &str, what: &str, value: f32) -> ForgeError {
+    ForgeError::InvalidLayout(format!("{field} {what}, got {value}"))
+}
+
+fn finite(field: &str, value: f32) -> Result<(), ForgeError> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(invalid(field, "must be finite", value))
+    }
+}
+
+fn non_negative(field: &str, value: f32) -> Result<(), ForgeError> {
+    if value.is_finite() && value >= 0.0 {
+        Ok(())
+    } else {
+        Err(invalid(field, "must be finite and non-negative", value))
+    }
+}
+
+fn positive(field: &str, value: f32) -> Result<(), ForgeError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    } else {
+        Err(invalid(field, "must be finite and positive", value))
+    }
+}
+
+fn unit(field: &str, value: f32) -> Result<(), ForgeError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(invalid(field, "must be between 0 and 1", value))
+    }
+}
+
+fn color(field: &str, rgba: &[f32; 4]) -> Result<(), ForgeError> {
+    for (channel, &value) in ["r", "g", "b", "a"].iter().zip(rgba) {
+        unit(&format!("{field}.{channel}"), value)?;
     }
+    Ok(())
 }
 
 impl LayoutBox {
@@ -119,10 +485,120 @@ impl LayoutBox {
             width,
             height,
             background_color: None,
+            background_gradient: None,
             border: None,
             text: None,
             image: None,
+            scale_x: None,
+            clip: false,
+            hidden: false,
+            anchors: Vec::new(),
+            link: None,
+            heading_level: None,
             children: Vec::new(),
         }
     }
+
+    /// Lines of text in this box and its descendants, in order.
+    pub fn text_lines(&self) -> impl Iterator<Item = &str> + '_ {
+        let own = self
+            .text
+            .iter()
+            .flat_map(|t| t.lines.iter().map(|l| l.text.as_str()));
+        let nested: Box<dyn Iterator<Item = &str>> =
+            Box::new(self.children.iter().flat_map(LayoutBox::text_lines));
+        own.chain(nested)
+    }
+
+    /// Move this box and all its descendants by `(dx, dy)`.
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        if let Some(scale) = &mut self.scale_x {
+            scale.origin_x += dx;
+        }
+        for child in &mut self.children {
+            child.translate(dx, dy);
+        }
+    }
+
+    /// Check this box and its descendants; `path` prefixes error messages.
+    fn validate(&self, path: &str) -> Result<(), ForgeError> {
+        finite(&format!("{path}.x"), self.x)?;
+        finite(&format!("{path}.y"), self.y)?;
+        non_negative(&format!("{path}.width"), self.width)?;
+        non_negative(&format!("{path}.height"), self.height)?;
+        if let Some(bg) = &self.background_color {
+            color(&format!("{path}.background_color"), bg)?;
+        }
+        if let Some(g) = &self.background_gradient {
+            finite(&format!("{path}.background_gradient.angle"), g.angle)?;
+            color(&format!("{path}.background_gradient.from"), &g.from)?;
+            color(&format!("{path}.background_gradient.to"), &g.to)?;
+        }
+        if let Some(border) = &self.border {
+            non_negative(&format!("{path}.border.width"), border.width)?;
+            color(&format!("{path}.border.color"), &border.color)?;
+            if let Some(sides) = &border.sides {
+                let edges = [
+                    ("top", &sides.top),
+                    ("right", &sides.right),
+                    ("bottom", &sides.bottom),
+                    ("left", &sides.left),
+                ];
+                for (side, edge) in edges {
+                    if let Some(edge) = edge {
+                        non_negative(&format!("{path}.border.{side}.width"), edge.width)?;
+                        color(&format!("{path}.border.{side}.color"), &edge.color)?;
+                    }
+                }
+            }
+        }
+        if let Some(text) = &self.text {
+            non_negative(&format!("{path}.text.font_size"), text.font_size)?;
+            non_negative(&format!("{path}.text.line_height"), text.line_height)?;
+            color(&format!("{path}.text.color"), &text.color)?;
+            for (l, line) in text.lines.iter().enumerate() {
+                finite(&format!("{path}.text.lines[{l}].x_offset"), line.x_offset)?;
+                finite(&format!("{path}.text.lines[{l}].y_offset"), line.y_offset)?;
+                for (r, run) in line.runs.iter().enumerate() {
+                    let run_path = format!("{path}.text.lines[{l}].runs[{r}]");
+                    finite(&format!("{run_path}.x_offset"), run.x_offset)?;
+                    non_negative(&format!("{run_path}.font_size"), run.font_size)?;
+                    finite(&format!("{run_path}.baseline_shift"), run.baseline_shift)?;
+                    if let Some(rgba) = &run.color {
+                        color(&format!("{run_path}.color"), rgba)?;
+                    }
+                }
+            }
+        }
+        if let Some(image) = &self.image {
+            non_negative(&format!("{path}.image.width"), image.width)?;
+            non_negative(&format!("{path}.image.height"), image.height)?;
+        }
+        if let Some(scale) = &self.scale_x {
+            finite(&format!("{path}.scale_x.factor"), scale.factor)?;
+            finite(&format!("{path}.scale_x.origin_x"), scale.origin_x)?;
+        }
+        for (i, child) in self.children.iter().enumerate() {
+            child.validate(&format!("{path}.children[{i}]"))?;
+        }
+        Ok(())
+    }
+
+    /// Bounding box `(left, top, right, bottom)` of the text and image boxes
+    /// in this subtree, ignoring empty containers.
+    pub fn content_bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        let own = (self.text.is_some() || self.image.is_some()).then_some((
+            self.x,
+            self.y,
+            self.x + self.width,
+            self.y + self.height,
+        ));
+        self.children
+            .iter()
+            .filter_map(LayoutBox::content_bounds)
+            .chain(own)
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+    }
 }
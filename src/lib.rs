@@ -9,18 +9,32 @@
 //! 4. **Paginate** – split into A4 pages ([`pagination`])
 //! 5. **Render** – emit PDF bytes via printpdf ([`render`])
 //!
+//! Documents can also be assembled programmatically with [`builder::Document`]
+//! instead of writing HTML.
+//!
 //! A C-compatible FFI surface is exposed via the [`ffi`] module.
 
+pub mod builder;
+pub mod cache;
 pub mod dom;
+pub mod error;
 pub mod ffi;
 pub mod fonts;
+pub mod hyphenation;
+pub mod images;
 pub mod layout;
 pub mod layout_config;
 pub mod pagination;
 pub mod pipeline;
 pub mod render;
 pub mod style;
+pub mod svg;
 pub mod templates;
 
 // Re-exports for convenience
-pub use pipeline::{generate_pdf, generate_pdf_from_html, PageOrientation};
+pub use error::ForgeError;
+pub use pipeline::{
+    generate_pdf, generate_pdf_from_html, generate_pdf_multi, generate_pdf_verbose,
+    preflight_images, PageBand, PageOrientation, PageSize,
+};
+pub use render::{ImageIssue, ImageIssueKind, Warning, WarningKind};
@@ -2,129 +2,353 @@
 //!
 //! Usage:
 //!   forge <input.html> [output.pdf] [--landscape] [--title "My Report"]
+//!         [--page-size A4|Letter|Legal] [--margin <pt>] [--layout-json <path>]
+//!         [--no-local-images]
+//!   forge <a.html> <b.html> ... | 'reports/*.html' [flags]
 //!
 //! If `output.pdf` is omitted the PDF is written next to the input file with
-//! the same stem (e.g. `report.html` → `report.pdf`).
+//! the same stem (e.g. `report.html` → `report.pdf`). When several inputs (or a
+//! glob pattern) are given, each is converted to its default output.
 
-use std::{env, fs, path::PathBuf, process};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
 
-use pdf_forge::pipeline::{generate_pdf, PageOrientation, PipelineConfig};
+use pdf_forge::pipeline::{generate_pdf, PageOrientation, PageSize, PipelineConfig};
+
+/// One input → output conversion.
+#[derive(Debug)]
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+    /// Document title (`--title`, or the input filename stem).
+    title: String,
+}
+
+/// Parsed command-line options.
+#[derive(Debug)]
+struct CliArgs {
+    jobs: Vec<Job>,
+    /// Where to dump the computed `LayoutConfig` JSON, if requested.
+    layout_json: Option<PathBuf>,
+    /// Shared settings; `title` is overridden per job.
+    config: PipelineConfig,
+}
+
+/// Result of parsing the command line.
+#[derive(Debug)]
+enum Command {
+    Run(Box<CliArgs>),
+    Help,
+}
 
 fn main() {
     env_logger::init();
 
     let args: Vec<String> = env::args().collect();
 
-    let mut input_path: Option<PathBuf> = None;
-    let mut output_path: Option<PathBuf> = None;
+    let cli = match parse_args(&args[1..]) {
+        Ok(Command::Run(cli)) => cli,
+        Ok(Command::Help) => {
+            print_usage(&args[0]);
+            process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            print_usage(&args[0]);
+            process::exit(1);
+        }
+    };
+
+    let batch = cli.jobs.len() > 1;
+    let mut failed = 0usize;
+    for job in &cli.jobs {
+        let mut config = cli.config.clone();
+        config.title = job.title.clone();
+        // Local <img> paths resolve relative to the input file.
+        config.base_dir = job.input.parent().map(Path::to_path_buf);
+        match convert(job, &config, cli.layout_json.as_deref()) {
+            Ok(summary) => eprintln!("{summary}"),
+            Err(e) => {
+                failed += 1;
+                if batch {
+                    eprintln!("Failed '{}': {e}", job.input.display());
+                } else {
+                    eprintln!("{e}");
+                }
+            }
+        }
+    }
+
+    if batch {
+        eprintln!(
+            "Converted {} of {} files",
+            cli.jobs.len() - failed,
+            cli.jobs.len()
+        );
+    }
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+/// Run a single conversion, returning a one-line summary on success.
+fn convert(
+    job: &Job,
+    config: &PipelineConfig,
+    layout_json: Option<&Path>,
+) -> Result<String, String> {
+    let html = fs::read_to_string(&job.input)
+        .map_err(|e| format!("Error reading '{}': {e}", job.input.display()))?;
+
+    let (bytes, layout) =
+        generate_pdf(&html, config).map_err(|e| format!("Error generating PDF: {e}"))?;
+    write_output(&job.output, &bytes)?;
+    if let Some(path) = layout_json {
+        write_output(path, layout.to_json().as_bytes())?;
+        eprintln!("Wrote layout '{}'", path.display());
+    }
+
+    let pages = layout.pages.len();
+    Ok(format!(
+        "Wrote '{}' ({} bytes, {} page{})",
+        job.output.display(),
+        bytes.len(),
+        pages,
+        if pages == 1 { "" } else { "s" }
+    ))
+}
+
+/// Write `bytes` to `path`, creating the parent directory if necessary.
+fn write_output(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Error creating output directory: {e}"))?;
+        }
+    }
+    fs::write(path, bytes).map_err(|e| format!("Error writing '{}': {e}", path.display()))
+}
+
+/// Parse the arguments that follow the program name.
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut positional: Vec<String> = Vec::new();
     let mut landscape = false;
     let mut title: Option<String> = None;
-    let mut positional = 0usize;
+    let mut page_size = PageSize::default();
+    let mut margin: Option<f32> = None;
+    let mut layout_json: Option<PathBuf> = None;
+    let mut local_images = true;
 
-    let mut iter = args.iter().skip(1).peekable();
+    let mut iter = args.iter();
     while let Some(arg) = iter.next() {
         match arg.as_str() {
             "--landscape" | "-l" => landscape = true,
             "--title" | "-t" => match iter.next() {
                 Some(v) => title = Some(v.clone()),
-                None => {
-                    title = Some("Template".to_string())
-                }
+                None => title = Some("Template".to_string()),
             },
-            "--help" | "-h" => {
-                print_usage(&args[0]);
-                process::exit(0);
+            "--page-size" | "-s" => {
+                let v = iter
+                    .next()
+                    .ok_or_else(|| format!("{arg} requires a value (A4, Letter or Legal)"))?;
+                page_size = PageSize::from_name(v).ok_or_else(|| {
+                    format!("Unknown page size '{v}' (expected A4, Letter or Legal)")
+                })?;
             }
-            other if other.starts_with('-') => {
-                eprintln!("Unknown flag: {other}");
-                print_usage(&args[0]);
-                process::exit(1);
+            "--margin" | "-m" => {
+                let v = iter
+                    .next()
+                    .ok_or_else(|| format!("{arg} requires a value in points"))?;
+                margin = Some(parse_points(arg, v)?);
             }
-            path => {
-                if positional == 0 {
-                    input_path = Some(PathBuf::from(path));
-                } else if positional == 1 {
-                    output_path = Some(PathBuf::from(path));
-                } else {
-                    eprintln!("Unexpected argument: {path}");
-                    print_usage(&args[0]);
-                    process::exit(1);
-                }
-                positional += 1;
+            "--layout-json" | "-j" => {
+                let v = iter
+                    .next()
+                    .ok_or_else(|| format!("{arg} requires a file path"))?;
+                layout_json = Some(PathBuf::from(v));
+            }
+            "--no-local-images" => local_images = false,
+            "--help" | "-h" => return Ok(Command::Help),
+            other if other.starts_with('-') => {
+                return Err(format!("Unknown flag: {other}"));
             }
+            path => positional.push(path.to_string()),
         }
     }
 
-    let input = match input_path {
-        Some(p) => p,
-        None => {
-            eprintln!("Error: no input file specified.");
-            print_usage(&args[0]);
-            process::exit(1);
-        }
-    };
-
-    // Default output: same directory + same stem as input, but with .pdf
-    let output = output_path.unwrap_or_else(|| {
-        let mut o = input.clone();
-        o.set_extension("pdf");
-        o
-    });
+    if positional.is_empty() {
+        return Err("no input file specified.".to_string());
+    }
 
-    let html = match fs::read_to_string(&input) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error reading '{}': {e}", input.display());
-            process::exit(1);
+    // `forge in.html out.pdf` keeps its single-file meaning; any other list of
+    // positionals is a batch of inputs (glob patterns expanded here), in which
+    // a `.pdf` is almost certainly a misplaced output path.
+    let explicit_output = positional.len() == 2 && has_pdf_extension(&positional[1]);
+    if positional.len() > 1 && !explicit_output {
+        if let Some(pdf) = positional.iter().find(|p| has_pdf_extension(p)) {
+            return Err(format!(
+                "'{pdf}' looks like an output file, but an output path can only \
+                 follow a single input; with several inputs each PDF is written \
+                 next to its input"
+            ));
+        }
+    }
+    let (inputs, output_path) = if explicit_output {
+        (
+            vec![PathBuf::from(&positional[0])],
+            Some(PathBuf::from(&positional[1])),
+        )
+    } else {
+        let mut inputs = Vec::new();
+        for p in &positional {
+            if is_glob(p) {
+                inputs.extend(expand_glob(p)?);
+            } else {
+                inputs.push(PathBuf::from(p));
+            }
         }
+        (inputs, None)
     };
 
-    // Default title: stem of the input filename.
-    let default_title = input
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("rpdf output")
-        .to_string();
+    if inputs.len() > 1 && layout_json.is_some() {
+        return Err("--layout-json can only be used with a single input".to_string());
+    }
+
+    let jobs = inputs
+        .into_iter()
+        .map(|input| {
+            // Default output: same directory + same stem as input, but with .pdf
+            let output = output_path.clone().unwrap_or_else(|| {
+                let mut o = input.clone();
+                o.set_extension("pdf");
+                o
+            });
+            // Default title: stem of the input filename.
+            let title = title.clone().unwrap_or_else(|| {
+                input
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("rpdf output")
+                    .to_string()
+            });
+            Job {
+                input,
+                output,
+                title,
+            }
+        })
+        .collect();
 
-    let config = PipelineConfig {
-        title: title.unwrap_or(default_title),
+    let mut config = PipelineConfig {
         orientation: if landscape {
             PageOrientation::Landscape
         } else {
             PageOrientation::Portrait
         },
+        allow_local_images: local_images,
         ..PipelineConfig::default()
+    }
+    .with_page_size(page_size);
+    if let Some(m) = margin {
+        config.page_margin = m;
+    }
+
+    Ok(Command::Run(Box::new(CliArgs {
+        jobs,
+        layout_json,
+        config,
+    })))
+}
+
+fn has_pdf_extension(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Expand a glob whose wildcards (`*`, `?`) are confined to the final path
+/// component, e.g. `reports/*.html`. Matches are returned sorted.
+fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let path = Path::new(pattern);
+    let file_pattern = path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .ok_or_else(|| format!("Invalid glob pattern: {pattern}"))?;
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
     };
+    if is_glob(&dir.to_string_lossy()) {
+        return Err(format!(
+            "Wildcards are only supported in the file name: {pattern}"
+        ));
+    }
 
-    match generate_pdf(&html, &config) {
-        Ok((bytes, layout)) => {
-            // Create output directory if necessary.
-            if let Some(parent) = output.parent() {
-                if !parent.as_os_str().is_empty() {
-                    if let Err(e) = fs::create_dir_all(parent) {
-                        eprintln!("Error creating output directory: {e}");
-                        process::exit(1);
-                    }
-                }
-            }
-            if let Err(e) = fs::write(&output, &bytes) {
-                eprintln!("Error writing '{}': {e}", output.display());
-                process::exit(1);
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Error reading '{}': {e}", dir.display()))?;
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .filter(|e| {
+            e.file_name()
+                .to_str()
+                .is_some_and(|name| wildcard_match(file_pattern, name))
+        })
+        .map(|e| {
+            if path.parent().is_some_and(|p| !p.as_os_str().is_empty()) {
+                dir.join(e.file_name())
+            } else {
+                PathBuf::from(e.file_name())
             }
-            let pages = layout.pages.len();
-            eprintln!(
-                "Wrote '{}' ({} bytes, {} page{})",
-                output.display(),
-                bytes.len(),
-                pages,
-                if pages == 1 { "" } else { "s" }
-            );
-        }
-        Err(e) => {
-            eprintln!("Error generating PDF: {e}");
-            process::exit(1);
+        })
+        .collect();
+    if matches.is_empty() {
+        return Err(format!("No files match '{pattern}'"));
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+/// Match `name` against a pattern where `*` is any run and `?` any one char.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let (mut star, mut star_ni) = (None, 0);
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some(pi);
+            star_ni = ni;
+            pi += 1;
+        } else if let Some(sp) = star {
+            pi = sp + 1;
+            star_ni += 1;
+            ni = star_ni;
+        } else {
+            return false;
         }
     }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Parse a non-negative length in points for `flag`.
+fn parse_points(flag: &str, value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(v) if v.is_finite() && v >= 0.0 => Ok(v),
+        _ => Err(format!(
+            "{flag} expects a non-negative number of points, got '{value}'"
+        )),
+    }
 }
 
 fn print_usage(prog: &str) {
@@ -132,13 +356,112 @@ fn print_usage(prog: &str) {
     eprintln!();
     eprintln!("Usage:");
     eprintln!("  {prog} <input.html> [output.pdf] [--landscape] [--title \"My Report\"]");
+    eprintln!("        [--page-size A4|Letter|Legal] [--margin <pt>] [--layout-json <path>]");
+    eprintln!("        [--no-local-images]");
+    eprintln!("  {prog} <a.html> <b.html> ... | 'dir/*.html' [flags]");
     eprintln!();
     eprintln!("Arguments:");
-    eprintln!("  <input.html>   HTML file to convert (images must be base64 data URIs; others are skipped)");
+    eprintln!("  <input.html>   HTML file to convert (images: data URIs and local files; others are skipped)");
     eprintln!("  [output.pdf]   Output path  (default: same stem as input with .pdf)");
+    eprintln!("  Several inputs or a glob convert each file to its default output.");
     eprintln!();
     eprintln!("Flags:");
     eprintln!("  --title, -t    Document title in PDF metadata (default: input filename stem)");
     eprintln!("  --landscape    Use landscape page orientation (A4 841×595 pt)");
+    eprintln!("  --page-size    Paper size: A4 (default), Letter or Legal");
+    eprintln!("  --margin, -m   Page margin in points on all sides (default: 40)");
+    eprintln!("  --layout-json  Also write the computed layout (JSON) to this path");
+    eprintln!("  --no-local-images  Skip <img> file paths instead of embedding them");
     eprintln!("  --help         Print this message");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> CliArgs {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        match parse_args(&args) {
+            Ok(Command::Run(cli)) => *cli,
+            other => panic!("Expected Run, got {other:?}"),
+        }
+    }
+
+    fn parse_err(args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_args(&args).unwrap_err()
+    }
+
+    #[test]
+    fn defaults_follow_input_path() {
+        let cli = run(&["report.html"]);
+        assert_eq!(cli.jobs.len(), 1);
+        assert_eq!(cli.jobs[0].output, PathBuf::from("report.pdf"));
+        assert_eq!(cli.jobs[0].title, "report");
+        assert_eq!(cli.config.page_width, 595.28);
+        assert_eq!(cli.config.page_margin, 40.0);
+        assert!(cli.config.allow_local_images);
+        assert!(
+            !run(&["report.html", "--no-local-images"])
+                .config
+                .allow_local_images
+        );
+    }
+
+    #[test]
+    fn page_size_and_margin_compose_with_landscape() {
+        let cli = run(&["in.html", "--page-size", "letter", "--margin", "18", "-l"]);
+        assert_eq!(cli.config.page_width, 612.0);
+        assert_eq!(cli.config.page_height, 792.0);
+        assert_eq!(cli.config.page_margin, 18.0);
+        assert_eq!(cli.config.orientation, PageOrientation::Landscape);
+        assert_eq!(cli.config.effective_width(), 792.0);
+    }
+
+    #[test]
+    fn layout_json_flag_stores_path() {
+        let cli = run(&["in.html", "out.pdf", "--layout-json", "debug/layout.json"]);
+        assert_eq!(cli.jobs[0].output, PathBuf::from("out.pdf"));
+        assert_eq!(cli.layout_json, Some(PathBuf::from("debug/layout.json")));
+        assert!(run(&["in.html"]).layout_json.is_none());
+        assert!(parse_err(&["in.html", "--layout-json"]).contains("requires a file path"));
+    }
+
+    #[test]
+    fn multiple_inputs_become_separate_jobs() {
+        let cli = run(&["a.html", "docs/b.html", "--title", "Shared"]);
+        let outputs: Vec<_> = cli.jobs.iter().map(|j| j.output.clone()).collect();
+        assert_eq!(
+            outputs,
+            vec![PathBuf::from("a.pdf"), PathBuf::from("docs/b.pdf")]
+        );
+        assert!(cli.jobs.iter().all(|j| j.title == "Shared"));
+        assert!(parse_err(&["a.html", "b.html", "-j", "l.json"]).contains("single input"));
+    }
+
+    #[test]
+    fn pdf_after_several_inputs_is_rejected() {
+        let err = parse_err(&["a.html", "b.html", "out.pdf"]);
+        assert!(
+            err.contains("'out.pdf'") && err.contains("single input"),
+            "{err}"
+        );
+        assert!(parse_err(&["out.pdf", "a.html", "b.html"]).contains("out.pdf"));
+    }
+
+    #[test]
+    fn wildcard_matching() {
+        assert!(wildcard_match("*.html", "report.html"));
+        assert!(wildcard_match("r?port*", "report.html"));
+        assert!(!wildcard_match("*.html", "report.pdf"));
+        assert!(!wildcard_match("a*b", "acbd"));
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(parse_err(&["in.html", "--margin", "wide"]).contains("--margin"));
+        assert!(parse_err(&["in.html", "--margin", "-5"]).contains("non-negative"));
+        assert!(parse_err(&["in.html", "--page-size", "A3"]).contains("A3"));
+        assert!(parse_err(&["in.html", "--margin"]).contains("requires a value"));
+    }
+}
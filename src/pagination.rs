@@ -5,21 +5,39 @@
 //! - Page-break-before / page-break-after hints
 //! - Table row splitting across pages
 //! - Orphan avoidance for text blocks
+//! - Keep-with-next for headings
 
 use crate::fonts::FontManager;
-use crate::layout::{BoxContent, PositionedBox};
+use crate::layout::{line_heights, BoxContent, PositionedBox};
 use crate::layout_config::*;
 use crate::style;
 
 /// Default page margins in points.
 pub const PAGE_MARGIN_PT: f32 = 40.0;
 
+/// Default [`PaginationOptions::keep_with_next_pt`].
+pub const KEEP_WITH_NEXT_PT: f32 = 48.0;
+
+/// Tunables for [`paginate_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaginationOptions {
+    /// A keep-with-next box (e.g. a heading) is moved to the next page when
+    /// less than this much space, in points, would remain below it — or when
+    /// the block after it would not start on the same page.
+    pub keep_with_next_pt: f32,
+}
+
+impl Default for PaginationOptions {
+    fn default() -> Self {
+        Self {
+            keep_with_next_pt: KEEP_WITH_NEXT_PT,
+        }
+    }
+}
+
 /// Recursively expand any pure-container box whose height exceeds a single
 /// page so its children can be split across pages individually.
-fn flatten_for_pagination<'a>(
-    boxes: &'a [PositionedBox],
-    content_height: f32,
-) -> Vec<&'a PositionedBox> {
+fn flatten_for_pagination(boxes: &[PositionedBox], content_height: f32) -> Vec<&PositionedBox> {
     let mut result = Vec::new();
     for pbox in boxes {
         if pbox.height > content_height
@@ -41,11 +59,39 @@ pub fn paginate(
     page_height: f32,
     page_margin: f32,
     fonts: &FontManager,
+) -> LayoutConfig {
+    paginate_with_options(
+        boxes,
+        page_width,
+        page_height,
+        page_margin,
+        &PaginationOptions::default(),
+        fonts,
+    )
+}
+
+/// Like [`paginate`], with explicit [`PaginationOptions`].
+pub fn paginate_with_options(
+    boxes: &[PositionedBox],
+    page_width: f32,
+    page_height: f32,
+    page_margin: f32,
+    options: &PaginationOptions,
+    fonts: &FontManager,
 ) -> LayoutConfig {
     let mut config = LayoutConfig {
         title: "rpdf output".to_string(),
         page_width_pt: page_width,
         page_height_pt: page_height,
+        svg_dpi: crate::svg::DEFAULT_SVG_DPI,
+        image_dpi: crate::images::DEFAULT_IMAGE_DPI,
+        bleed_pt: 0.0,
+        crop_marks: false,
+        page_background: None,
+        default_zoom: None,
+        watermark: None,
+        deterministic: false,
+        compress: true,
         pages: Vec::new(),
     };
 
@@ -64,9 +110,13 @@ pub fn paginate(
     // so `pbox.y - page_start_doc_y` gives the y-on-page for any box.
     let mut page_start_doc_y = 0.0f32;
 
-    for pbox in &flat {
-        // Page break before
-        if pbox.page_break_before && !current_page.boxes.is_empty() {
+    for (i, pbox) in flat.iter().enumerate() {
+        // Page break before – explicit, or to keep a heading with its block.
+        let strand = pbox.keep_with_next
+            && flat
+                .get(i + 1)
+                .is_some_and(|next| strands(pbox, next, page_start_doc_y, content_height, options));
+        if (pbox.page_break_before || strand) && !current_page.boxes.is_empty() {
             config.pages.push(current_page);
             current_page = PageLayout {
                 page_index: config.pages.len(),
@@ -128,6 +178,37 @@ pub fn paginate(
     config
 }
 
+/// Convert positioned boxes into page-absolute layout boxes without
+/// paginating, offsetting every box vertically by `top`.
+pub fn place_boxes(boxes: &[PositionedBox], top: f32, fonts: &FontManager) -> Vec<LayoutBox> {
+    boxes
+        .iter()
+        .map(|pbox| build_layout_box(pbox, pbox.x, top + pbox.y, fonts))
+        .collect()
+}
+
+/// Whether `pbox`, placed on the current page, would end up at the bottom
+/// of it without the start of `next`.
+fn strands(
+    pbox: &PositionedBox,
+    next: &PositionedBox,
+    page_start_doc_y: f32,
+    content_height: f32,
+    options: &PaginationOptions,
+) -> bool {
+    let bottom = pbox.y + pbox.height - page_start_doc_y;
+    if bottom > content_height {
+        return false; // overflows anyway and moves on its own
+    }
+    // A splittable table only needs its first row to fit.
+    let lead = match next.children.first() {
+        Some(row) if is_table_like(next) && !next.page_break_inside_avoid => row,
+        _ => next,
+    };
+    let next_bottom = lead.y + lead.height - page_start_doc_y;
+    content_height - bottom < options.keep_with_next_pt || next_bottom > content_height
+}
+
 fn is_table_like(pbox: &PositionedBox) -> bool {
     pbox.style.display == style::Display::Grid && !pbox.children.is_empty()
 }
@@ -154,7 +235,14 @@ fn split_table_box(
             *page_start_doc_y = child.y;
         }
         let y = (child.y - *page_start_doc_y).max(0.0);
-        let row_box = positioned_to_layout_box(child, page_margin, y, fonts);
+        let mut row_box = positioned_to_layout_box(child, page_margin, y, fonts);
+        // Rows lifted out of a scaled table keep the table's transform.
+        if let Some(factor) = pbox.scale_x {
+            row_box.scale_x = Some(ScaleX {
+                factor,
+                origin_x: pbox.x,
+            });
+        }
         current_page.boxes.push(row_box);
     }
 }
@@ -189,33 +277,50 @@ fn build_layout_box(
 ) -> LayoutBox {
     let mut lb = LayoutBox::new(abs_x, abs_y, pbox.width, pbox.height);
 
+    if let Some(factor) = pbox.scale_x {
+        lb.scale_x = Some(ScaleX {
+            factor,
+            origin_x: abs_x,
+        });
+    }
+
     // Background
     if !pbox.style.background_color.is_transparent() {
         let c = &pbox.style.background_color;
         lb.background_color = Some([c.r, c.g, c.b, c.a]);
     }
+    lb.background_gradient = pbox.style.background_gradient;
 
     // Border
-    if pbox.style.border_width > 0.5 {
-        let c = &pbox.style.border_color;
-        lb.border = Some(BorderStyle {
-            width: pbox.style.border_width,
-            color: [c.r, c.g, c.b, c.a],
-        });
-    }
+    lb.border = border_style(&pbox.style);
+    lb.clip = pbox.style.overflow == style::Overflow::Hidden;
+    lb.hidden = pbox.style.visibility == style::Visibility::Hidden;
+    lb.anchors = pbox.anchors.clone();
+    lb.link = pbox.link.clone();
+    lb.heading_level = pbox.heading_level;
 
     // Content
     match &pbox.content {
-        BoxContent::Text { lines, .. } => {
+        BoxContent::Text { lines, runs, .. } => {
             let c = &pbox.style.color;
             let line_height = fonts.line_height_px(pbox.style.font_size, pbox.style.line_height);
+            // A line grown for a larger run keeps its extra height above the
+            // baseline, so the big glyphs clear the line before.
+            let heights = line_heights(runs, lines.len(), &pbox.style, fonts);
+            let mut top = 0.0;
             let text_lines: Vec<TextLine> = lines
                 .iter()
+                .zip(heights)
                 .enumerate()
-                .map(|(i, line)| TextLine {
-                    text: line.clone(),
-                    x_offset: 0.0,
-                    y_offset: i as f32 * line_height,
+                .map(|(i, (line, height))| {
+                    let y_offset = top + height - line_height;
+                    top += height;
+                    TextLine {
+                        text: line.clone(),
+                        x_offset: 0.0,
+                        y_offset,
+                        runs: runs.get(i).cloned().unwrap_or_default(),
+                    }
                 })
                 .collect();
 
@@ -233,22 +338,32 @@ fn build_layout_box(
                     style::TextAlign::Right => "right".to_string(),
                 },
                 underline: pbox.style.text_decoration == style::TextDecoration::Underline,
+                line_through: pbox.style.text_decoration == style::TextDecoration::LineThrough,
+                letter_spacing: pbox.style.letter_spacing * pbox.style.font_size,
                 list_marker: None,
+                list_marker_offset: 0.0,
             });
         }
-        BoxContent::Image { src } => {
+        BoxContent::Image { src, alt } => {
             lb.image = Some(ImageContent {
                 src: src.clone(),
+                alt: alt.clone(),
                 width: pbox.width,
                 height: pbox.height,
+                object_fit: pbox.style.object_fit,
             });
         }
-        BoxContent::ListItem { marker } => {
+        BoxContent::ListItem { marker, width, gap } => {
             let c = &pbox.style.color;
             let line_height = fonts.line_height_px(pbox.style.font_size, pbox.style.line_height);
             // `lines` is empty – the bullet / number is rendered via
-            // `list_marker` (drawn 16 pt to the left of the li box), while
-            // the li's actual text content comes from its child boxes.
+            // `list_marker` (drawn in the gutter before the li box, or after
+            // it for right-to-left lists), while the li's actual text content
+            // comes from its child boxes.
+            let list_marker_offset = match pbox.style.direction {
+                style::Direction::Ltr => width + gap,
+                style::Direction::Rtl => -(pbox.width + gap),
+            };
             lb.text = Some(TextContent {
                 lines: vec![],
                 font_family: pbox.style.font_family.clone(),
@@ -259,7 +374,10 @@ fn build_layout_box(
                 line_height,
                 text_align: "left".to_string(),
                 underline: false,
+                line_through: false,
+                letter_spacing: 0.0,
                 list_marker: Some(marker.clone()),
+                list_marker_offset,
             });
         }
         BoxContent::None => {}
@@ -278,6 +396,42 @@ fn build_layout_box(
     lb
 }
 
+/// A uniform outline when all four edges agree, otherwise the individual
+/// edges. Hairlines under half a point are not drawn.
+fn border_style(style: &style::ComputedStyle) -> Option<BorderStyle> {
+    let edge = |width: f32, c: &style::Color| BorderEdge {
+        width,
+        color: [c.r, c.g, c.b, c.a],
+    };
+    let edges = [
+        edge(style.border_top_width, &style.border_top_color),
+        edge(style.border_right_width, &style.border_right_color),
+        edge(style.border_bottom_width, &style.border_bottom_color),
+        edge(style.border_left_width, &style.border_left_color),
+    ];
+    let visible = |e: BorderEdge| (e.width > 0.5).then_some(e);
+    let widest = edges
+        .into_iter()
+        .max_by(|a, b| a.width.total_cmp(&b.width))?;
+    if widest.width <= 0.5 {
+        return None;
+    }
+
+    let uniform = edges
+        .iter()
+        .all(|e| e.width == edges[0].width && e.color == edges[0].color);
+    Some(BorderStyle {
+        width: widest.width,
+        color: widest.color,
+        sides: (!uniform).then_some(BorderSides {
+            top: visible(edges[0]),
+            right: visible(edges[1]),
+            bottom: visible(edges[2]),
+            left: visible(edges[3]),
+        }),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,6 +450,79 @@ mod tests {
         assert_eq!(config.pages.len(), 1);
     }
 
+    #[test]
+    fn percentage_line_height_multiplies_the_font_size() {
+        let html = r#"<p style="font-size: 16px; line-height: 200%">Tall lines</p>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let fonts = FontManager::default();
+        let boxes = compute_layout(&styled, 595.0, PAGE_MARGIN_PT, &fonts);
+        let config = paginate(&boxes, 595.0, 842.0, PAGE_MARGIN_PT, &fonts);
+        let text = config.pages[0].boxes[0].text.as_ref().unwrap();
+        assert_eq!(text.line_height, 32.0);
+    }
+
+    #[test]
+    fn larger_run_grows_its_line() {
+        let html = r#"<p>small <span style="font-size: 40px">Big</span></p><p>next</p>"#;
+        let styled = build_styled_tree(&parse_html(html), None);
+        let fonts = FontManager::default();
+        let boxes = compute_layout(&styled, 595.0, PAGE_MARGIN_PT, &fonts);
+        let config = paginate(&boxes, 595.0, 842.0, PAGE_MARGIN_PT, &fonts);
+        let para = &config.pages[0].boxes[0];
+        let text = para.text.as_ref().unwrap();
+        let (big, base) = (
+            fonts.line_height_px(40.0, text.line_height / 16.0),
+            text.line_height,
+        );
+        assert!(para.height >= big - 0.01, "{} < {big}", para.height);
+        assert!((text.lines[0].y_offset - (big - base)).abs() < 0.01);
+        assert!(config.pages[0].boxes[1].y >= para.y + big - 0.01);
+    }
+
+    fn page_of(config: &LayoutConfig, needle: &str) -> usize {
+        config
+            .pages
+            .iter()
+            .position(|page| {
+                page.boxes.iter().any(|b| {
+                    b.text
+                        .as_ref()
+                        .is_some_and(|t| t.lines.iter().any(|l| l.text == needle))
+                })
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn heading_moves_to_next_page_with_its_paragraph() {
+        let fonts = FontManager::default();
+        let layout = |spacer: f32, options: &PaginationOptions| {
+            let html =
+                format!(r#"<div style="height: {spacer}px"></div><h2>Heading</h2><p>Body</p>"#);
+            let styled = build_styled_tree(&parse_html(&html), None);
+            let boxes = compute_layout(&styled, 595.0, PAGE_MARGIN_PT, &fonts);
+            paginate_with_options(&boxes, 595.0, 842.0, PAGE_MARGIN_PT, options, &fonts)
+        };
+
+        // The heading would fit as the last line of page 1, but its
+        // paragraph would not.
+        let config = layout(700.0, &PaginationOptions::default());
+        assert_eq!(page_of(&config, "Heading"), 1);
+        assert_eq!(page_of(&config, "Body"), 1);
+        assert!(
+            config.pages[1].boxes[0].text.is_some(),
+            "heading starts page 2"
+        );
+
+        // With room for both, the threshold decides.
+        let config = layout(640.0, &PaginationOptions::default());
+        assert_eq!(page_of(&config, "Heading"), 0);
+        let strict = PaginationOptions {
+            keep_with_next_pt: 200.0,
+        };
+        assert_eq!(page_of(&layout(640.0, &strict), "Heading"), 1);
+    }
+
     #[test]
     fn multiple_pages() {
         // Generate enough content to fill multiple pages
@@ -1,13 +1,27 @@
 //! Pipeline – ties together parsing, styling, layout, pagination, and
 //! rendering into a single function call.
 
-use crate::dom::{body_children, parse_html};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::dom::{body_children, parse_html, DomNode, Tag};
+use crate::error::ForgeError;
 use crate::fonts::FontManager;
-use crate::layout::compute_layout;
-use crate::layout_config::LayoutConfig;
-use crate::pagination::{paginate, PAGE_MARGIN_PT};
-use crate::render::render_pdf;
-use crate::style::build_styled_tree;
+use crate::images::{ImageCache, DEFAULT_IMAGE_DPI};
+use crate::layout::compute_layout_with_images;
+use crate::layout_config::{LayoutConfig, Watermark, ZoomMode};
+use crate::pagination::{
+    paginate_with_options, place_boxes, PaginationOptions, KEEP_WITH_NEXT_PT, PAGE_MARGIN_PT,
+};
+use crate::render::{
+    is_local_image_src, is_remote_image_src, local_image_to_data_uri, preflight_layout_images,
+    remote_image_to_data_uri, render_pdf_with_images, ImageIssue, Warning,
+};
+use crate::style::{build_styled_tree_with_theme, Color, ComputedStyle, StyledNode, Theme};
+use crate::svg::DEFAULT_SVG_DPI;
+use crate::templates::render_template;
 
 /// Page orientation for the generated PDF.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -19,6 +33,58 @@ pub enum PageOrientation {
     Landscape,
 }
 
+/// Standard paper sizes, in portrait orientation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageSize {
+    /// ISO A4: 210 × 297 mm (default).
+    #[default]
+    A4,
+    /// US Letter: 8.5 × 11 in.
+    Letter,
+    /// US Legal: 8.5 × 14 in.
+    Legal,
+}
+
+impl PageSize {
+    /// Portrait `(width, height)` in points.
+    pub fn dimensions_pt(self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (595.28, 841.89),
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::Legal => (612.0, 1008.0),
+        }
+    }
+
+    /// Look up a page size by name (case-insensitive), e.g. `"letter"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "a4" => Some(PageSize::A4),
+            "letter" => Some(PageSize::Letter),
+            "legal" => Some(PageSize::Legal),
+            _ => None,
+        }
+    }
+}
+
+/// HTML snippets for the left, centre and right of a header or footer band.
+///
+/// Each snippet is laid out on its own and aligned to its edge of the content
+/// area, vertically centred in the page margin. `{{ page }}` and
+/// `{{ pages }}` are substituted with the page number and page count, both
+/// counted from the first page after the cover.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageBand {
+    pub left: Option<String>,
+    pub center: Option<String>,
+    pub right: Option<String>,
+}
+
+impl PageBand {
+    fn is_empty(&self) -> bool {
+        self.left.is_none() && self.center.is_none() && self.right.is_none()
+    }
+}
+
 /// Configuration for the PDF generation pipeline.
 #[derive(Debug, Clone)]
 pub struct PipelineConfig {
@@ -32,6 +98,74 @@ pub struct PipelineConfig {
     pub page_margin: f32,
     /// Page orientation; swaps effective width/height when `Landscape`.
     pub orientation: PageOrientation,
+    /// Resolution at which SVG images are rasterized (default: 144 DPI).
+    pub svg_dpi: f32,
+    /// Resolution that maps raster image pixels to points: an image `n` px
+    /// wide is `n * 72 / image_dpi` pt wide at its intrinsic size (default:
+    /// 96 DPI, matching CSS px).
+    pub image_dpi: f32,
+    /// Initial zoom a viewer should open the PDF with (default: `None`,
+    /// leaving it to the viewer).
+    pub default_zoom: Option<ZoomMode>,
+    /// Tag defaults such as list and blockquote indentation, block spacing
+    /// and table striping.
+    pub theme: Theme,
+    /// Font size in points that unstyled text inherits; headings scale with
+    /// it (default: 16).
+    pub base_font_size: f32,
+    /// Line height, as a multiple of the font size, that unstyled text
+    /// inherits (default: 1.4).
+    pub base_line_height: f32,
+    /// Spaces between tab stops in preformatted text, unless overridden by
+    /// CSS `tab-size` (default: 4).
+    pub tab_size: u32,
+    /// Measure and draw `ff`, `fi`, `fl`, `ffi` and `ffl` with the ligature
+    /// glyphs of loaded fonts that have them (default: `false`, so layout
+    /// does not change with the fonts' ligature tables).
+    pub ligatures: bool,
+    /// ISO 639-1 code of the language to hyphenate text in, e.g. `"en"`:
+    /// words may then break at syllables as well as at spaces and `&shy;`
+    /// (default: `None`). Requires the `hyphenation` cargo feature; without
+    /// it the setting is ignored with a warning.
+    pub hyphenate_lang: Option<String>,
+    /// Headings (and other keep-with-next blocks) with less than this much
+    /// space in points left below them move to the next page (default: 48).
+    pub keep_with_next_pt: f32,
+    /// Allow `<img>` sources that are file paths or `file://` URIs; they are
+    /// read from disk and embedded (default: `false`, data URIs only).
+    pub allow_local_images: bool,
+    /// Directory that relative image paths are resolved against (default:
+    /// the current working directory).
+    pub base_dir: Option<PathBuf>,
+    /// Download `http(s)` `<img>` sources and embed them (default: `false`).
+    /// Requires the `remote-images` cargo feature; otherwise such images are
+    /// skipped with a warning as before.
+    pub allow_remote_images: bool,
+    /// Running header drawn in the top margin of every content page.
+    pub header: PageBand,
+    /// Running footer drawn in the bottom margin of every content page.
+    pub footer: PageBand,
+    /// Optional cover page HTML, laid out on its own page(s) before the main
+    /// content (default: `None`).
+    pub cover_html: Option<String>,
+    /// Bleed in points added around every page for print production; the
+    /// page background fills it (default: 0).
+    pub bleed_pt: f32,
+    /// Draw trim marks at the page corners, outside the bleed, enlarging the
+    /// media box to fit them (default: `false`).
+    pub crop_marks: bool,
+    /// Colour filling every page, margins included, beneath the watermark
+    /// and content (default: `None`, white paper).
+    pub page_background: Option<Color>,
+    /// Text drawn rotated behind the content of every page, including the
+    /// cover (default: `None`).
+    pub watermark: Option<Watermark>,
+    /// Make identical input render to byte-identical PDFs, for golden-file
+    /// tests and content-addressed caching (default: `false`).
+    pub deterministic: bool,
+    /// Flate-compress the PDF's streams (default: `true`). Turn off to get
+    /// human-readable content streams when debugging.
+    pub compress: bool,
 }
 
 impl Default for PipelineConfig {
@@ -42,6 +176,28 @@ impl Default for PipelineConfig {
             page_height: 841.89,
             page_margin: PAGE_MARGIN_PT,
             orientation: PageOrientation::Portrait,
+            svg_dpi: DEFAULT_SVG_DPI,
+            image_dpi: DEFAULT_IMAGE_DPI,
+            default_zoom: None,
+            theme: Theme::default(),
+            base_font_size: 16.0,
+            base_line_height: 1.4,
+            tab_size: 4,
+            ligatures: false,
+            hyphenate_lang: None,
+            keep_with_next_pt: KEEP_WITH_NEXT_PT,
+            allow_local_images: false,
+            base_dir: None,
+            allow_remote_images: false,
+            header: PageBand::default(),
+            footer: PageBand::default(),
+            cover_html: None,
+            bleed_pt: 0.0,
+            crop_marks: false,
+            page_background: None,
+            watermark: None,
+            deterministic: false,
+            compress: true,
         }
     }
 }
@@ -63,6 +219,14 @@ impl PipelineConfig {
         }
     }
 
+    /// Set `page_width` / `page_height` from a standard paper size.
+    pub fn with_page_size(mut self, size: PageSize) -> Self {
+        let (w, h) = size.dimensions_pt();
+        self.page_width = w;
+        self.page_height = h;
+        self
+    }
+
     /// Create an A4 landscape config.
     pub fn a4_landscape() -> Self {
         Self {
@@ -70,6 +234,75 @@ impl PipelineConfig {
             ..Self::default()
         }
     }
+
+    /// The fields styling depends on.
+    pub(crate) fn style_options(&self) -> StyleOptions<'_> {
+        StyleOptions {
+            theme: &self.theme,
+            base_font_size: self.base_font_size,
+            base_line_height: self.base_line_height,
+            tab_size: self.tab_size,
+            hyphenate_lang: self.hyphenate_lang.as_deref(),
+            allow_local_images: self.allow_local_images,
+            allow_remote_images: self.allow_remote_images,
+            base_dir: self.base_dir.as_deref(),
+        }
+    }
+}
+
+/// The [`PipelineConfig`] fields that [`style_dom`] reads. Styling sees
+/// nothing else, so hashing these is enough to key a styled tree.
+pub(crate) struct StyleOptions<'a> {
+    pub theme: &'a Theme,
+    pub base_font_size: f32,
+    pub base_line_height: f32,
+    pub tab_size: u32,
+    pub hyphenate_lang: Option<&'a str>,
+    pub allow_local_images: bool,
+    pub allow_remote_images: bool,
+    pub base_dir: Option<&'a Path>,
+}
+
+impl Hash for StyleOptions<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Exhaustive destructuring, so a new field cannot be left out of the
+        // hash and serve stale trees.
+        let Self {
+            theme,
+            base_font_size,
+            base_line_height,
+            tab_size,
+            hyphenate_lang,
+            allow_local_images,
+            allow_remote_images,
+            base_dir,
+        } = self;
+        let Theme {
+            list_indent,
+            blockquote_indent,
+            list_marker_gap,
+            spacing_scale,
+            table_zebra,
+        } = theme;
+        for f in [
+            list_indent,
+            blockquote_indent,
+            list_marker_gap,
+            spacing_scale,
+            base_font_size,
+            base_line_height,
+        ] {
+            f.to_bits().hash(state);
+        }
+        table_zebra
+            .map(|c| [c.r, c.g, c.b, c.a].map(f32::to_bits))
+            .hash(state);
+        tab_size.hash(state);
+        hyphenate_lang.hash(state);
+        allow_local_images.hash(state);
+        allow_remote_images.hash(state);
+        base_dir.hash(state);
+    }
 }
 
 /// Full pipeline: HTML string → PDF bytes.
@@ -78,46 +311,342 @@ impl PipelineConfig {
 pub fn generate_pdf(
     html: &str,
     config: &PipelineConfig,
-) -> Result<(Vec<u8>, LayoutConfig), String> {
-    // 1. Parse HTML
-    let dom = parse_html(html);
-    let dom_nodes = body_children(&dom);
+) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+    generate_pdf_with_fonts(html, config, &FontManager::default())
+}
+
+/// Like [`generate_pdf`], but measures and renders text with the fonts loaded
+/// into `fonts`. Fonts with bytes are embedded in the PDF; text in any other
+/// family falls back to the builtin Helvetica.
+pub fn generate_pdf_with_fonts(
+    html: &str,
+    config: &PipelineConfig,
+    fonts: &FontManager,
+) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+    generate_pdf_verbose(html, config, fonts).map(|(bytes, layout, _)| (bytes, layout))
+}
 
-    // 2. Build styled tree
-    let styled = build_styled_tree(&dom_nodes, None);
+/// Like [`generate_pdf_with_fonts`], but also returns the problems that were
+/// worked around instead of failing, such as images that were skipped.
+pub fn generate_pdf_verbose(
+    html: &str,
+    config: &PipelineConfig,
+    fonts: &FontManager,
+) -> Result<(Vec<u8>, LayoutConfig, Vec<Warning>), ForgeError> {
+    // 1–4. Parse, style, lay out and paginate (cover first, if any)
+    let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+    let layout_config =
+        compute_layout_config_from_styled(&style_html(html, config), config, fonts, &images);
 
-    // 3. Compute layout
-    let fonts = FontManager::default();
-    let eff_w = config.effective_width();
-    let eff_h = config.effective_height();
-    let boxes = compute_layout(&styled, eff_w, config.page_margin, &fonts);
+    // 5. Render PDF, reusing the images decoded during layout
+    render_layout_verbose(layout_config, config, fonts, &images)
+}
 
-    // 4. Paginate
-    let mut layout_config = paginate(&boxes, eff_w, eff_h, config.page_margin, &fonts);
-    layout_config.title = config.title.clone();
+/// Render several HTML documents into one PDF. Each document starts on a
+/// fresh page; the cover, header and footer apply to the combined document.
+pub fn generate_pdf_multi(
+    htmls: &[&str],
+    config: &PipelineConfig,
+) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+    generate_pdf_multi_verbose(htmls, config).map(|(bytes, layout, _)| (bytes, layout))
+}
 
-    // 5. Render PDF
-    let pdf_bytes = render_pdf(&layout_config)?;
+/// Like [`generate_pdf_multi`], but also returns the renderer's warnings.
+pub(crate) fn generate_pdf_multi_verbose(
+    htmls: &[&str],
+    config: &PipelineConfig,
+) -> Result<(Vec<u8>, LayoutConfig, Vec<Warning>), ForgeError> {
+    if htmls.is_empty() {
+        return Err(ForgeError::InvalidInput(
+            "no HTML documents to render".to_string(),
+        ));
+    }
+    let fonts = FontManager::default();
+    let styled: Vec<Vec<StyledNode>> = htmls.iter().map(|html| style_html(html, config)).collect();
+    let docs: Vec<&[StyledNode]> = styled.iter().map(Vec::as_slice).collect();
+    let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+    let layout_config = layout_documents(&docs, config, &fonts, &images);
+    render_layout_verbose(layout_config, config, &fonts, &images)
+}
 
-    Ok((pdf_bytes, layout_config))
+/// Lay out, paginate and render a styled tree built or rewritten by the
+/// caller, e.g. from [`style_html`] or assembled from [`StyledNode`]s
+/// directly. The cover, header and footer in `config` apply as for
+/// [`generate_pdf`].
+pub fn generate_pdf_from_styled(
+    styled: &[StyledNode],
+    config: &PipelineConfig,
+) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+    let fonts = FontManager::default();
+    let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+    let layout_config = compute_layout_config_from_styled(styled, config, &fonts, &images);
+    render_layout(layout_config, config, &fonts, &images)
 }
 
 /// Convenience: generate PDF with default A4 config.
-pub fn generate_pdf_from_html(html: &str) -> Result<Vec<u8>, String> {
+pub fn generate_pdf_from_html(html: &str) -> Result<Vec<u8>, ForgeError> {
     let (bytes, _) = generate_pdf(html, &PipelineConfig::default())?;
     Ok(bytes)
 }
 
 /// Generate only the layout config (no PDF rendering) – useful for testing.
+///
+/// When `config.cover_html` is set, the cover is paginated separately and its
+/// pages are prepended, so the main content always starts on a fresh page.
 pub fn compute_layout_config(html: &str, config: &PipelineConfig) -> LayoutConfig {
+    compute_layout_config_with_fonts(html, config, &FontManager::default())
+}
+
+/// Like [`compute_layout_config`], but wraps text using the metrics of the
+/// fonts loaded into `fonts`.
+pub fn compute_layout_config_with_fonts(
+    html: &str,
+    config: &PipelineConfig,
+    fonts: &FontManager,
+) -> LayoutConfig {
+    let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+    compute_layout_config_from_styled(&style_html(html, config), config, fonts, &images)
+}
+
+/// Lay out and paginate a styled tree, e.g. one from [`style_html`] that
+/// was post-processed. The cover, header and footer in `config` apply as
+/// for [`compute_layout_config`].
+pub fn compute_layout_from_styled(styled: &[StyledNode], config: &PipelineConfig) -> LayoutConfig {
+    let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+    compute_layout_config_from_styled(styled, config, &FontManager::default(), &images)
+}
+
+/// Lay out and paginate an already-styled tree (plus the cover, if any).
+pub(crate) fn compute_layout_config_from_styled(
+    styled: &[StyledNode],
+    config: &PipelineConfig,
+    fonts: &FontManager,
+    images: &ImageCache,
+) -> LayoutConfig {
+    layout_documents(&[styled], config, fonts, images)
+}
+
+/// Paginate each styled document on its own and concatenate the pages after
+/// the cover (if any), so every document starts on a fresh page.
+fn layout_documents(
+    docs: &[&[StyledNode]],
+    config: &PipelineConfig,
+    fonts: &FontManager,
+    images: &ImageCache,
+) -> LayoutConfig {
+    let fonts = &*with_ligatures(fonts, config);
+    let mut parts = Vec::new();
+    let mut cover_pages = 0;
+    if let Some(cover_html) = &config.cover_html {
+        let cover = paginate_styled(&style_html(cover_html, config), config, fonts, images);
+        cover_pages = cover.pages.len();
+        parts.push(cover);
+    }
+    parts.extend(
+        docs.iter()
+            .map(|styled| paginate_styled(styled, config, fonts, images)),
+    );
+
+    let mut parts = parts.into_iter();
+    let mut layout = parts.next().expect("at least one document");
+    for mut part in parts {
+        layout.pages.append(&mut part.pages);
+    }
+    for (i, page) in layout.pages.iter_mut().enumerate() {
+        page.page_index = i;
+    }
+
+    add_page_bands(&mut layout, cover_pages, config, fonts, images);
+    layout.svg_dpi = config.svg_dpi;
+    layout.image_dpi = config.image_dpi;
+    layout.default_zoom = config.default_zoom;
+    layout.bleed_pt = config.bleed_pt;
+    layout.crop_marks = config.crop_marks;
+    layout.page_background = config.page_background.map(|c| [c.r, c.g, c.b, c.a]);
+    layout.watermark = config.watermark.clone();
+    layout.deterministic = config.deterministic;
+    layout.compress = config.compress;
+    layout
+}
+
+/// Lay out the header/footer snippets on every page after the cover.
+fn add_page_bands(
+    layout: &mut LayoutConfig,
+    first_page: usize,
+    config: &PipelineConfig,
+    fonts: &FontManager,
+    images: &ImageCache,
+) {
+    if config.header.is_empty() && config.footer.is_empty() {
+        return;
+    }
+    let total = (layout.pages.len() - first_page).to_string();
+    let eff_w = config.effective_width();
+    let eff_h = config.effective_height();
+    let margin = config.page_margin;
+
+    for (page, number) in layout.pages.iter_mut().skip(first_page).zip(1usize..) {
+        let mut vars = HashMap::new();
+        vars.insert("page".to_string(), number.to_string());
+        vars.insert("pages".to_string(), total.clone());
+
+        for (band, band_top) in [(&config.header, 0.0), (&config.footer, eff_h - margin)] {
+            let slots = [
+                (&band.left, BandSlot::Left),
+                (&band.center, BandSlot::Center),
+                (&band.right, BandSlot::Right),
+            ];
+            for (html, slot) in slots {
+                let Some(html) = html else { continue };
+                let html = render_template(html, &vars);
+                let styled = style_html(&html, config);
+                let boxes = compute_layout_with_images(&styled, eff_w, margin, fonts, images);
+                let mut placed = place_boxes(&boxes, 0.0, fonts);
+                let Some(bounds) = placed
+                    .iter()
+                    .filter_map(|b| b.content_bounds())
+                    .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+                else {
+                    continue;
+                };
+                let (left, top, right, bottom) = bounds;
+                let dx = match slot {
+                    BandSlot::Left => margin - left,
+                    BandSlot::Center => eff_w / 2.0 - (left + right) / 2.0,
+                    BandSlot::Right => eff_w - margin - right,
+                };
+                let dy = band_top + (margin - (bottom - top)) / 2.0 - top;
+                for b in &mut placed {
+                    b.translate(dx, dy);
+                }
+                page.boxes.extend(placed);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum BandSlot {
+    Left,
+    Center,
+    Right,
+}
+
+/// Render a layout to PDF bytes, stamping the configured title.
+pub(crate) fn render_layout(
+    layout: LayoutConfig,
+    config: &PipelineConfig,
+    fonts: &FontManager,
+    images: &ImageCache,
+) -> Result<(Vec<u8>, LayoutConfig), ForgeError> {
+    render_layout_verbose(layout, config, fonts, images).map(|(bytes, layout, _)| (bytes, layout))
+}
+
+/// `fonts` with ligatures switched on or off as `config` asks, copied only
+/// when that differs from how they were loaded.
+fn with_ligatures<'f>(fonts: &'f FontManager, config: &PipelineConfig) -> Cow<'f, FontManager> {
+    if fonts.ligatures() == config.ligatures {
+        return Cow::Borrowed(fonts);
+    }
+    let mut fonts = fonts.clone();
+    fonts.set_ligatures(config.ligatures);
+    Cow::Owned(fonts)
+}
+
+/// Like [`render_layout`], but also returns the renderer's warnings.
+fn render_layout_verbose(
+    mut layout: LayoutConfig,
+    config: &PipelineConfig,
+    fonts: &FontManager,
+    images: &ImageCache,
+) -> Result<(Vec<u8>, LayoutConfig, Vec<Warning>), ForgeError> {
+    layout.title = config.title.clone();
+    let fonts = with_ligatures(fonts, config);
+    let (pdf_bytes, warnings) = render_pdf_with_images(&layout, &fonts, images)?;
+    Ok((pdf_bytes, layout, warnings))
+}
+
+/// Parse an HTML document and resolve its styles: the first stages of
+/// [`generate_pdf`], stopping before layout.
+///
+/// Only the `<body>` content is styled (the whole document if there is no
+/// `<body>`), starting from `config`'s base font size, line height and
+/// theme. The tree can be inspected or rewritten and then laid out with
+/// [`compute_layout_from_styled`].
+pub fn style_html(html: &str, config: &PipelineConfig) -> Vec<StyledNode> {
     let dom = parse_html(html);
-    let dom_nodes = body_children(&dom);
-    let styled = build_styled_tree(&dom_nodes, None);
-    let fonts = FontManager::default();
+    style_dom(body_children(&dom), &config.style_options())
+}
+
+/// Resolve styles for a DOM tree, first embedding local/remote images if
+/// allowed.
+pub(crate) fn style_dom(mut nodes: Vec<DomNode>, options: &StyleOptions) -> Vec<StyledNode> {
+    if options.allow_local_images || options.allow_remote_images {
+        embed_images(&mut nodes, options);
+    }
+    let root = ComputedStyle {
+        font_size: options.base_font_size,
+        line_height: options.base_line_height,
+        tab_size: options.tab_size,
+        ..ComputedStyle::default()
+    };
+    let mut styled = build_styled_tree_with_theme(&nodes, Some(&root), options.theme);
+    if let Some(lang) = options.hyphenate_lang {
+        crate::hyphenation::hyphenate_tree(&mut styled, lang);
+    }
+    styled
+}
+
+/// Replace file-path and URL `<img>` sources with data URIs. Sources that
+/// cannot be loaded are left untouched, so the renderer skips them with a
+/// warning.
+fn embed_images(nodes: &mut [DomNode], options: &StyleOptions) {
+    for node in nodes {
+        let DomNode::Element(e) = node else { continue };
+        if e.tag == Tag::Img {
+            if let Some(src) = e.attributes.get_mut("src") {
+                let loaded = if options.allow_remote_images && is_remote_image_src(src) {
+                    Some(remote_image_to_data_uri(src))
+                } else if options.allow_local_images && is_local_image_src(src) {
+                    Some(local_image_to_data_uri(src, options.base_dir))
+                } else {
+                    None
+                };
+                match loaded {
+                    Some(Ok(uri)) => *src = uri,
+                    Some(Err(err)) => log::warn!("Skipping image — {err}"),
+                    None => {}
+                }
+            }
+        }
+        embed_images(&mut e.children, options);
+    }
+}
+
+/// Lay out and paginate a single styled document.
+fn paginate_styled(
+    styled: &[StyledNode],
+    config: &PipelineConfig,
+    fonts: &FontManager,
+    images: &ImageCache,
+) -> LayoutConfig {
     let eff_w = config.effective_width();
     let eff_h = config.effective_height();
-    let boxes = compute_layout(&styled, eff_w, config.page_margin, &fonts);
-    paginate(&boxes, eff_w, eff_h, config.page_margin, &fonts)
+    let boxes = compute_layout_with_images(styled, eff_w, config.page_margin, fonts, images);
+    let options = PaginationOptions {
+        keep_with_next_pt: config.keep_with_next_pt,
+    };
+    paginate_with_options(&boxes, eff_w, eff_h, config.page_margin, &options, fonts)
+}
+
+/// Check every `<img>` in `html` for problems that would cause it to be
+/// skipped at render time, without producing a PDF.
+///
+/// Only images that survive layout (i.e. are not `display: none`) are checked.
+/// An empty result means every image will be embedded.
+pub fn preflight_images(html: &str, config: &PipelineConfig) -> Vec<ImageIssue> {
+    let layout = compute_layout_config(html, config);
+    preflight_layout_images(&layout)
 }
 
 #[cfg(test)]
@@ -132,4 +661,186 @@ mod tests {
         assert!(!config.pages.is_empty());
         assert_eq!(&bytes[0..5], b"%PDF-");
     }
+
+    #[test]
+    fn spacing_scale_halves_heading_margins() {
+        let margins = |config: &PipelineConfig| {
+            let styled = style_html(r#"<h1>Title</h1><p class="mb-8">Body</p>"#, config);
+            styled
+                .iter()
+                .map(|node| match node {
+                    StyledNode::Element { style, .. } => (style.margin_top, style.margin_bottom),
+                    StyledNode::Text { .. } => panic!("expected elements"),
+                })
+                .collect::<Vec<_>>()
+        };
+        let normal = margins(&PipelineConfig::default());
+        let tight = margins(&PipelineConfig {
+            theme: Theme {
+                spacing_scale: 0.5,
+                ..Theme::default()
+            },
+            ..PipelineConfig::default()
+        });
+        assert_eq!(tight[0], (normal[0].0 / 2.0, normal[0].1 / 2.0));
+        // Margins set by classes are left alone.
+        assert_eq!(tight[1], normal[1]);
+    }
+
+    #[test]
+    fn table_zebra_tints_alternate_body_rows() {
+        let html = r#"<table>
+            <tr><th>Item</th></tr>
+            <tr><td>One</td></tr>
+            <tr><td>Two</td></tr>
+            <tr><td>Three</td></tr>
+            <tr style="background-color: #ff0000"><td>Four</td></tr>
+        </table>"#;
+        let config = PipelineConfig {
+            theme: Theme {
+                table_zebra: Some(Color::from_hex("#eeeeee").unwrap()),
+                ..Theme::default()
+            },
+            ..PipelineConfig::default()
+        };
+        let layout = compute_layout_config(html, &config);
+        let table = &layout.pages[0].boxes[0];
+        let grey = 0xee as f32 / 255.0;
+        let backgrounds: Vec<_> = table
+            .children
+            .iter()
+            .map(|row| row.background_color.map(|c| c[0]))
+            .collect();
+        assert_eq!(backgrounds, [None, None, Some(grey), None, Some(1.0)]);
+    }
+
+    #[test]
+    fn repeated_image_is_decoded_once() {
+        let png = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+        let html = format!(r#"<img src="{png}"/><img src="{png}"/><img src="{png}"/>"#);
+        let config = PipelineConfig::default();
+        let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+
+        let layout = compute_layout_config_from_styled(
+            &style_html(&html, &config),
+            &config,
+            &FontManager::default(),
+            &images,
+        );
+        assert_eq!(images.decodes(), 0, "layout only probes the size");
+        let (_, _, warnings) =
+            render_layout_verbose(layout, &config, &FontManager::default(), &images).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(images.decodes(), 1);
+    }
+
+    #[test]
+    fn styled_tree_can_be_edited_before_layout() {
+        let config = PipelineConfig::default();
+        let mut styled = style_html(
+            r#"<html><body><p class="text-xl">Hi <b>there</b></p></body></html>"#,
+            &config,
+        );
+        assert_eq!(styled.len(), 1);
+        let StyledNode::Element {
+            tag,
+            style,
+            children,
+            ..
+        } = &mut styled[0]
+        else {
+            panic!("expected the paragraph");
+        };
+        assert_eq!(*tag, Tag::P);
+        assert_eq!(style.font_size, 20.0);
+        let StyledNode::Element { style: bold, .. } = &children[1] else {
+            panic!("expected the <b>");
+        };
+        assert_eq!(bold.font_weight, crate::style::FontWeight::Bold);
+        assert_eq!(bold.font_size, 20.0);
+
+        style.color = Color {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
+        let layout = compute_layout_from_styled(&styled, &config);
+        let text = layout.pages[0].boxes[0].text.as_ref().unwrap();
+        assert_eq!(text.color, [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn image_dpi_maps_pixels_to_points() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        ::image::DynamicImage::new_rgb8(96, 48)
+            .write_to(&mut png, ::image::ImageFormat::Png)
+            .unwrap();
+        let html = format!(
+            r#"<img src="data:image/png;base64,{}"/>"#,
+            STANDARD.encode(png.get_ref())
+        );
+        let size = |image_dpi: f32| {
+            let config = PipelineConfig {
+                image_dpi,
+                ..PipelineConfig::default()
+            };
+            let layout = compute_layout_config(&html, &config);
+            let img = layout.pages[0].boxes[0].image.clone().unwrap();
+            (img.width, img.height)
+        };
+        assert_eq!(size(96.0), (72.0, 36.0));
+        assert_eq!(size(72.0), (96.0, 48.0));
+    }
+
+    #[test]
+    fn tab_size_sets_pre_indentation() {
+        let indent = |tab_size: u32| {
+            let config = PipelineConfig {
+                tab_size,
+                ..PipelineConfig::default()
+            };
+            let layout = compute_layout_config("<pre>\tx</pre>", &config);
+            let text = layout.pages[0].boxes[0].children[0].text.clone().unwrap();
+            text.lines[0].text.len() - 1
+        };
+        assert_eq!(indent(4), 4);
+        assert_eq!(indent(2), 2);
+        assert_eq!(indent(8), 8);
+    }
+
+    #[test]
+    fn base_font_size_scales_unstyled_text() {
+        fn text_sizes(b: &crate::layout_config::LayoutBox, out: &mut Vec<(String, f32, f32)>) {
+            if let Some(t) = &b.text {
+                let text = t.lines.iter().map(|l| l.text.as_str()).collect();
+                out.push((
+                    text,
+                    t.font_size,
+                    (t.line_height / t.font_size * 10.0).round(),
+                ));
+            }
+            b.children.iter().for_each(|c| text_sizes(c, out));
+        }
+        let html = "<p>Body text</p><div><p>Nested</p></div><h1>Title</h1>";
+        let config = PipelineConfig {
+            base_font_size: 12.0,
+            base_line_height: 1.2,
+            ..PipelineConfig::default()
+        };
+        let mut sizes = Vec::new();
+        for b in &compute_layout_config(html, &config).pages[0].boxes {
+            text_sizes(b, &mut sizes);
+        }
+        assert_eq!(
+            sizes,
+            [
+                ("Body text".to_string(), 12.0, 12.0),
+                ("Nested".to_string(), 12.0, 12.0),
+                ("Title".to_string(), 24.0, 12.0),
+            ]
+        );
+    }
 }
@@ -1,92 +1,447 @@
 //! PDF renderer – takes a [`LayoutConfig`] and produces PDF bytes using
 //! `printpdf` (v0.8 ops-based API).
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
 
 use base64::{engine::general_purpose::STANDARD as BASE64_STD, Engine as _};
 use printpdf::*;
+use rayon::prelude::*;
 
+use crate::error::ForgeError;
+use crate::fonts::{apply_ligatures, FontKey, FontManager, Ligature, Shaped, NBSP};
+use crate::images::{decode_image, ImageCache};
 use crate::layout_config::*;
+use crate::svg;
 
 /// A printpdf XObject together with the pixel dimensions of the source image.
 struct ImageResource {
     xobj_id: XObjectId,
     px_width: u32,
     px_height: u32,
+    /// Intrinsic size in points, used when the layout gave the box no size.
+    width: f32,
+    height: f32,
+}
+
+/// The document being built. Resources get printpdf's random IDs, or
+/// sequential ones when the output must be byte-for-byte reproducible.
+struct DocBuilder {
+    doc: PdfDocument,
+    /// Last sequential ID handed out; `None` for random IDs.
+    last_id: Option<usize>,
+    warnings: Vec<Warning>,
+}
+
+impl DocBuilder {
+    fn new(title: &str, deterministic: bool) -> Self {
+        let mut doc = PdfDocument::new(title);
+        if deterministic {
+            let epoch = OffsetDateTime::from_unix_timestamp(0).expect("epoch is a valid date");
+            let info = &mut doc.metadata.info;
+            info.creation_date = epoch;
+            info.modification_date = epoch;
+            info.metadata_date = epoch;
+        }
+        Self {
+            doc,
+            last_id: deterministic.then_some(0),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Record a problem that does not stop rendering; it is also logged.
+    fn warn(&mut self, kind: WarningKind, message: String) {
+        log::warn!("{message}");
+        self.warnings.push(Warning { kind, message });
+    }
+
+    fn next_id(&mut self) -> Option<String> {
+        self.last_id.as_mut().map(|n| {
+            *n += 1;
+            format!("R{n}")
+        })
+    }
+
+    fn add_font(&mut self, font: &ParsedFont) -> FontId {
+        match self.next_id() {
+            Some(id) => {
+                let id = FontId(id);
+                self.doc
+                    .resources
+                    .fonts
+                    .map
+                    .insert(id.clone(), font.clone());
+                id
+            }
+            None => self.doc.add_font(font),
+        }
+    }
+
+    fn add_xobject(&mut self, xobject: XObject) -> XObjectId {
+        let id = XObjectId(self.next_id().unwrap_or_else(|| XObjectId::new().0));
+        self.doc.resources.xobjects.map.insert(id.clone(), xobject);
+        id
+    }
+
+    fn add_graphics_state(&mut self, gs: ExtendedGraphicsState) -> ExtendedGraphicsStateId {
+        match self.next_id() {
+            Some(id) => {
+                let id = ExtendedGraphicsStateId(id);
+                self.doc.resources.extgstates.map.insert(id.clone(), gs);
+                id
+            }
+            None => self.doc.add_graphics_state(gs),
+        }
+    }
+
+    /// Move the resources and warnings collected while drawing a page into
+    /// the document.
+    fn merge(&mut self, page: PageResources) {
+        for (id, xobject) in page.xobjects {
+            self.doc.resources.xobjects.map.insert(id, xobject);
+        }
+        self.warnings.extend(page.warnings);
+    }
+}
+
+/// Resources created while drawing a single page. Pages are drawn in
+/// parallel, each into its own `PageResources`, and merged into the
+/// [`DocBuilder`] in page order afterwards.
+struct PageResources {
+    /// Prefix of sequential IDs (unique per page); `None` for random IDs.
+    id_prefix: Option<String>,
+    last_id: usize,
+    xobjects: Vec<(XObjectId, XObject)>,
+    /// Gradient forms by [`GradientFill::key`]: those registered with the
+    /// document up front, plus any this page had to add itself.
+    gradients: HashMap<GradientKey, XObjectId>,
+    warnings: Vec<Warning>,
+}
+
+impl PageResources {
+    fn new(page_index: usize, deterministic: bool) -> Self {
+        Self {
+            id_prefix: deterministic.then(|| format!("P{page_index}R")),
+            last_id: 0,
+            xobjects: Vec::new(),
+            gradients: HashMap::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Record a problem that does not stop rendering; it is also logged.
+    fn warn(&mut self, kind: WarningKind, message: String) {
+        log::warn!("{message}");
+        self.warnings.push(Warning { kind, message });
+    }
+
+    fn add_xobject(&mut self, xobject: XObject) -> XObjectId {
+        let id = match &self.id_prefix {
+            Some(prefix) => {
+                self.last_id += 1;
+                XObjectId(format!("{prefix}{}", self.last_id))
+            }
+            None => XObjectId::new(),
+        };
+        self.xobjects.push((id.clone(), xobject));
+        id
+    }
+
+    /// The form XObject painting `fill`, shared by every box with the same
+    /// gradient and size.
+    fn gradient(&mut self, fill: &GradientFill) -> XObjectId {
+        if let Some(id) = self.gradients.get(&fill.key()) {
+            return id.clone();
+        }
+        let id = self.add_xobject(XObject::External(gradient_xobject(fill)));
+        self.gradients.insert(fill.key(), id.clone());
+        id
+    }
+}
+
+/// Ascender of the builtin Helvetica as a fraction of the font size.
+const BUILTIN_ASCENT: f32 = 0.75;
+
+/// A font embedded in the document.
+#[derive(Clone)]
+struct EmbeddedFont {
+    id: FontId,
+    /// Ascender as a fraction of the font size.
+    ascent: f32,
+    /// Ligatures drawn in place of their letters; empty when switched off.
+    ligatures: Vec<Ligature>,
+}
+
+/// Fonts embedded in the document, keyed the way [`FontManager`] stores them.
+#[derive(Default)]
+struct EmbeddedFonts {
+    /// Every loaded key; `None` for fonts with synthetic metrics only, which
+    /// are drawn with the builtin Helvetica.
+    ids: HashMap<FontKey, Option<EmbeddedFont>>,
+    /// Font used for keys that are not loaded, mirroring [`FontManager::get`].
+    fallback: Option<EmbeddedFont>,
+}
+
+impl EmbeddedFonts {
+    /// Embed every font in `fonts` that was loaded from bytes into `doc`.
+    fn embed(doc: &mut DocBuilder, fonts: &FontManager) -> Result<Self, ForgeError> {
+        let mut embedded = Self::default();
+        let mut warnings: Vec<PdfWarnMsg> = Vec::new();
+        let mut keys = fonts.keys();
+        keys.sort_by(|a, b| (&a.family, a.bold, a.italic).cmp(&(&b.family, b.bold, b.italic)));
+        for key in keys {
+            let font = match fonts.font_bytes(&key) {
+                Some(bytes) => Some(EmbeddedFont {
+                    id: ParsedFont::from_bytes(bytes, 0, &mut warnings)
+                        .map(|font| doc.add_font(&font))
+                        .ok_or_else(|| {
+                            ForgeError::Font(format!("failed to embed font {:?}", key.family))
+                        })?,
+                    ascent: fonts.ascender_px(1.0, key.bold, key.italic, &key.family),
+                    ligatures: fonts
+                        .ligatures_for(key.bold, key.italic, &key.family)
+                        .to_vec(),
+                }),
+                None => None,
+            };
+            embedded.ids.insert(key, font);
+        }
+        embedded.fallback = embedded.ids.get(fonts.default_key()).cloned().flatten();
+        Ok(embedded)
+    }
+
+    /// Distance from the top of a line to its baseline: the embedded font's
+    /// ascender, or Helvetica's for text drawn with the builtin font.
+    fn baseline_offset(&self, family: &str, bold: bool, italic: bool, font_size: f32) -> f32 {
+        self.lookup(family, bold, italic)
+            .map_or(BUILTIN_ASCENT, |font| font.ascent)
+            * font_size
+    }
+
+    fn lookup(&self, family: &str, bold: bool, italic: bool) -> Option<&EmbeddedFont> {
+        let key = FontKey {
+            family: family.to_string(),
+            bold,
+            italic,
+        };
+        match self.ids.get(&key) {
+            Some(id) => id.as_ref(),
+            None => self.fallback.as_ref(),
+        }
+    }
 }
 
 /// Render a LayoutConfig into PDF bytes.
 ///
 /// `<img>` elements whose `src` is not a base64 data URI, or whose bytes
-/// cannot be decoded, are silently skipped (a `log::warn` is emitted).
-pub fn render_pdf(config: &LayoutConfig) -> Result<Vec<u8>, String> {
-    let page_w = Mm(config.page_width_pt * 0.352778); // pt → mm
-    let page_h = Mm(config.page_height_pt * 0.352778);
+/// cannot be decoded, are skipped with a `log::warn`; use
+/// [`render_pdf_with_warnings`] to get those warnings back.
+/// SVG images are rasterized at `config.svg_dpi`.
+pub fn render_pdf(config: &LayoutConfig) -> Result<Vec<u8>, ForgeError> {
+    render_pdf_with_fonts(config, &FontManager::default())
+}
 
-    let mut doc = PdfDocument::new(&config.title);
+/// Like [`render_pdf`], but embeds every font in `fonts` that was loaded from
+/// bytes and draws text set in those fonts with it.
+pub fn render_pdf_with_fonts(
+    config: &LayoutConfig,
+    fonts: &FontManager,
+) -> Result<Vec<u8>, ForgeError> {
+    render_pdf_with_warnings(config, fonts).map(|(bytes, _)| bytes)
+}
+
+/// Like [`render_pdf_with_fonts`], but also returns the problems that were
+/// worked around, such as skipped images.
+pub fn render_pdf_with_warnings(
+    config: &LayoutConfig,
+    fonts: &FontManager,
+) -> Result<(Vec<u8>, Vec<Warning>), ForgeError> {
+    render_pdf_with_images(
+        config,
+        fonts,
+        &ImageCache::new(config.svg_dpi, config.image_dpi),
+    )
+}
+
+/// Like [`render_pdf_with_warnings`], but takes images from `images`, which
+/// may already hold the ones decoded during layout.
+pub(crate) fn render_pdf_with_images(
+    config: &LayoutConfig,
+    fonts: &FontManager,
+    images: &ImageCache,
+) -> Result<(Vec<u8>, Vec<Warning>), ForgeError> {
+    render_pages(config, &config.pages, fonts, images)
+}
+
+/// Render only the pages of `config` whose indices fall in `range` (e.g.
+/// `1..=1` for the second page), for previewing part of a long document.
+/// Only the images on those pages are decoded and embedded.
+///
+/// Fails with [`ForgeError::InvalidInput`] when the range is empty or
+/// extends past the last page.
+pub fn render_pages_range(
+    config: &LayoutConfig,
+    fonts: &FontManager,
+    range: impl RangeBounds<usize>,
+) -> Result<Vec<u8>, ForgeError> {
+    let bounds: (Bound<usize>, Bound<usize>) =
+        (range.start_bound().cloned(), range.end_bound().cloned());
+    let pages = config
+        .pages
+        .get(bounds)
+        .filter(|pages| !pages.is_empty())
+        .ok_or_else(|| {
+            ForgeError::InvalidInput(format!(
+                "page range selects no pages of a {}-page layout",
+                config.pages.len()
+            ))
+        })?;
+    let images = ImageCache::new(config.svg_dpi, config.image_dpi);
+    render_pages(config, pages, fonts, &images).map(|(bytes, _)| bytes)
+}
+
+/// Render `pages` with the page size, title and document options of
+/// `config`.
+fn render_pages(
+    config: &LayoutConfig,
+    pages: &[PageLayout],
+    fonts: &FontManager,
+    images: &ImageCache,
+) -> Result<(Vec<u8>, Vec<Warning>), ForgeError> {
+    // Bleed and crop marks surround the trimmed page on the media box; the
+    // page is drawn offset by `margin` so layout coordinates are unchanged.
+    let margin = media_margin(config);
+    let page_w = Mm((config.page_width_pt + 2.0 * margin) * 0.352778); // pt → mm
+    let page_h = Mm((config.page_height_pt + 2.0 * margin) * 0.352778);
+
+    let mut doc = DocBuilder::new(&config.title, config.deterministic);
+
+    // ── Embed loaded fonts ─────────────────────────────────────────────────
+    let embedded = EmbeddedFonts::embed(&mut doc, fonts)?;
 
     // ── Pre-register all images ────────────────────────────────────────────
     let mut all_srcs: HashSet<&str> = HashSet::new();
-    for page_layout in &config.pages {
+    for page_layout in pages {
         for lbox in &page_layout.boxes {
             collect_image_srcs(lbox, &mut all_srcs);
         }
     }
 
     let mut image_resources: HashMap<String, ImageResource> = HashMap::new();
-    let mut img_warnings: Vec<PdfWarnMsg> = Vec::new();
 
+    // Register in a stable order so sequential resource IDs are reproducible.
+    let mut all_srcs: Vec<&str> = all_srcs.into_iter().collect();
+    all_srcs.sort_unstable();
     for src in &all_srcs {
-        let bytes = match parse_data_uri(src) {
-            Ok(b) => b,
-            Err(e) => {
-                log::warn!("Skipping image — {e}");
-                continue;
-            }
-        };
-
-        // Decode with the `image` crate to obtain pixel dimensions.
-        let dyn_img = match ::image::load_from_memory(&bytes) {
-            Ok(img) => img,
+        let image = match images.get(src) {
+            Ok(image) => image,
             Err(e) => {
-                log::warn!("Skipping image — decode error: {e}");
+                doc.warn(WarningKind::SkippedImage, format!("Skipping image — {e}"));
                 continue;
             }
         };
-        let (px_width, px_height) = (dyn_img.width(), dyn_img.height());
-
         // Register with printpdf as a reusable XObject.
-        let raw = match RawImage::decode_from_bytes(&bytes, &mut img_warnings) {
-            Ok(r) => r,
-            Err(e) => {
-                log::warn!("Skipping image — PDF encode error: {e}");
-                continue;
-            }
-        };
-        let xobj_id = doc.add_image(&raw);
-
+        let xobj_id = doc.add_xobject(XObject::Image(image.raw.clone()));
         image_resources.insert(
             src.to_string(),
             ImageResource {
                 xobj_id,
-                px_width,
-                px_height,
+                px_width: image.raw.width as u32,
+                px_height: image.raw.height as u32,
+                width: image.width,
+                height: image.height,
             },
         );
     }
 
-    // ── Render pages ──────────────────────────────────────────────────────
-    let mut pages = Vec::new();
-
-    for page_layout in &config.pages {
-        let mut ops = Vec::new();
-
+    // ── Pre-register gradient forms, one per distinct gradient and size ──
+    let mut fills = BTreeMap::new();
+    for page_layout in pages {
         for lbox in &page_layout.boxes {
-            render_box(&mut ops, lbox, config.page_height_pt, &image_resources);
+            collect_gradient_fills(lbox, &mut fills);
         }
+    }
+    let gradients: HashMap<GradientKey, XObjectId> = fills
+        .into_iter()
+        .map(|(key, fill)| {
+            let id = doc.add_xobject(XObject::External(gradient_xobject(&fill)));
+            (key, id)
+        })
+        .collect();
+
+    // The watermark's opacity is shared by every page.
+    let watermark_gs = config
+        .watermark
+        .as_ref()
+        .filter(|watermark| watermark.opacity < 1.0)
+        .map(|watermark| {
+            doc.add_graphics_state(
+                ExtendedGraphicsState::default().with_current_fill_alpha(watermark.opacity),
+            )
+        });
+
+    // Internal links can point at any page, so targets are found up front.
+    let anchors = anchor_positions(pages);
+
+    // ── Render pages ──────────────────────────────────────────────────────
+    // Pages only read the shared resources above, so their ops are built in
+    // parallel; `collect` keeps them in page order.
+    let rendered: Vec<(Vec<Op>, PageResources, Vec<InternalLink>)> = pages
+        .par_iter()
+        .enumerate()
+        .map(|(index, page_layout)| {
+            let mut ops = Vec::new();
+            let mut resources = PageResources::new(index, config.deterministic);
+            resources.gradients = gradients.clone();
+            if margin > 0.0 {
+                ops.push(Op::SaveGraphicsState);
+                ops.push(Op::SetTransformationMatrix {
+                    matrix: CurTransMat::Raw([1.0, 0.0, 0.0, 1.0, margin, margin]),
+                });
+            }
+            if let Some(background) = &config.page_background {
+                fill_page(&mut ops, background, config);
+            }
+            if let Some(watermark) = &config.watermark {
+                draw_watermark(&mut ops, watermark, config, fonts, watermark_gs.as_ref());
+            }
+            for lbox in &page_layout.boxes {
+                render_box(
+                    &mut ops,
+                    lbox,
+                    config.page_height_pt,
+                    &image_resources,
+                    &embedded,
+                    &mut resources,
+                );
+            }
+            if config.crop_marks {
+                draw_crop_marks(&mut ops, config);
+            }
+            if margin > 0.0 {
+                ops.push(Op::RestoreGraphicsState);
+            }
+            let mut links = Vec::new();
+            for lbox in &page_layout.boxes {
+                collect_links(lbox, &anchors, config.page_height_pt, &mut links);
+            }
+            // Annotations are placed in unshifted page space.
+            for link in &mut links {
+                link.rect = link.rect.map(|v| v + margin);
+                link.top += margin;
+            }
+            (ops, resources, links)
+        })
+        .collect();
 
-        let page = PdfPage::new(page_w, page_h, ops);
-        pages.push(page);
+    let mut pages = Vec::new();
+    let mut page_links = Vec::new();
+    for (ops, resources, links) in rendered {
+        doc.merge(resources);
+        pages.push(PdfPage::new(page_w, page_h, ops));
+        page_links.push(links);
     }
 
     // Ensure at least one page.
@@ -94,10 +449,302 @@ pub fn render_pdf(config: &LayoutConfig) -> Result<Vec<u8>, String> {
         pages.push(PdfPage::new(page_w, page_h, Vec::new()));
     }
 
+    let DocBuilder {
+        mut doc, warnings, ..
+    } = doc;
     doc.with_pages(pages);
-    let bytes = doc.save(&PdfSaveOptions::default(), &mut Vec::new());
+    let mut bytes = doc.save(&PdfSaveOptions::default(), &mut Vec::new());
+    if config.deterministic {
+        set_content_id(&mut bytes);
+    }
+
+    let has_links = page_links.iter().any(|links| !links.is_empty());
+    if !needs_post_pass(config, has_links, margin) {
+        return Ok((bytes, warnings));
+    }
+
+    let mut pdf = lopdf::Document::load_mem(&bytes)
+        .map_err(|e| ForgeError::Render(format!("PDF reparse failed: {e}")))?;
+    if let Some(zoom) = config.default_zoom {
+        set_open_action(&mut pdf, zoom)?;
+    }
+    if has_links {
+        add_link_annotations(&mut pdf, &page_links);
+    }
+    if margin > 0.0 {
+        set_trim_boxes(&mut pdf, config, margin);
+    }
+    if config.compress {
+        pdf.compress();
+    }
+    let mut out = Vec::new();
+    pdf.save_to(&mut out)
+        .map_err(|e| ForgeError::Render(format!("PDF write failed: {e}")))?;
+    Ok((out, warnings))
+}
+
+/// Whether the saved PDF must be reparsed with lopdf. printpdf can neither
+/// compress streams, set an open action, attach annotations to the page (it
+/// files them under the page's resources) nor write an offset trim box, so
+/// any of those means a second pass; otherwise printpdf's bytes are final.
+fn needs_post_pass(config: &LayoutConfig, has_links: bool, margin: f32) -> bool {
+    config.compress || config.default_zoom.is_some() || has_links || margin > 0.0
+}
+
+/// Length of each trim mark line, in points.
+const CROP_MARK_LENGTH: f32 = 12.0;
+/// Least distance between a trim mark and the trim edge, in points.
+const CROP_MARK_GAP: f32 = 3.0;
+
+/// Distance between a trim mark and the trim edge: outside the bleed, so
+/// the marks are never printed on a page.
+fn crop_mark_offset(config: &LayoutConfig) -> f32 {
+    config.bleed_pt.max(CROP_MARK_GAP)
+}
+
+/// Space around the trimmed page on the media box: the bleed, widened to
+/// fit the trim marks when they are drawn.
+fn media_margin(config: &LayoutConfig) -> f32 {
+    if config.crop_marks {
+        crop_mark_offset(config) + CROP_MARK_LENGTH
+    } else {
+        config.bleed_pt
+    }
+}
+
+/// Draw a horizontal and a vertical trim mark outside each page corner, in
+/// line with the trim edges.
+fn draw_crop_marks(ops: &mut Vec<Op>, config: &LayoutConfig) {
+    let (w, h) = (config.page_width_pt, config.page_height_pt);
+    let near = crop_mark_offset(config);
+    let far = near + CROP_MARK_LENGTH;
+    ops.push(Op::SetOutlineThickness { pt: Pt(0.25) });
+    ops.push(Op::SetOutlineColor {
+        col: Color::Rgb(Rgb {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            icc_profile: None,
+        }),
+    });
+    for (x, y, out_x, out_y) in [
+        (0.0, 0.0, -1.0, -1.0),
+        (w, 0.0, 1.0, -1.0),
+        (0.0, h, -1.0, 1.0),
+        (w, h, 1.0, 1.0),
+    ] {
+        for (from, to) in [
+            ((x + out_x * near, y), (x + out_x * far, y)),
+            ((x, y + out_y * near), (x, y + out_y * far)),
+        ] {
+            ops.push(Op::DrawLine {
+                line: Line {
+                    points: [from, to]
+                        .into_iter()
+                        .map(|(x, y)| LinePoint {
+                            p: Point { x: Pt(x), y: Pt(y) },
+                            bezier: false,
+                        })
+                        .collect(),
+                    is_closed: false,
+                },
+            });
+        }
+    }
+}
+
+/// Fill the whole page, margins and bleed included, with `color`.
+fn fill_page(ops: &mut Vec<Op>, color: &[f32; 4], config: &LayoutConfig) {
+    let bleed = config.bleed_pt;
+    ops.push(Op::SetFillColor {
+        col: Color::Rgb(Rgb {
+            r: color[0],
+            g: color[1],
+            b: color[2],
+            icc_profile: None,
+        }),
+    });
+    ops.push(Op::DrawPolygon {
+        polygon: Polygon {
+            rings: vec![PolygonRing {
+                points: rect_points(
+                    -bleed,
+                    -bleed,
+                    config.page_width_pt + 2.0 * bleed,
+                    config.page_height_pt + 2.0 * bleed,
+                ),
+            }],
+            mode: PaintMode::Fill,
+            winding_order: WindingOrder::NonZero,
+        },
+    });
+}
+
+/// Draw `watermark` centred on the page in builtin Helvetica, rotated about
+/// its centre.
+fn draw_watermark(
+    ops: &mut Vec<Op>,
+    watermark: &Watermark,
+    config: &LayoutConfig,
+    fonts: &FontManager,
+    gs: Option<&ExtendedGraphicsStateId>,
+) {
+    let size = watermark.font_size;
+    let width = fonts.measure_text_width(&watermark.text, size, false, false, "Helvetica");
+    let (sin, cos) = watermark.angle.to_radians().sin_cos();
+    // Step back half the text width along the baseline and drop the
+    // baseline by half the cap height so the text's centre sits on the
+    // page's centre.
+    let half_cap = size * 0.35;
+    let x = config.page_width_pt / 2.0 - width / 2.0 * cos + half_cap * sin;
+    let y = config.page_height_pt / 2.0 - width / 2.0 * sin - half_cap * cos;
+
+    ops.push(Op::SaveGraphicsState);
+    if let Some(gs) = gs {
+        ops.push(Op::LoadGraphicsState { gs: gs.clone() });
+    }
+    ops.push(Op::StartTextSection);
+    ops.push(Op::SetFontSizeBuiltinFont {
+        size: Pt(size),
+        font: BuiltinFont::Helvetica,
+    });
+    ops.push(Op::SetFillColor {
+        col: Color::Rgb(Rgb {
+            r: watermark.color[0],
+            g: watermark.color[1],
+            b: watermark.color[2],
+            icc_profile: None,
+        }),
+    });
+    ops.push(Op::SetTextMatrix {
+        matrix: TextMatrix::TranslateRotate(Pt(x), Pt(y), watermark.angle),
+    });
+    ops.push(Op::WriteTextBuiltinFont {
+        items: vec![TextItem::Text(to_winlatin(&watermark.text))],
+        font: BuiltinFont::Helvetica,
+    });
+    ops.push(Op::EndTextSection);
+    ops.push(Op::RestoreGraphicsState);
+}
+
+/// Replace the random file identifiers printpdf writes into the trailer's
+/// `/ID` with a hash of the document body. The new strings have the same
+/// length, so the patch leaves every byte offset intact.
+fn set_content_id(pdf: &mut [u8]) {
+    const ID_LEN: usize = 32;
+    let Some(start) = pdf.windows(5).rposition(|w| w == b"/ID[(") else {
+        return;
+    };
+    // 64-bit FNV-1a from two offset bases, for 32 hex digits.
+    let fnv = |basis: u64| {
+        pdf[..start].iter().fold(basis, |hash, &b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    };
+    let id = format!(
+        "{:016X}{:016X}",
+        fnv(0xcbf2_9ce4_8422_2325),
+        fnv(0x6c62_272e_07bb_0142)
+    );
+    let first = start + 5;
+    let second = first + ID_LEN + 2;
+    if pdf.get(first + ID_LEN..second) != Some(&b")("[..]) || pdf.len() < second + ID_LEN {
+        return;
+    }
+    pdf[first..first + ID_LEN].copy_from_slice(id.as_bytes());
+    pdf[second..second + ID_LEN].copy_from_slice(id.as_bytes());
+}
+
+/// Add an `/OpenAction` to the catalog so viewers open the first page at
+/// `zoom`.
+fn set_open_action(doc: &mut lopdf::Document, zoom: ZoomMode) -> Result<(), ForgeError> {
+    use lopdf::Object;
+
+    let first_page = *doc
+        .get_pages()
+        .values()
+        .next()
+        .ok_or_else(|| ForgeError::Render("OpenAction: document has no pages".to_string()))?;
+
+    let page = Object::Reference(first_page);
+    let xyz = |scale: f32| {
+        vec![
+            page.clone(),
+            Object::Name(b"XYZ".to_vec()),
+            Object::Null,
+            Object::Null,
+            Object::Real(scale),
+        ]
+    };
+    let dest = match zoom {
+        ZoomMode::FitPage => vec![page.clone(), Object::Name(b"Fit".to_vec())],
+        ZoomMode::FitWidth => vec![page.clone(), Object::Name(b"FitH".to_vec()), Object::Null],
+        ZoomMode::ActualSize => xyz(1.0),
+        ZoomMode::Percent(p) => xyz(p / 100.0),
+    };
+
+    doc.catalog_mut()
+        .map_err(|e| ForgeError::Render(format!("OpenAction: {e}")))?
+        .set("OpenAction", Object::Array(dest));
+    Ok(())
+}
+
+/// Attach `links[i]` to the `i`-th page as GoTo link annotations.
+fn add_link_annotations(doc: &mut lopdf::Document, links: &[Vec<InternalLink>]) {
+    use lopdf::{dictionary, Object};
+
+    let page_ids: Vec<lopdf::ObjectId> = doc.get_pages().into_values().collect();
+    for (page_id, links) in page_ids.iter().zip(links) {
+        let annots: Vec<Object> = links
+            .iter()
+            .filter_map(|link| {
+                let target = *page_ids.get(link.page)?;
+                let annot = dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "Link",
+                    "Rect" => link.rect.iter().map(|&v| Object::Real(v)).collect::<Vec<_>>(),
+                    "Border" => vec![0.into(), 0.into(), 0.into()],
+                    "A" => dictionary! {
+                        "S" => "GoTo",
+                        "D" => vec![
+                            Object::Reference(target),
+                            "XYZ".into(),
+                            Object::Null,
+                            Object::Real(link.top),
+                            Object::Null,
+                        ],
+                    },
+                };
+                Some(Object::Reference(doc.add_object(annot)))
+            })
+            .collect();
+        if let Ok(page) = doc.get_dictionary_mut(*page_id) {
+            page.set("Annots", annots);
+        }
+    }
+}
 
-    Ok(bytes)
+/// Mark the trimmed page, and the bleed around it, inside every page's
+/// enlarged media box.
+fn set_trim_boxes(doc: &mut lopdf::Document, config: &LayoutConfig, margin: f32) {
+    let rect = |inset: f32| -> Vec<lopdf::Object> {
+        [
+            inset,
+            inset,
+            config.page_width_pt + 2.0 * margin - inset,
+            config.page_height_pt + 2.0 * margin - inset,
+        ]
+        .into_iter()
+        .map(lopdf::Object::Real)
+        .collect()
+    };
+    let page_ids: Vec<lopdf::ObjectId> = doc.get_pages().into_values().collect();
+    for page_id in page_ids {
+        if let Ok(page) = doc.get_dictionary_mut(page_id) {
+            page.set("TrimBox", rect(margin));
+            page.set("BleedBox", rect(margin - config.bleed_pt));
+        }
+    }
 }
 
 /// Convert a UTF-8 string to raw Windows-1252 bytes then wrap in a String so
@@ -132,31 +779,327 @@ fn to_winlatin(s: &str) -> String {
     }
 }
 
+/// Category of problem found while checking an image `src` for embedding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageIssueKind {
+    /// The src is not a `data:` URI (e.g. a URL or file path).
+    NotDataUri,
+    /// The data URI is missing its `,` separator or the `;base64` marker.
+    MalformedDataUri,
+    /// The base64 payload could not be decoded.
+    InvalidBase64,
+    /// The decoded bytes are not an image format we can embed.
+    UnsupportedFormat,
+}
+
+/// A problem with a single `<img>` source, reported by [`preflight_layout_images`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageIssue {
+    /// The offending `src` attribute, verbatim.
+    pub src: String,
+    pub kind: ImageIssueKind,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl ImageIssue {
+    pub(crate) fn new(src: &str, kind: ImageIssueKind, message: impl Into<String>) -> Self {
+        Self {
+            src: src.to_string(),
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ImageIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A problem that did not stop rendering, such as a skipped image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+/// What a [`Warning`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// An image could not be decoded or had no size, and was left out.
+    SkippedImage,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 /// Parse a `data:<mime>;base64,<data>` URI and return the raw decoded bytes.
 ///
 /// Returns `Err` if `src` is not a data URI or does not use base64 encoding.
-fn parse_data_uri(src: &str) -> Result<Vec<u8>, String> {
+pub(crate) fn parse_data_uri(src: &str) -> Result<Vec<u8>, ImageIssue> {
     if !src.starts_with("data:") {
         let preview = if src.len() > 80 { &src[..80] } else { src };
-        return Err(format!(
-            "Image src must be a base64 data URI \
-             (e.g. `data:image/png;base64,...`). Got: {preview:?}"
+        return Err(ImageIssue::new(
+            src,
+            ImageIssueKind::NotDataUri,
+            format!(
+                "Image src must be a base64 data URI \
+                 (e.g. `data:image/png;base64,...`). Got: {preview:?}"
+            ),
         ));
     }
     let rest = &src["data:".len()..];
     let comma_pos = rest.find(',').ok_or_else(|| {
-        "Invalid data URI: missing `,` separator between header and data".to_string()
+        ImageIssue::new(
+            src,
+            ImageIssueKind::MalformedDataUri,
+            "Invalid data URI: missing `,` separator between header and data",
+        )
     })?;
     let header = &rest[..comma_pos];
     if !header.contains(";base64") {
-        return Err("Only base64-encoded data URIs are supported. \
-             The header must contain `;base64` (e.g. `data:image/png;base64,...`)."
-            .to_string());
+        return Err(ImageIssue::new(
+            src,
+            ImageIssueKind::MalformedDataUri,
+            "Only base64-encoded data URIs are supported. \
+             The header must contain `;base64` (e.g. `data:image/png;base64,...`).",
+        ));
     }
     let b64_data = rest[comma_pos + 1..].trim();
-    BASE64_STD
-        .decode(b64_data)
-        .map_err(|e| format!("Base64 decode error: {e}"))
+    BASE64_STD.decode(b64_data).map_err(|e| {
+        ImageIssue::new(
+            src,
+            ImageIssueKind::InvalidBase64,
+            format!("Base64 decode error: {e}"),
+        )
+    })
+}
+
+/// Whether `src` names a local file (a plain path or a `file://` URI) rather
+/// than a data URI or a remote URL.
+pub fn is_local_image_src(src: &str) -> bool {
+    !src.starts_with("data:") && (file_uri_path(src).is_some() || !src.contains("://"))
+}
+
+/// The path named by a `file://` URI, percent-decoded. The host must be
+/// empty or `localhost`; `None` for anything else.
+fn file_uri_path(src: &str) -> Option<String> {
+    let scheme = src.get(..7)?;
+    if !scheme.eq_ignore_ascii_case("file://") {
+        return None;
+    }
+    let rest = &src[7..];
+    let path = match rest.find('/') {
+        Some(0) => rest,
+        Some(i) if rest[..i].eq_ignore_ascii_case("localhost") => &rest[i..],
+        _ => return None,
+    };
+    Some(percent_decode(path))
+}
+
+/// Decode `%XX` escapes; malformed escapes are kept as written.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = s
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Read a local image (a path or `file://` URI) and re-encode it as a base64
+/// data URI. Relative paths are resolved against `base_dir` when given.
+pub fn local_image_to_data_uri(src: &str, base_dir: Option<&Path>) -> Result<String, ForgeError> {
+    let mut path = PathBuf::from(file_uri_path(src).unwrap_or_else(|| src.to_string()));
+    if path.is_relative() {
+        if let Some(dir) = base_dir {
+            path = dir.join(path);
+        }
+    }
+    let bytes = std::fs::read(&path)
+        .map_err(|e| ForgeError::ImageDecode(format!("cannot read '{}': {e}", path.display())))?;
+
+    let is_svg = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+    bytes_to_data_uri(&bytes, is_svg, &path.display().to_string())
+}
+
+/// Upper bound on the size of a downloaded image.
+pub const REMOTE_IMAGE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Connect + read timeout for downloading an image.
+pub const REMOTE_IMAGE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Whether `src` is an `http://` or `https://` URL.
+pub fn is_remote_image_src(src: &str) -> bool {
+    let lower = src.get(..8).unwrap_or(src).to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Download an `http(s)` image and re-encode it as a base64 data URI.
+///
+/// Fails on timeouts, non-2xx responses and bodies larger than
+/// [`REMOTE_IMAGE_MAX_BYTES`].
+#[cfg(feature = "remote-images")]
+pub fn remote_image_to_data_uri(url: &str) -> Result<String, ForgeError> {
+    use std::io::Read as _;
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout(REMOTE_IMAGE_TIMEOUT)
+        .build();
+    let resp = agent
+        .get(url)
+        .call()
+        .map_err(|e| ForgeError::ImageDecode(format!("cannot fetch '{url}': {e}")))?;
+    let is_svg = resp.content_type().eq_ignore_ascii_case("image/svg+xml");
+
+    let mut bytes = Vec::new();
+    resp.into_reader()
+        .take(REMOTE_IMAGE_MAX_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| ForgeError::ImageDecode(format!("cannot fetch '{url}': {e}")))?;
+    if bytes.len() as u64 > REMOTE_IMAGE_MAX_BYTES {
+        return Err(ForgeError::ImageDecode(format!(
+            "'{url}' exceeds the {REMOTE_IMAGE_MAX_BYTES}-byte image limit"
+        )));
+    }
+    bytes_to_data_uri(&bytes, is_svg, url)
+}
+
+/// Without the `remote-images` feature remote sources cannot be fetched.
+#[cfg(not(feature = "remote-images"))]
+pub fn remote_image_to_data_uri(url: &str) -> Result<String, ForgeError> {
+    Err(ForgeError::ImageDecode(format!(
+        "cannot fetch '{url}': built without the `remote-images` feature"
+    )))
+}
+
+fn bytes_to_data_uri(bytes: &[u8], is_svg: bool, name: &str) -> Result<String, ForgeError> {
+    let mime = if is_svg {
+        "image/svg+xml"
+    } else {
+        ::image::guess_format(bytes)
+            .map_err(|e| ForgeError::ImageDecode(format!("unrecognised image '{name}': {e}")))?
+            .to_mime_type()
+    };
+    Ok(format!("data:{mime};base64,{}", BASE64_STD.encode(bytes)))
+}
+
+/// Check that a single image `src` would be embedded by [`render_pdf`].
+///
+/// Runs the same parse → decode steps as the renderer, but discards the
+/// result.
+pub fn check_image_src(src: &str) -> Result<(), ImageIssue> {
+    decode_image(src, svg::DEFAULT_SVG_DPI).map(|_| ())
+}
+
+/// Check every image referenced by a [`LayoutConfig`] and report the ones
+/// [`render_pdf`] would skip.
+///
+/// Issues are returned in document order; each distinct `src` is checked once.
+pub fn preflight_layout_images(config: &LayoutConfig) -> Vec<ImageIssue> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut issues = Vec::new();
+    for page_layout in &config.pages {
+        for lbox in &page_layout.boxes {
+            preflight_box_images(lbox, &mut seen, &mut issues);
+        }
+    }
+    issues
+}
+
+fn preflight_box_images<'a>(
+    lbox: &'a LayoutBox,
+    seen: &mut HashSet<&'a str>,
+    issues: &mut Vec<ImageIssue>,
+) {
+    if let Some(img) = &lbox.image {
+        if seen.insert(img.src.as_str()) {
+            if let Err(issue) = check_image_src(&img.src) {
+                issues.push(issue);
+            }
+        }
+    }
+    for child in &lbox.children {
+        preflight_box_images(child, seen, issues);
+    }
+}
+
+/// Page index and top edge of the first box carrying each anchor name.
+fn anchor_positions(pages: &[PageLayout]) -> HashMap<&str, (usize, f32)> {
+    fn collect<'a>(lbox: &'a LayoutBox, page: usize, anchors: &mut HashMap<&'a str, (usize, f32)>) {
+        for name in &lbox.anchors {
+            anchors.entry(name.as_str()).or_insert((page, lbox.y));
+        }
+        for child in &lbox.children {
+            collect(child, page, anchors);
+        }
+    }
+    let mut anchors = HashMap::new();
+    for (page, layout) in pages.iter().enumerate() {
+        for lbox in &layout.boxes {
+            collect(lbox, page, &mut anchors);
+        }
+    }
+    anchors
+}
+
+/// A clickable area that jumps to another page of the document.
+struct InternalLink {
+    /// `[left, bottom, right, top]` in PDF user space.
+    rect: [f32; 4],
+    page: usize,
+    /// Destination y in PDF user space (from the page bottom).
+    top: f32,
+}
+
+/// Collect a link for every box whose `#name` link resolves to an anchor in
+/// `anchors`. Other links are left inactive.
+fn collect_links(
+    lbox: &LayoutBox,
+    anchors: &HashMap<&str, (usize, f32)>,
+    page_h: f32,
+    links: &mut Vec<InternalLink>,
+) {
+    let target = lbox
+        .link
+        .as_deref()
+        .and_then(|href| href.strip_prefix('#'))
+        .and_then(|name| anchors.get(name));
+    if let Some(&(page, top)) = target {
+        links.push(InternalLink {
+            rect: [
+                lbox.x,
+                page_h - lbox.y - lbox.height,
+                lbox.x + lbox.width,
+                page_h - lbox.y,
+            ],
+            page,
+            top: page_h - top,
+        });
+    }
+    for child in &lbox.children {
+        collect_links(child, anchors, page_h, links);
+    }
 }
 
 /// Recursively collect all unique `image.src` strings from a [`LayoutBox`] tree.
@@ -169,19 +1112,340 @@ fn collect_image_srcs<'a>(lbox: &'a LayoutBox, srcs: &mut HashSet<&'a str>) {
     }
 }
 
+/// Recursively collect the distinct gradient fills of a [`LayoutBox`] tree,
+/// ordered by key so sequential resource IDs are reproducible.
+fn collect_gradient_fills(lbox: &LayoutBox, fills: &mut BTreeMap<GradientKey, GradientFill>) {
+    if let Some(fill) = GradientFill::of(lbox) {
+        fills.entry(fill.key()).or_insert(fill);
+    }
+    for child in &lbox.children {
+        collect_gradient_fills(child, fills);
+    }
+}
+
+/// Font size of the `alt` text drawn for an image that was skipped.
+const ALT_TEXT_SIZE: f32 = 10.0;
+
+/// Draw an image's `alt` text at the top-left of its box in grey Helvetica,
+/// so a skipped image still says what it showed.
+fn draw_alt_text(ops: &mut Vec<Op>, lbox: &LayoutBox, page_height: f32, alt: &str) {
+    ops.push(Op::StartTextSection);
+    ops.push(Op::SetTextCursor {
+        pos: Point {
+            x: Pt(lbox.x),
+            y: Pt(page_height - lbox.y - ALT_TEXT_SIZE),
+        },
+    });
+    ops.push(Op::SetFontSizeBuiltinFont {
+        size: Pt(ALT_TEXT_SIZE),
+        font: BuiltinFont::Helvetica,
+    });
+    ops.push(Op::SetFillColor {
+        col: Color::Rgb(Rgb {
+            r: 0.4,
+            g: 0.4,
+            b: 0.4,
+            icc_profile: None,
+        }),
+    });
+    ops.push(Op::WriteTextBuiltinFont {
+        items: vec![TextItem::Text(to_winlatin(alt))],
+        font: BuiltinFont::Helvetica,
+    });
+    ops.push(Op::EndTextSection);
+}
+
+/// Place a `px_w` × `px_h` image in a `box_w` × `box_h` box according to
+/// `fit`, returning `(dx, dy, width, height)` relative to the box's top-left.
+fn fit_image(box_w: f32, box_h: f32, px_w: f32, px_h: f32, fit: ObjectFit) -> (f32, f32, f32, f32) {
+    let scale = match fit {
+        ObjectFit::Fill => return (0.0, 0.0, box_w, box_h),
+        ObjectFit::Contain => (box_w / px_w).min(box_h / px_h),
+        ObjectFit::Cover => (box_w / px_w).max(box_h / px_h),
+    };
+    let (w, h) = (px_w * scale, px_h * scale);
+    ((box_w - w) / 2.0, (box_h - h) / 2.0, w, h)
+}
+
+/// Corner points of an axis-aligned rectangle in PDF coordinates.
+fn rect_points(x: f32, bottom: f32, width: f32, height: f32) -> Vec<LinePoint> {
+    [
+        (x, bottom),
+        (x + width, bottom),
+        (x + width, bottom + height),
+        (x, bottom + height),
+    ]
+    .into_iter()
+    .map(|(x, y)| LinePoint {
+        p: Point { x: Pt(x), y: Pt(y) },
+        bezier: false,
+    })
+    .collect()
+}
+
+/// A box's gradient background: the shading's geometry depends on the box
+/// size as well as the gradient, so both identify its form XObject.
+#[derive(Debug, Clone, Copy)]
+struct GradientFill {
+    gradient: LinearGradient,
+    width: f32,
+    height: f32,
+}
+
+/// The bit patterns of a [`GradientFill`]'s numbers, hashable and ordered.
+type GradientKey = [u32; 11];
+
+impl GradientFill {
+    /// The gradient background of `lbox`, if it has one and a visible size.
+    fn of(lbox: &LayoutBox) -> Option<Self> {
+        let gradient = lbox.background_gradient?;
+        (lbox.width > 0.0 && lbox.height > 0.0).then_some(Self {
+            gradient,
+            width: lbox.width,
+            height: lbox.height,
+        })
+    }
+
+    fn key(&self) -> GradientKey {
+        let g = &self.gradient;
+        [
+            g.angle,
+            g.from[0],
+            g.from[1],
+            g.from[2],
+            g.from[3],
+            g.to[0],
+            g.to[1],
+            g.to[2],
+            g.to[3],
+            self.width,
+            self.height,
+        ]
+        .map(f32::to_bits)
+    }
+}
+
+/// A `width` × `height` form XObject filled with an axial shading for
+/// `fill`'s gradient. printpdf has no shading ops, so the form is written as
+/// a raw stream; its `/BBox` clips the shading to the box.
+fn gradient_xobject(fill: &GradientFill) -> ExternalXObject {
+    use DictItem::{Array, Dict, Int, Name, Real};
+
+    let GradientFill {
+        gradient,
+        width,
+        height,
+    } = fill;
+    let (width, height) = (*width, *height);
+
+    // CSS angles run clockwise from "to top"; in PDF space (y up) the
+    // direction is (sin θ, cos θ). The gradient line spans the box corners.
+    let (sin, cos) = gradient.angle.to_radians().sin_cos();
+    let half = (width * sin.abs() + height * cos.abs()) / 2.0;
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let coords = [
+        cx - sin * half,
+        cy - cos * half,
+        cx + sin * half,
+        cy + cos * half,
+    ];
+
+    let name = |n: &str| Name(n.as_bytes().to_vec());
+    let reals = |vals: &[f32]| Array(vals.iter().copied().map(Real).collect());
+    let dict = |entries: Vec<(&str, DictItem)>| Dict {
+        map: entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    };
+
+    let function = dict(vec![
+        ("FunctionType", Int(2)),
+        ("Domain", reals(&[0.0, 1.0])),
+        ("C0", reals(&gradient.from[..3])),
+        ("C1", reals(&gradient.to[..3])),
+        ("N", Int(1)),
+    ]);
+    let shading = dict(vec![
+        ("ShadingType", Int(2)),
+        ("ColorSpace", name("DeviceRGB")),
+        ("Coords", reals(&coords)),
+        ("Function", function),
+        (
+            "Extend",
+            Array(vec![DictItem::Bool(true), DictItem::Bool(true)]),
+        ),
+    ]);
+    let resources = dict(vec![("Shading", dict(vec![("Sh0", shading)]))]);
+
+    let stream_dict = [
+        ("Type", name("XObject")),
+        ("Subtype", name("Form")),
+        ("BBox", reals(&[0.0, 0.0, width, height])),
+        ("Resources", resources),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v))
+    .collect();
+
+    ExternalXObject {
+        stream: ExternalStream {
+            dict: stream_dict,
+            content: b"/Sh0 sh".to_vec(),
+            compress: false,
+        },
+        width: None,
+        height: None,
+        dpi: None,
+    }
+}
+
+/// Stroke a 0.5 pt underline or strike-through `width` long from (`x`, `y`).
+fn draw_decoration(ops: &mut Vec<Op>, x: f32, width: f32, y: f32, color: [f32; 4]) {
+    ops.push(Op::SetOutlineThickness { pt: Pt(0.5) });
+    ops.push(Op::SetOutlineColor {
+        col: Color::Rgb(Rgb {
+            r: color[0],
+            g: color[1],
+            b: color[2],
+            icc_profile: None,
+        }),
+    });
+    ops.push(Op::DrawLine {
+        line: Line {
+            points: vec![
+                LinePoint {
+                    p: Point { x: Pt(x), y: Pt(y) },
+                    bezier: false,
+                },
+                LinePoint {
+                    p: Point {
+                        x: Pt(x + width),
+                        y: Pt(y),
+                    },
+                    bezier: false,
+                },
+            ],
+            is_closed: false,
+        },
+    });
+}
+
+/// Draw a nested-list marker that WinAnsi Helvetica has no glyph for: an
+/// outlined circle (`Stroke`) or a filled square (`Fill`), sized and placed
+/// like the `•` bullet on the baseline at (`x`, `baseline`).
+fn draw_marker_shape(
+    ops: &mut Vec<Op>,
+    x: f32,
+    baseline: f32,
+    font_size: f32,
+    color: [f32; 4],
+    mode: PaintMode,
+) {
+    let col = Color::Rgb(Rgb {
+        r: color[0],
+        g: color[1],
+        b: color[2],
+        icc_profile: None,
+    });
+    let size = font_size * 0.3;
+    let (cx, cy) = (x + font_size * 0.175, baseline + font_size * 0.33);
+    let points = if mode == PaintMode::Stroke {
+        ops.push(Op::SetOutlineColor { col });
+        ops.push(Op::SetOutlineThickness {
+            pt: Pt(font_size * 0.06),
+        });
+        const SEGMENTS: usize = 16;
+        (0..SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / SEGMENTS as f32;
+                LinePoint {
+                    p: Point {
+                        x: Pt(cx + size / 2.0 * angle.cos()),
+                        y: Pt(cy + size / 2.0 * angle.sin()),
+                    },
+                    bezier: false,
+                }
+            })
+            .collect()
+    } else {
+        ops.push(Op::SetFillColor { col });
+        rect_points(cx - size / 2.0, cy - size / 2.0, size, size)
+    };
+    ops.push(Op::DrawPolygon {
+        polygon: Polygon {
+            rings: vec![PolygonRing { points }],
+            mode,
+            winding_order: WindingOrder::NonZero,
+        },
+    });
+}
+
+/// Stroke one border edge from `from` to `to` (PDF coordinates).
+fn stroke_segment(ops: &mut Vec<Op>, from: (f32, f32), to: (f32, f32), edge: BorderEdge) {
+    ops.push(Op::SetOutlineColor {
+        col: Color::Rgb(Rgb {
+            r: edge.color[0],
+            g: edge.color[1],
+            b: edge.color[2],
+            icc_profile: None,
+        }),
+    });
+    ops.push(Op::SetOutlineThickness { pt: Pt(edge.width) });
+    ops.push(Op::DrawLine {
+        line: Line {
+            points: [from, to]
+                .into_iter()
+                .map(|(x, y)| LinePoint {
+                    p: Point { x: Pt(x), y: Pt(y) },
+                    bezier: false,
+                })
+                .collect(),
+            is_closed: false,
+        },
+    });
+}
+
 /// Recursively render a LayoutBox and its children into PDF ops.
 fn render_box(
     ops: &mut Vec<Op>,
     lbox: &LayoutBox,
     page_height: f32,
     images: &HashMap<String, ImageResource>,
+    fonts: &EmbeddedFonts,
+    resources: &mut PageResources,
 ) {
+    // Horizontal scale: x' = origin + (x - origin) * factor, wrapping every
+    // op for this box and its descendants.
+    if let Some(scale) = &lbox.scale_x {
+        ops.push(Op::SaveGraphicsState);
+        ops.push(Op::SetTransformationMatrix {
+            matrix: CurTransMat::Raw([
+                scale.factor,
+                0.0,
+                0.0,
+                1.0,
+                scale.origin_x * (1.0 - scale.factor),
+                0.0,
+            ]),
+        });
+    }
+
     // PDF coordinate system: origin at bottom-left.
     // Our layout uses origin at top-left. Convert:
     let pdf_y = page_height - lbox.y;
 
-    // Background
-    if let Some(bg) = &lbox.background_color {
+    // A hidden box keeps its place but draws nothing of its own; its
+    // children still draw unless they are hidden too.
+    let visible = !lbox.hidden;
+
+    // Background – a gradient is painted over a flat fill of its first
+    // stop, which viewers without shading support show instead.
+    let fill = lbox
+        .background_color
+        .or(lbox.background_gradient.map(|g| g.from))
+        .filter(|_| visible);
+    if let Some(bg) = &fill {
         ops.push(Op::SetFillColor {
             col: Color::Rgb(Rgb {
                 r: bg[0],
@@ -237,8 +1501,41 @@ fn render_box(
         });
     }
 
-    // Border
-    if let Some(border) = &lbox.border {
+    if let Some(fill) = GradientFill::of(lbox).filter(|_| visible) {
+        let id = resources.gradient(&fill);
+        ops.push(Op::UseXobject {
+            id,
+            transform: XObjectTransform {
+                translate_x: Some(Pt(lbox.x)),
+                translate_y: Some(Pt(pdf_y - lbox.height)),
+                ..Default::default()
+            },
+        });
+    }
+
+    // Border – individual edges are stroked just inside the box so thick
+    // accents do not spill onto neighbouring content.
+    let border = lbox.border.as_ref().filter(|_| visible);
+    if let Some(sides) = border.and_then(|b| b.sides.as_ref()) {
+        let (left, right) = (lbox.x, lbox.x + lbox.width);
+        let (bottom, top) = (pdf_y - lbox.height, pdf_y);
+        if let Some(e) = sides.top {
+            let y = top - e.width / 2.0;
+            stroke_segment(ops, (left, y), (right, y), e);
+        }
+        if let Some(e) = sides.right {
+            let x = right - e.width / 2.0;
+            stroke_segment(ops, (x, top), (x, bottom), e);
+        }
+        if let Some(e) = sides.bottom {
+            let y = bottom + e.width / 2.0;
+            stroke_segment(ops, (left, y), (right, y), e);
+        }
+        if let Some(e) = sides.left {
+            let x = left + e.width / 2.0;
+            stroke_segment(ops, (x, top), (x, bottom), e);
+        }
+    } else if let Some(border) = border {
         ops.push(Op::SetOutlineColor {
             col: Color::Rgb(Rgb {
                 r: border.color[0],
@@ -293,160 +1590,285 @@ fn render_box(
         });
     }
 
+    // Clip everything after the background and border to the box.
+    if lbox.clip {
+        ops.push(Op::SaveGraphicsState);
+        ops.push(Op::DrawPolygon {
+            polygon: Polygon {
+                rings: vec![PolygonRing {
+                    points: rect_points(lbox.x, pdf_y - lbox.height, lbox.width, lbox.height),
+                }],
+                mode: PaintMode::Clip,
+                winding_order: WindingOrder::NonZero,
+            },
+        });
+    }
+
     // Text
-    if let Some(text) = &lbox.text {
-        let font = match (text.bold, text.italic) {
-            (true, true) => BuiltinFont::HelveticaBoldOblique,
-            (true, false) => BuiltinFont::HelveticaBold,
-            (false, true) => BuiltinFont::HelveticaOblique,
-            (false, false) => BuiltinFont::Helvetica,
+    if let Some(text) = lbox.text.as_ref().filter(|_| visible) {
+        let run_font = |bold: bool, italic: bool| {
+            let builtin = match (bold, italic) {
+                (true, true) => BuiltinFont::HelveticaBoldOblique,
+                (true, false) => BuiltinFont::HelveticaBold,
+                (false, true) => BuiltinFont::HelveticaOblique,
+                (false, false) => BuiltinFont::Helvetica,
+            };
+            (builtin, fonts.lookup(&text.font_family, bold, italic))
         };
+        let ascender_offset =
+            fonts.baseline_offset(&text.font_family, text.bold, text.italic, text.font_size);
 
         for tline in &text.lines {
             if tline.text.is_empty() {
                 continue;
             }
             let text_x = lbox.x + tline.x_offset;
-            // Baseline ≈ top of line + ascender (approx 0.75 × font_size)
-            let ascender_offset = text.font_size * 0.75;
             let text_y = pdf_y - tline.y_offset - ascender_offset;
 
-            ops.push(Op::StartTextSection);
-            ops.push(Op::SetTextCursor {
-                pos: Point {
-                    x: Pt(text_x),
-                    y: Pt(text_y),
-                },
-            });
-            ops.push(Op::SetFontSizeBuiltinFont {
-                size: Pt(text.font_size),
-                font,
-            });
-            ops.push(Op::SetLineHeight {
-                lh: Pt(text.line_height),
-            });
-            ops.push(Op::SetFillColor {
-                col: Color::Rgb(Rgb {
-                    r: text.color[0],
-                    g: text.color[1],
-                    b: text.color[2],
-                    icc_profile: None,
-                }),
-            });
-            ops.push(Op::WriteTextBuiltinFont {
-                items: vec![TextItem::Text(to_winlatin(&tline.text))],
-                font,
-            });
-            ops.push(Op::EndTextSection);
+            // A line without runs is a single run in the box's style.
+            let whole_line = [TextRun {
+                text: tline.text.clone(),
+                x_offset: 0.0,
+                font_size: text.font_size,
+                baseline_shift: 0.0,
+                bold: None,
+                italic: None,
+                color: None,
+                underline: None,
+                line_through: None,
+                background: None,
+                letter_spacing: None,
+                width: lbox.width,
+            }];
+            let runs = if tline.runs.is_empty() {
+                &whole_line[..]
+            } else {
+                &tline.runs[..]
+            };
+
+            // Highlights fill the line's height behind their runs; they are
+            // all painted first so no glyph is covered by a later one.
+            let line_top = pdf_y - tline.y_offset;
+            for run in runs {
+                if let Some(bg) = run.background {
+                    ops.push(Op::SetFillColor {
+                        col: Color::Rgb(Rgb {
+                            r: bg[0],
+                            g: bg[1],
+                            b: bg[2],
+                            icc_profile: None,
+                        }),
+                    });
+                    ops.push(Op::DrawPolygon {
+                        polygon: Polygon {
+                            rings: vec![PolygonRing {
+                                points: rect_points(
+                                    text_x + run.x_offset,
+                                    line_top - text.line_height,
+                                    run.width,
+                                    text.line_height,
+                                ),
+                            }],
+                            mode: PaintMode::Fill,
+                            winding_order: WindingOrder::NonZero,
+                        },
+                    });
+                }
+            }
 
-            // Underline
-            if text.underline {
-                let underline_y = text_y - text.font_size * 0.1;
-                ops.push(Op::SetOutlineThickness { pt: Pt(0.5) });
-                ops.push(Op::SetOutlineColor {
+            for run in runs {
+                let (font, embedded) = run_font(
+                    run.bold.unwrap_or(text.bold),
+                    run.italic.unwrap_or(text.italic),
+                );
+                ops.push(Op::StartTextSection);
+                ops.push(Op::SetTextCursor {
+                    pos: Point {
+                        x: Pt(text_x + run.x_offset),
+                        y: Pt(text_y + run.baseline_shift),
+                    },
+                });
+                ops.push(match embedded {
+                    Some(embedded) => Op::SetFontSize {
+                        size: Pt(run.font_size),
+                        font: embedded.id.clone(),
+                    },
+                    None => Op::SetFontSizeBuiltinFont {
+                        size: Pt(run.font_size),
+                        font,
+                    },
+                });
+                ops.push(Op::SetLineHeight {
+                    lh: Pt(text.line_height),
+                });
+                let color = run.color.unwrap_or(text.color);
+                ops.push(Op::SetFillColor {
                     col: Color::Rgb(Rgb {
-                        r: text.color[0],
-                        g: text.color[1],
-                        b: text.color[2],
+                        r: color[0],
+                        g: color[1],
+                        b: color[2],
                         icc_profile: None,
                     }),
                 });
-                ops.push(Op::DrawLine {
-                    line: Line {
-                        points: vec![
-                            LinePoint {
-                                p: Point {
-                                    x: Pt(text_x),
-                                    y: Pt(underline_y),
+                // Character spacing is text state that outlives the section,
+                // so it is reset after every spaced run.
+                let spacing = run.letter_spacing.unwrap_or(text.letter_spacing);
+                if spacing != 0.0 {
+                    ops.push(Op::SetCharacterSpacing {
+                        multiplier: spacing,
+                    });
+                }
+                match embedded {
+                    // Embedded fonts may lack a no-break space glyph, and it
+                    // is measured as a space anyway.
+                    Some(embedded) => {
+                        let text = run.text.replace(NBSP, " ");
+                        for piece in apply_ligatures(&text, &embedded.ligatures) {
+                            ops.push(match piece {
+                                Shaped::Text(text) => Op::WriteText {
+                                    items: vec![TextItem::Text(text.to_string())],
+                                    font: embedded.id.clone(),
                                 },
-                                bezier: false,
-                            },
-                            LinePoint {
-                                p: Point {
-                                    x: Pt(text_x + lbox.width),
-                                    y: Pt(underline_y),
+                                Shaped::Ligature(lig) => Op::WriteCodepoints {
+                                    font: embedded.id.clone(),
+                                    cp: vec![(lig.glyph, lig.ch)],
                                 },
-                                bezier: false,
-                            },
-                        ],
-                        is_closed: false,
-                    },
-                });
+                            });
+                        }
+                    }
+                    None => ops.push(Op::WriteTextBuiltinFont {
+                        items: vec![TextItem::Text(to_winlatin(&run.text))],
+                        font,
+                    }),
+                }
+                if spacing != 0.0 {
+                    ops.push(Op::SetCharacterSpacing { multiplier: 0.0 });
+                }
+                ops.push(Op::EndTextSection);
+
+                // Underline and strike-through, in the run's colour.
+                let run_x = text_x + run.x_offset;
+                let baseline = text_y + run.baseline_shift;
+                if run.underline.unwrap_or(text.underline) {
+                    let y = baseline - run.font_size * 0.1;
+                    draw_decoration(ops, run_x, run.width, y, color);
+                }
+                if run.line_through.unwrap_or(text.line_through) {
+                    let y = baseline + run.font_size * 0.3;
+                    draw_decoration(ops, run_x, run.width, y, color);
+                }
             }
         }
 
         // List marker
         if let Some(marker) = &text.list_marker {
-            let marker_x = lbox.x - 16.0;
-            let marker_y = pdf_y - text.font_size * 0.75;
-            ops.push(Op::StartTextSection);
-            ops.push(Op::SetTextCursor {
-                pos: Point {
-                    x: Pt(marker_x),
-                    y: Pt(marker_y),
-                },
-            });
-            ops.push(Op::SetFontSizeBuiltinFont {
-                size: Pt(text.font_size),
-                font: BuiltinFont::Helvetica,
-            });
-            ops.push(Op::SetFillColor {
-                col: Color::Rgb(Rgb {
-                    r: text.color[0],
-                    g: text.color[1],
-                    b: text.color[2],
-                    icc_profile: None,
-                }),
-            });
-            ops.push(Op::WriteTextBuiltinFont {
-                items: vec![TextItem::Text(to_winlatin(marker))],
-                font: BuiltinFont::Helvetica,
-            });
-            ops.push(Op::EndTextSection);
+            let marker_x = lbox.x - text.list_marker_offset;
+            let marker_y = pdf_y - ascender_offset;
+            let shape = match marker.trim_end() {
+                "\u{25E6}" => Some(PaintMode::Stroke),
+                "\u{25AA}" => Some(PaintMode::Fill),
+                _ => None,
+            };
+            if let Some(mode) = shape {
+                draw_marker_shape(ops, marker_x, marker_y, text.font_size, text.color, mode);
+            } else {
+                ops.push(Op::StartTextSection);
+                ops.push(Op::SetTextCursor {
+                    pos: Point {
+                        x: Pt(marker_x),
+                        y: Pt(marker_y),
+                    },
+                });
+                ops.push(Op::SetFontSizeBuiltinFont {
+                    size: Pt(text.font_size),
+                    font: BuiltinFont::Helvetica,
+                });
+                ops.push(Op::SetFillColor {
+                    col: Color::Rgb(Rgb {
+                        r: text.color[0],
+                        g: text.color[1],
+                        b: text.color[2],
+                        icc_profile: None,
+                    }),
+                });
+                ops.push(Op::WriteTextBuiltinFont {
+                    items: vec![TextItem::Text(to_winlatin(marker))],
+                    font: BuiltinFont::Helvetica,
+                });
+                ops.push(Op::EndTextSection);
+            }
         }
     }
 
     // Image – embed from pre-registered XObject
-    if let Some(img) = &lbox.image {
+    if let Some(img) = lbox.image.as_ref().filter(|_| visible) {
+        let embeddable = images
+            .get(&img.src)
+            .filter(|res| res.px_width > 0 && res.px_height > 0);
+        if let (None, Some(alt)) = (embeddable, &img.alt) {
+            draw_alt_text(ops, lbox, page_height, alt);
+        }
         if let Some(res) = images.get(&img.src) {
             let px_w = res.px_width as f32;
             let px_h = res.px_height as f32;
             if px_w <= 0.0 || px_h <= 0.0 {
-                log::warn!("Skipping image — zero intrinsic dimensions");
+                resources.warn(
+                    WarningKind::SkippedImage,
+                    "Skipping image — zero intrinsic dimensions".to_string(),
+                );
             } else {
                 // Determine render dimensions. If the layout gave us a zero
                 // width or height (e.g. because no CSS size was specified and
                 // the intrinsic resolution fallback in layout.rs couldn't run
-                // for non-data-URI sources), fall back to the intrinsic pixel
-                // size at 72 dpi (1 px = 1 pt).
+                // for non-data-URI sources), fall back to the intrinsic size
+                // at the image DPI.
                 let asp = px_w / px_h;
                 let render_w = if img.width > 0.0 {
                     img.width
                 } else if img.height > 0.0 {
                     img.height * asp
                 } else {
-                    px_w // intrinsic fallback
+                    res.width // intrinsic fallback
                 };
                 let render_h = if img.height > 0.0 {
                     img.height
                 } else if img.width > 0.0 {
                     img.width / asp
                 } else {
-                    px_h // intrinsic fallback
+                    res.height // intrinsic fallback
                 };
 
+                let (dx, dy, draw_w, draw_h) =
+                    fit_image(render_w, render_h, px_w, px_h, img.object_fit);
+
                 // PDF origin is bottom-left; our layout origin is top-left.
-                let img_bottom_y = page_height - lbox.y - render_h;
+                let box_bottom_y = page_height - lbox.y - render_h;
+                let img_bottom_y = page_height - lbox.y - dy - draw_h;
+
+                // `cover` overflows the box; clip the overflow away.
+                let clip = img.object_fit == ObjectFit::Cover;
+                if clip {
+                    ops.push(Op::SaveGraphicsState);
+                    ops.push(Op::DrawPolygon {
+                        polygon: Polygon {
+                            rings: vec![PolygonRing {
+                                points: rect_points(lbox.x, box_bottom_y, render_w, render_h),
+                            }],
+                            mode: PaintMode::Clip,
+                            winding_order: WindingOrder::NonZero,
+                        },
+                    });
+                }
 
                 // At dpi=72 printpdf renders 1 px = 1 pt, so
                 // scale = desired_pt / px_dim.
-                let scale_x = render_w / px_w;
-                let scale_y = render_h / px_h;
+                let scale_x = draw_w / px_w;
+                let scale_y = draw_h / px_h;
 
                 ops.push(Op::UseXobject {
                     id: res.xobj_id.clone(),
                     transform: XObjectTransform {
-                        translate_x: Some(Pt(lbox.x)),
+                        translate_x: Some(Pt(lbox.x + dx)),
                         translate_y: Some(Pt(img_bottom_y)),
                         dpi: Some(72.0),
                         scale_x: Some(scale_x),
@@ -454,13 +1876,25 @@ fn render_box(
                         rotate: None,
                     },
                 });
+
+                if clip {
+                    ops.push(Op::RestoreGraphicsState);
+                }
             }
         }
     }
 
     // Children
     for child in &lbox.children {
-        render_box(ops, child, page_height, images);
+        render_box(ops, child, page_height, images, fonts, resources);
+    }
+
+    if lbox.clip {
+        ops.push(Op::RestoreGraphicsState);
+    }
+
+    if lbox.scale_x.is_some() {
+        ops.push(Op::RestoreGraphicsState);
     }
 }
 
@@ -468,6 +1902,804 @@ fn render_box(
 mod tests {
     use super::*;
 
+    const PNG_1X1: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn file_uris_are_decoded_and_accept_localhost() {
+        let path = |src| file_uri_path(src);
+        assert_eq!(
+            path("file:///tmp/my%20logo.png").as_deref(),
+            Some("/tmp/my logo.png")
+        );
+        assert_eq!(
+            path("file://localhost/tmp/a.png").as_deref(),
+            Some("/tmp/a.png")
+        );
+        assert_eq!(path("FILE:///100%.png").as_deref(), Some("/100%.png"));
+        assert_eq!(path("file://example.com/a.png"), None);
+        assert!(!is_local_image_src("file://example.com/a.png"));
+        assert!(is_local_image_src("logo%20v2.png"));
+    }
+
+    #[test]
+    fn check_image_src_classifies_problems() {
+        assert!(check_image_src(PNG_1X1).is_ok());
+        let kind = |src: &str| check_image_src(src).unwrap_err().kind;
+        assert_eq!(kind("logo.png"), ImageIssueKind::NotDataUri);
+        assert_eq!(kind("data:image/png,abc"), ImageIssueKind::MalformedDataUri);
+        assert_eq!(
+            kind("data:image/png;base64,!!!"),
+            ImageIssueKind::InvalidBase64
+        );
+        assert_eq!(
+            kind("data:image/png;base64,aGVsbG8="),
+            ImageIssueKind::UnsupportedFormat
+        );
+    }
+
+    #[test]
+    fn gradient_background_paints_a_shading() {
+        let mut lbox = LayoutBox::new(40.0, 40.0, 200.0, 100.0);
+        lbox.background_gradient = Some(LinearGradient {
+            angle: 90.0,
+            from: [1.0, 0.0, 0.0, 1.0],
+            to: [0.0, 0.0, 1.0, 1.0],
+        });
+        let mut config = LayoutConfig::a4();
+        config.pages.push(PageLayout {
+            page_index: 0,
+            boxes: vec![lbox],
+        });
+
+        let pdf = render_pdf(&config).unwrap();
+        let doc = lopdf::Document::load_mem(&pdf).unwrap();
+        let form = doc
+            .objects
+            .values()
+            .filter_map(|obj| obj.as_stream().ok())
+            .find(|stream| {
+                stream.dict.get(b"Subtype").and_then(|s| s.as_name()).ok() == Some(b"Form")
+            })
+            .expect("gradient form XObject");
+        let shading = form
+            .dict
+            .get_deref(b"Resources", &doc)
+            .and_then(|r| r.as_dict())
+            .and_then(|r| r.get_deref(b"Shading", &doc))
+            .and_then(|s| s.as_dict())
+            .and_then(|s| s.get_deref(b"Sh0", &doc))
+            .and_then(|s| s.as_dict())
+            .unwrap();
+        assert_eq!(shading.get(b"ShadingType").unwrap().as_i64().unwrap(), 2);
+        let ops = form.decode_content().unwrap().operations;
+        assert!(ops.iter().any(|op| op.operator == "sh"));
+    }
+
+    #[test]
+    fn equal_gradients_share_one_form() {
+        let gradient = LinearGradient {
+            angle: 90.0,
+            from: [1.0, 0.0, 0.0, 1.0],
+            to: [0.0, 0.0, 1.0, 1.0],
+        };
+        let mut config = LayoutConfig::a4();
+        for (page_index, y) in [(0, 40.0), (0, 200.0), (1, 40.0)] {
+            if config.pages.len() == page_index {
+                config.pages.push(PageLayout {
+                    page_index,
+                    boxes: Vec::new(),
+                });
+            }
+            let mut lbox = LayoutBox::new(40.0, y, 200.0, 100.0);
+            lbox.background_gradient = Some(gradient);
+            config.pages[page_index].boxes.push(lbox);
+        }
+        let mut taller = LayoutBox::new(40.0, 400.0, 200.0, 150.0);
+        taller.background_gradient = Some(gradient);
+        config.pages[1].boxes.push(taller);
+
+        let pdf = render_pdf(&config).unwrap();
+        let doc = lopdf::Document::load_mem(&pdf).unwrap();
+        let forms = doc
+            .objects
+            .values()
+            .filter_map(|obj| obj.as_stream().ok())
+            .filter(|stream| {
+                stream.dict.get(b"Subtype").and_then(|s| s.as_name()).ok() == Some(b"Form")
+            })
+            .count();
+        assert_eq!(forms, 2, "one form per distinct gradient and size");
+    }
+
+    #[test]
+    fn blockquote_draws_a_left_bar_only() {
+        let config = crate::pipeline::compute_layout_config(
+            "<blockquote>Quoted text</blockquote>",
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let quote = &config.pages[0].boxes[0];
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            quote,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+
+        let lines: Vec<&Line> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::DrawLine { line } => Some(line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(lines.len(), 1, "only the left edge is stroked");
+        let xs: Vec<f32> = lines[0].points.iter().map(|p| p.p.x.0).collect();
+        let ys: Vec<f32> = lines[0].points.iter().map(|p| p.p.y.0).collect();
+        assert_eq!(xs[0], xs[1], "the bar is vertical");
+        assert!(xs[0] > quote.x && xs[0] < quote.x + 3.0);
+        assert!((ys[0] - ys[1]).abs() >= quote.height - 0.5);
+        assert!(quote.children[0].text.as_ref().unwrap().italic);
+    }
+
+    #[test]
+    fn overflow_hidden_clips_children() {
+        let config = crate::pipeline::compute_layout_config(
+            r#"<div class="overflow-hidden" style="width: 80px">Averyveryverylongunbreakableword</div>"#,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let card = &config.pages[0].boxes[0];
+        assert!(card.clip);
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            card,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+
+        let clip = ops
+            .iter()
+            .position(
+                |op| matches!(op, Op::DrawPolygon { polygon } if polygon.mode == PaintMode::Clip),
+            )
+            .expect("clip path");
+        let text = ops
+            .iter()
+            .position(|op| matches!(op, Op::StartTextSection))
+            .expect("text");
+        assert!(matches!(ops[clip - 1], Op::SaveGraphicsState));
+        assert!(clip < text);
+        assert!(matches!(ops.last(), Some(Op::RestoreGraphicsState)));
+        let Op::DrawPolygon { polygon } = &ops[clip] else {
+            unreachable!()
+        };
+        let xs: Vec<f32> = polygon.rings[0].points.iter().map(|p| p.p.x.0).collect();
+        assert_eq!(xs.iter().cloned().fold(f32::MAX, f32::min), card.x);
+        assert_eq!(
+            xs.iter().cloned().fold(f32::MIN, f32::max),
+            card.x + card.width
+        );
+    }
+
+    #[test]
+    fn invisible_box_keeps_its_space_but_draws_nothing() {
+        let html = r#"<div class="invisible bg-red-500 border p-4">Hidden</div><p>After</p>"#;
+        let config = crate::pipeline::compute_layout_config(
+            html,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let shown = crate::pipeline::compute_layout_config(
+            &html.replace("invisible ", ""),
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let (hidden, after) = (&config.pages[0].boxes[0], &config.pages[0].boxes[1]);
+        assert!(hidden.hidden);
+        // The paragraph sits where it would below the visible box.
+        assert_eq!(after.y, shown.pages[0].boxes[1].y);
+        assert!(after.y >= hidden.y + hidden.height);
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            hidden,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        assert!(ops.is_empty(), "{ops:?}");
+    }
+
+    #[test]
+    fn colspan_header_draws_one_continuous_top_border() {
+        let config = crate::pipeline::compute_layout_config(
+            r#"<table><tr><th colspan="2">Head</th></tr><tr><td>a</td><td>b</td></tr></table>"#,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let table = &config.pages[0].boxes[0];
+        let head = &table.children[0].children[0];
+        let cells = &table.children[1].children;
+        assert_eq!(table.children[0].children.len(), 1);
+        assert!((head.x - cells[0].x).abs() < 0.01);
+        let right = cells[1].x + cells[1].width;
+        assert!((head.x + head.width - right).abs() < 0.01);
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            head,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        let top = config.page_height_pt - head.y;
+        let spans_both = ops.iter().any(|op| match op {
+            Op::DrawLine { line } => line.points.windows(2).any(|w| {
+                let (a, b) = (w[0].p, w[1].p);
+                (a.y.0 - top).abs() < 0.01
+                    && (b.y.0 - top).abs() < 0.01
+                    && (a.x.0.min(b.x.0) - cells[0].x).abs() < 0.01
+                    && (a.x.0.max(b.x.0) - right).abs() < 0.01
+            }),
+            _ => false,
+        });
+        assert!(spans_both, "{ops:?}");
+    }
+
+    #[test]
+    fn bold_span_keeps_its_weight_in_a_paragraph() {
+        let config = crate::pipeline::compute_layout_config(
+            "<p>normal <b>bold</b></p>",
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let para = &config.pages[0].boxes[0];
+        let runs = &para.text.as_ref().unwrap().lines[0].runs;
+        let texts: Vec<&str> = runs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, ["normal ", "bold"]);
+        assert_eq!((runs[0].bold, runs[1].bold), (None, Some(true)));
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            para,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        let fonts: Vec<BuiltinFont> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::SetFontSizeBuiltinFont { font, .. } => Some(*font),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fonts, [BuiltinFont::Helvetica, BuiltinFont::HelveticaBold]);
+    }
+
+    #[test]
+    fn tracked_span_sets_and_resets_character_spacing() {
+        let config = crate::pipeline::compute_layout_config(
+            r#"<p>plain <span class="tracking-widest">wide</span></p>"#,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let para = &config.pages[0].boxes[0];
+        let runs = &para.text.as_ref().unwrap().lines[0].runs;
+        assert_eq!(runs[1].letter_spacing, Some(1.6));
+        let fonts = FontManager::default();
+        let wide = fonts.measure_text_width("wide", 16.0, false, false, "Helvetica");
+        assert!((runs[1].width - (wide + 4.0 * 1.6)).abs() < 0.01);
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            para,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        let spacing: Vec<f32> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::SetCharacterSpacing { multiplier } => Some(*multiplier),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(spacing, [1.6, 0.0]);
+    }
+
+    #[test]
+    fn colored_span_switches_fill_mid_line() {
+        let config = crate::pipeline::compute_layout_config(
+            r##"<p>before <span style="color: #ff0000">red</span> after</p>"##,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let para = &config.pages[0].boxes[0];
+        let text = para.text.as_ref().unwrap();
+        assert_eq!(text.color, [0.0, 0.0, 0.0, 1.0]);
+        let colors: Vec<_> = text.lines[0]
+            .runs
+            .iter()
+            .map(|r| (r.text.as_str(), r.color))
+            .collect();
+        assert_eq!(
+            colors,
+            [
+                ("before ", None),
+                ("red", Some([1.0, 0.0, 0.0, 1.0])),
+                (" after", None)
+            ]
+        );
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            para,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        let fills: Vec<f32> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::SetFillColor {
+                    col: Color::Rgb(rgb),
+                } => Some(rgb.r),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fills, [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn del_and_ins_strike_and_underline_their_runs() {
+        let config = crate::pipeline::compute_layout_config(
+            "<p><del>old</del><ins>new</ins></p>",
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let para = &config.pages[0].boxes[0];
+        let text = para.text.as_ref().unwrap();
+        let runs = &text.lines[0].runs;
+        let decorations: Vec<_> = runs
+            .iter()
+            .map(|r| (r.text.as_str(), r.line_through, r.underline))
+            .collect();
+        assert_eq!(
+            decorations,
+            [
+                ("old", Some(true), Some(false)),
+                ("new", Some(false), Some(true))
+            ]
+        );
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            para,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        let lines: Vec<(f32, f32, f32)> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::DrawLine { line } => {
+                    let (a, b) = (&line.points[0].p, &line.points[1].p);
+                    Some((a.x.0, b.x.0, a.y.0))
+                }
+                _ => None,
+            })
+            .collect();
+        let [(strike_from, strike_to, strike_y), (under_from, under_to, under_y)] = lines[..]
+        else {
+            panic!("expected two lines, got {lines:?}");
+        };
+        // The strike spans "old", then the underline spans "new", below it.
+        assert_eq!(strike_from, para.x + runs[0].x_offset);
+        assert_eq!(strike_to, under_from);
+        assert_eq!(under_to, para.x + runs[1].x_offset + runs[1].width);
+        assert!(strike_y > under_y);
+    }
+
+    #[test]
+    fn mark_paints_a_yellow_highlight_behind_its_text() {
+        let config = crate::pipeline::compute_layout_config(
+            "<p>find <mark>x</mark></p>",
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let para = &config.pages[0].boxes[0];
+        let text = para.text.as_ref().unwrap();
+        let mark = &text.lines[0].runs[1];
+        assert_eq!(mark.text, "x");
+        assert_eq!(mark.background, Some([1.0, 1.0, 0.0, 1.0]));
+        assert!(mark.width > 0.0);
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            para,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        let highlight = ops
+            .iter()
+            .position(|op| matches!(op, Op::DrawPolygon { .. }))
+            .expect("highlight rect");
+        let Op::SetFillColor {
+            col: Color::Rgb(yellow),
+        } = &ops[highlight - 1]
+        else {
+            panic!("expected a fill colour before the highlight");
+        };
+        assert_eq!((yellow.r, yellow.g, yellow.b), (1.0, 1.0, 0.0));
+        let Op::DrawPolygon { polygon } = &ops[highlight] else {
+            unreachable!()
+        };
+        let xs: Vec<f32> = polygon.rings[0].points.iter().map(|p| p.p.x.0).collect();
+        assert_eq!(xs[0], para.x + mark.x_offset);
+        assert_eq!(xs[1], para.x + mark.x_offset + mark.width);
+        // The highlight is behind the glyphs.
+        let first_text = ops
+            .iter()
+            .position(|op| matches!(op, Op::StartTextSection))
+            .unwrap();
+        assert!(highlight < first_text);
+    }
+
+    #[test]
+    fn broken_image_shows_its_alt_text() {
+        let config = crate::pipeline::compute_layout_config(
+            r#"<img src="data:image/png;base64,AAAA" alt="logo" class="w-32 h-16">"#,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let img = &config.pages[0].boxes[0];
+        assert_eq!(img.image.as_ref().unwrap().alt.as_deref(), Some("logo"));
+
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            img,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+        let texts: Vec<_> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::WriteTextBuiltinFont { items, .. } => Some(items.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, [vec![TextItem::Text("logo".to_string())]]);
+    }
+
+    #[test]
+    fn page_range_renders_only_the_selected_pages() {
+        let config = crate::pipeline::compute_layout_config(
+            r#"<p>One</p><div class="page-break"></div><p>Two</p>
+            <div class="page-break"></div><p>Three</p>"#,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        assert_eq!(config.pages.len(), 3);
+
+        let bytes = render_pages_range(&config, &FontManager::default(), 1..=1).unwrap();
+        let doc = lopdf::Document::load_mem(&bytes).unwrap();
+        assert_eq!(doc.get_pages().len(), 1);
+        assert_eq!(doc.extract_text(&[1]).unwrap().trim(), "Two");
+
+        let tail = render_pages_range(&config, &FontManager::default(), 1..).unwrap();
+        assert_eq!(
+            lopdf::Document::load_mem(&tail).unwrap().get_pages().len(),
+            2
+        );
+        for range in [3..4, 2..2] {
+            assert!(matches!(
+                render_pages_range(&config, &FontManager::default(), range),
+                Err(ForgeError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn gradient_is_not_repeated_behind_text() {
+        let config = crate::pipeline::compute_layout_config(
+            r#"<div style="background: linear-gradient(#ff0000, #0000ff)">Text</div>"#,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let block = &config.pages[0].boxes[0];
+        assert!(block.background_gradient.is_some());
+        assert!(block.children[0].background_gradient.is_none());
+    }
+
+    #[test]
+    fn border_b_draws_only_the_bottom_edge() {
+        let config = crate::pipeline::compute_layout_config(
+            r#"<div class="border-b border-gray-300">Row</div>"#,
+            &crate::pipeline::PipelineConfig::default(),
+        );
+        let row = &config.pages[0].boxes[0];
+        let mut ops = Vec::new();
+        render_box(
+            &mut ops,
+            row,
+            config.page_height_pt,
+            &HashMap::new(),
+            &EmbeddedFonts::default(),
+            &mut PageResources::new(0, false),
+        );
+
+        let lines: Vec<&Line> = ops
+            .iter()
+            .filter_map(|op| match op {
+                Op::DrawLine { line } => Some(line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(lines.len(), 1, "only the bottom edge is stroked");
+        let bottom = config.page_height_pt - row.y - row.height;
+        for point in &lines[0].points {
+            assert!((point.p.y.0 - bottom).abs() <= 1.0);
+        }
+        assert_eq!(lines[0].points[0].p.x.0, row.x);
+        assert_eq!(lines[0].points[1].p.x.0, row.x + row.width);
+    }
+
+    #[test]
+    fn watermark_is_drawn_rotated_on_every_page() {
+        let mut config = LayoutConfig::a4();
+        for page_index in 0..2 {
+            config.pages.push(PageLayout {
+                page_index,
+                boxes: Vec::new(),
+            });
+        }
+        config.watermark = Some(Watermark::new("DRAFT"));
+        let doc = lopdf::Document::load_mem(&render_pdf(&config).unwrap()).unwrap();
+
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 2);
+        for page_id in pages.into_values() {
+            let content =
+                lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+            let tm = content
+                .operations
+                .iter()
+                .find(|op| op.operator == "Tm")
+                .expect("watermark sets a text matrix");
+            let sin = tm.operands[1].as_float().unwrap();
+            assert!((sin - 45f32.to_radians().sin()).abs() < 1e-3);
+            assert!(content.operations.iter().any(|op| op.operator == "gs"));
+        }
+    }
+
+    #[test]
+    fn page_background_fills_the_page_first() {
+        let mut config = LayoutConfig::a4();
+        config.pages.push(PageLayout {
+            page_index: 0,
+            boxes: vec![LayoutBox::new(40.0, 40.0, 100.0, 20.0)],
+        });
+        config.pages[0].boxes[0].background_color = Some([1.0, 0.0, 0.0, 1.0]);
+        config.page_background = Some([0.5, 0.5, 0.5, 1.0]);
+        config.compress = false;
+        let doc = lopdf::Document::load_mem(&render_pdf(&config).unwrap()).unwrap();
+        let page_id = *doc.get_pages().values().next().unwrap();
+        let content =
+            lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+        let ops: Vec<&str> = content
+            .operations
+            .iter()
+            .map(|op| op.operator.as_str())
+            .collect();
+        let first_fill = ops.iter().position(|&op| op == "f").unwrap();
+        assert_eq!(ops[0], "rg");
+        let grey: Vec<f32> = content.operations[0]
+            .operands
+            .iter()
+            .map(|o| o.as_float().unwrap())
+            .collect();
+        assert_eq!(grey, [0.5, 0.5, 0.5]);
+        // The fill is a rectangle from the origin to the page's far corner.
+        let corners: Vec<Vec<f32>> = content.operations[1..first_fill]
+            .iter()
+            .map(|op| op.operands.iter().map(|o| o.as_float().unwrap()).collect())
+            .collect();
+        assert!(corners.contains(&vec![0.0, 0.0]), "{corners:?}");
+        assert!(corners.contains(&vec![config.page_width_pt, config.page_height_pt]));
+    }
+
+    #[test]
+    fn post_pass_runs_only_when_there_is_something_to_patch() {
+        let mut config = LayoutConfig::a4();
+        config.compress = false;
+        assert!(!needs_post_pass(&config, false, 0.0));
+        assert!(needs_post_pass(&config, true, 0.0));
+        assert!(needs_post_pass(&config, false, 9.0));
+        config.default_zoom = Some(ZoomMode::FitPage);
+        assert!(needs_post_pass(&config, false, 0.0));
+        config.default_zoom = None;
+        config.compress = true;
+        assert!(needs_post_pass(&config, false, 0.0));
+    }
+
+    #[test]
+    fn crop_marks_enlarge_the_media_box_and_mark_the_corners() {
+        let mut config = LayoutConfig::a4();
+        config.pages.push(PageLayout {
+            page_index: 0,
+            boxes: Vec::new(),
+        });
+        config.compress = false;
+        let page_box = |config: &LayoutConfig, name: &[u8]| {
+            let doc = lopdf::Document::load_mem(&render_pdf(config).unwrap()).unwrap();
+            let page_id = *doc.get_pages().values().next().unwrap();
+            let page = doc.get_dictionary(page_id).unwrap();
+            let rect: Vec<f32> = page
+                .get(name)
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v.as_float().unwrap())
+                .collect();
+            let content =
+                lopdf::content::Content::decode(&doc.get_page_content(page_id).unwrap()).unwrap();
+            let strokes = content
+                .operations
+                .iter()
+                .filter(|op| op.operator == "S")
+                .count();
+            (rect, strokes)
+        };
+        // printpdf writes whole-point media boxes.
+        let (plain, strokes) = page_box(&config, b"MediaBox");
+        assert_eq!(plain[2], config.page_width_pt.round());
+        assert_eq!(strokes, 0);
+
+        config.bleed_pt = 9.0;
+        config.crop_marks = true;
+        let margin = 9.0 + CROP_MARK_LENGTH;
+        let (media, strokes) = page_box(&config, b"MediaBox");
+        assert_eq!(media[2], (config.page_width_pt + 2.0 * margin).round());
+        assert_eq!(media[3], (config.page_height_pt + 2.0 * margin).round());
+        // Two marks at each of the four corners.
+        assert_eq!(strokes, 8);
+        let (trim, _) = page_box(&config, b"TrimBox");
+        assert_eq!(trim[..2], [margin, margin]);
+        assert_eq!(trim[2], config.page_width_pt + margin);
+        let (bleed, _) = page_box(&config, b"BleedBox");
+        assert_eq!(bleed[..2], [margin - 9.0, margin - 9.0]);
+    }
+
+    #[test]
+    fn default_zoom_sets_open_action() {
+        let mut config = LayoutConfig::a4();
+        config.pages.push(PageLayout {
+            page_index: 0,
+            boxes: Vec::new(),
+        });
+        for (zoom, name) in [
+            (ZoomMode::FitPage, "Fit"),
+            (ZoomMode::FitWidth, "FitH"),
+            (ZoomMode::Percent(150.0), "XYZ"),
+        ] {
+            config.default_zoom = Some(zoom);
+            let bytes = render_pdf(&config).unwrap();
+            let doc = lopdf::Document::load_mem(&bytes).unwrap();
+            let action = doc.catalog().unwrap().get(b"OpenAction").unwrap();
+            let dest = action.as_array().unwrap();
+            assert_eq!(dest[1].as_name().unwrap(), name.as_bytes());
+            if let ZoomMode::Percent(_) = zoom {
+                assert_eq!(dest[4].as_float().unwrap(), 1.5);
+            }
+        }
+
+        config.default_zoom = None;
+        let bytes = render_pdf(&config).unwrap();
+        let doc = lopdf::Document::load_mem(&bytes).unwrap();
+        assert!(doc.catalog().unwrap().get(b"OpenAction").is_err());
+    }
+
+    #[test]
+    fn object_fit_placement() {
+        // A 200×100 image in a 100×100 box.
+        assert_eq!(
+            fit_image(100.0, 100.0, 200.0, 100.0, ObjectFit::Fill),
+            (0.0, 0.0, 100.0, 100.0)
+        );
+        // Contain: aspect kept, letterboxed vertically.
+        assert_eq!(
+            fit_image(100.0, 100.0, 200.0, 100.0, ObjectFit::Contain),
+            (0.0, 25.0, 100.0, 50.0)
+        );
+        // Cover: aspect kept, overflows horizontally (clipped at render).
+        assert_eq!(
+            fit_image(100.0, 100.0, 200.0, 100.0, ObjectFit::Cover),
+            (-50.0, 0.0, 200.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn jpeg_and_webp_sources_are_embeddable() {
+        let img = ::image::DynamicImage::new_rgb8(4, 2);
+        for (format, mime) in [
+            (::image::ImageFormat::Jpeg, "image/jpeg"),
+            (::image::ImageFormat::WebP, "image/webp"),
+        ] {
+            let mut bytes = std::io::Cursor::new(Vec::new());
+            img.write_to(&mut bytes, format).unwrap();
+            let src = format!("data:{mime};base64,{}", BASE64_STD.encode(bytes.get_ref()));
+            assert!(check_image_src(&src).is_ok(), "{mime} should be embeddable");
+        }
+    }
+
+    #[test]
+    fn baseline_follows_embedded_font_ascender() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/Tuffy.ttf");
+        let mut fonts = FontManager::default();
+        fonts
+            .load_font("Tuffy", false, false, std::fs::read(path).unwrap())
+            .unwrap();
+        let embedded = EmbeddedFonts::embed(&mut DocBuilder::new("t", false), &fonts).unwrap();
+
+        let builtin = embedded.baseline_offset("Helvetica", false, false, 20.0);
+        assert_eq!(builtin, 20.0 * BUILTIN_ASCENT);
+        let tuffy = embedded.baseline_offset("Tuffy", false, false, 20.0);
+        assert_eq!(tuffy, fonts.ascender_px(20.0, false, false, "Tuffy"));
+        assert!((tuffy - builtin).abs() > 0.1, "{tuffy} vs {builtin}");
+    }
+
+    #[test]
+    fn ligatures_draw_the_fi_glyph_when_enabled() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/RobotoMedium.ttf");
+        let mut fonts = FontManager::new();
+        fonts
+            .load_font("Helvetica", false, false, std::fs::read(path).unwrap())
+            .unwrap();
+        let config = crate::pipeline::PipelineConfig {
+            ligatures: true,
+            ..Default::default()
+        };
+        let layout =
+            crate::pipeline::compute_layout_config_with_fonts("<p>office</p>", &config, &fonts);
+        assert!(crate::pipeline::generate_pdf_with_fonts("<p>office</p>", &config, &fonts).is_ok());
+
+        let draw = |fonts: &FontManager| {
+            let embedded = EmbeddedFonts::embed(&mut DocBuilder::new("t", false), fonts).unwrap();
+            let mut ops = Vec::new();
+            render_box(
+                &mut ops,
+                &layout.pages[0].boxes[0],
+                layout.page_height_pt,
+                &HashMap::new(),
+                &embedded,
+                &mut PageResources::new(0, false),
+            );
+            ops.iter()
+                .filter_map(|op| match op {
+                    Op::WriteCodepoints { cp, .. } => Some(cp[0].1),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert!(draw(&fonts).is_empty());
+        fonts.set_ligatures(true);
+        // Roboto has no `ffi` ligature: "of", then `fi`.
+        assert_eq!(draw(&fonts), ['\u{FB01}']);
+    }
+
     #[test]
     fn render_empty_page() {
         let config = LayoutConfig::a4();
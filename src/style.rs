@@ -2,6 +2,7 @@
 //! to a flat [`ComputedStyle`] struct consumed by the layout engine.
 
 use crate::dom::{DomNode, ElementNode, Tag};
+use crate::layout_config::{LinearGradient, ObjectFit};
 
 /// Fully resolved style for a single element.
 #[derive(Debug, Clone)]
@@ -14,17 +15,32 @@ pub struct ComputedStyle {
     pub flex_shrink: f32,
     pub justify_content: JustifyContent,
     pub align_items: AlignItems,
-    pub gap: f32,
+    /// Vertical space between flex/grid rows.
+    pub row_gap: f32,
+    /// Horizontal space between flex/grid items and between multi-column
+    /// columns; `None` is CSS `normal`, which is 0 for flex and grid and
+    /// [`MULTI_COLUMN_GAP`] for multi-column blocks.
+    pub column_gap: Option<f32>,
+
+    // Multi-column
+    /// Number of side-by-side columns the content flows through (CSS `columns`).
+    pub column_count: u32,
 
     // Grid
     pub grid_template_columns: Vec<GridTrack>,
     pub grid_template_rows: Vec<GridTrack>,
+    /// Number of grid columns / rows this item spans (CSS `span n`).
+    pub grid_column_span: u16,
+    pub grid_row_span: u16,
 
     // Sizing
     pub width: Dimension,
     pub height: Dimension,
     pub min_width: Dimension,
     pub max_width: Dimension,
+    /// Width divided by height (CSS `aspect-ratio`); the unset dimension is
+    /// derived from the other one.
+    pub aspect_ratio: Option<f32>,
 
     // Spacing (px)
     pub margin_top: f32,
@@ -37,8 +53,14 @@ pub struct ComputedStyle {
     pub padding_left: f32,
 
     // Border
-    pub border_width: f32,
-    pub border_color: Color,
+    pub border_top_width: f32,
+    pub border_right_width: f32,
+    pub border_bottom_width: f32,
+    pub border_left_width: f32,
+    pub border_top_color: Color,
+    pub border_right_color: Color,
+    pub border_bottom_color: Color,
+    pub border_left_color: Color,
 
     // Typography
     pub font_size: f32,
@@ -46,17 +68,59 @@ pub struct ComputedStyle {
     pub font_family: String,
     pub color: Color,
     pub text_align: TextAlign,
+    /// Vertical placement of a table cell's content.
+    pub vertical_align: VerticalAlign,
+    /// Whether a table's `<caption>` sits above or below its rows.
+    pub caption_side: CaptionSide,
     pub line_height: f32,
+    /// Extra space after each character, in ems (a multiple of the font
+    /// size, like `line_height`); inherited.
+    pub letter_spacing: f32,
+    /// Underline or strike-through; inherited, so it reaches nested text.
     pub text_decoration: TextDecoration,
     pub font_style: FontStyle,
+    /// Vertical offset of the text baseline in points; positive raises it
+    /// (`<sup>`), negative lowers it (`<sub>`).
+    pub baseline_shift: f32,
+    /// Marker drawn before each `<li>`; inherited from the enclosing list.
+    pub list_style_type: ListStyleType,
+    /// Space between a list marker and its item, in points; inherited from
+    /// the enclosing list.
+    pub list_marker_gap: f32,
+    /// Whether text keeps its line breaks and spacing (`<pre>`).
+    pub white_space: WhiteSpace,
+    /// Whether a word too wide for its line is broken between characters;
+    /// inherited.
+    pub overflow_wrap: OverflowWrap,
+    /// Paragraph direction, from `dir` or CSS `direction`; inherited.
+    pub direction: Direction,
+    /// Distance between tab stops in preformatted text, in spaces.
+    pub tab_size: u32,
 
     // Background
     pub background_color: Color,
+    pub background_gradient: Option<LinearGradient>,
+
+    // Overflow
+    /// Scale an over-wide table down horizontally to fit its container
+    /// (`overflow-x-auto` / `overflow-x: auto`) instead of letting it overflow.
+    pub scale_to_fit: bool,
+    /// Whether content and children are clipped to the box (CSS `overflow`).
+    pub overflow: Overflow,
+    /// Hidden boxes keep their space but draw nothing; inherited, so a
+    /// `visible` descendant still shows.
+    pub visibility: Visibility,
+
+    // Images
+    pub object_fit: ObjectFit,
 
     // Page break
     pub page_break_before: bool,
     pub page_break_after: bool,
     pub page_break_inside_avoid: bool,
+    /// Move this box to the next page rather than leave it at the bottom of
+    /// a page without the block that follows (headings, `break-after: avoid`).
+    pub keep_with_next: bool,
 }
 
 impl Default for ComputedStyle {
@@ -69,13 +133,18 @@ impl Default for ComputedStyle {
             flex_shrink: 1.0,
             justify_content: JustifyContent::Start,
             align_items: AlignItems::Stretch,
-            gap: 0.0,
+            row_gap: 0.0,
+            column_gap: None,
+            column_count: 1,
             grid_template_columns: Vec::new(),
             grid_template_rows: Vec::new(),
+            grid_column_span: 1,
+            grid_row_span: 1,
             width: Dimension::Auto,
             height: Dimension::Auto,
             min_width: Dimension::Auto,
             max_width: Dimension::Auto,
+            aspect_ratio: None,
             margin_top: 0.0,
             margin_right: 0.0,
             margin_bottom: 0.0,
@@ -84,20 +153,151 @@ impl Default for ComputedStyle {
             padding_right: 0.0,
             padding_bottom: 0.0,
             padding_left: 0.0,
-            border_width: 0.0,
-            border_color: Color::BLACK,
+            border_top_width: 0.0,
+            border_right_width: 0.0,
+            border_bottom_width: 0.0,
+            border_left_width: 0.0,
+            border_top_color: Color::BLACK,
+            border_right_color: Color::BLACK,
+            border_bottom_color: Color::BLACK,
+            border_left_color: Color::BLACK,
             font_size: 16.0,
             font_weight: FontWeight::Normal,
             font_family: "Helvetica".to_string(),
             color: Color::BLACK,
             text_align: TextAlign::Left,
-            line_height: 1.4,
+            vertical_align: VerticalAlign::Top,
+            caption_side: CaptionSide::Top,
+            line_height: crate::fonts::DEFAULT_LINE_HEIGHT,
+            letter_spacing: 0.0,
             text_decoration: TextDecoration::None,
             font_style: FontStyle::Normal,
+            baseline_shift: 0.0,
+            list_style_type: ListStyleType::Disc,
+            list_marker_gap: LIST_MARKER_GAP,
+            white_space: WhiteSpace::Normal,
+            overflow_wrap: OverflowWrap::Normal,
+            direction: Direction::Ltr,
+            tab_size: 4,
             background_color: Color::TRANSPARENT,
+            background_gradient: None,
+            scale_to_fit: false,
+            overflow: Overflow::Visible,
+            visibility: Visibility::Visible,
+            object_fit: ObjectFit::Fill,
             page_break_before: false,
             page_break_after: false,
             page_break_inside_avoid: false,
+            keep_with_next: false,
+        }
+    }
+}
+
+impl ComputedStyle {
+    /// Set the same border width on all four sides.
+    pub fn set_border_width(&mut self, width: f32) {
+        self.border_top_width = width;
+        self.border_right_width = width;
+        self.border_bottom_width = width;
+        self.border_left_width = width;
+    }
+
+    /// Set the same border colour on all four sides.
+    pub fn set_border_color(&mut self, color: Color) {
+        self.border_top_color = color;
+        self.border_right_color = color;
+        self.border_bottom_color = color;
+        self.border_left_color = color;
+    }
+
+    /// The style of text directly inside an element with this style. Text
+    /// renders inline, so the box-model properties that must not be
+    /// inherited (border, background, spacing) are cleared.
+    pub fn text_style(mut self) -> Self {
+        self.set_border_width(0.0);
+        self.background_color = Color {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.0,
+        };
+        self.background_gradient = None;
+        self.margin_top = 0.0;
+        self.margin_right = 0.0;
+        self.margin_bottom = 0.0;
+        self.margin_left = 0.0;
+        self.padding_top = 0.0;
+        self.padding_right = 0.0;
+        self.padding_bottom = 0.0;
+        self.padding_left = 0.0;
+        self
+    }
+}
+
+/// Default highlight behind `<mark>` text: yellow, as in browsers.
+const MARK_BACKGROUND: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+/// Width of the accent bar drawn down the left edge of a `<blockquote>`.
+const BLOCKQUOTE_BAR_WIDTH: f32 = 3.0;
+
+/// Space between the columns of a multi-column block when `column-gap` is
+/// not set.
+pub const MULTI_COLUMN_GAP: f32 = 16.0;
+
+/// Font size of `<sub>`/`<sup>` text relative to the surrounding text.
+const SCRIPT_SCALE: f32 = 0.75;
+/// Font size of `<small>` and `font-size: smaller` text relative to the
+/// surrounding text; `larger` divides by it.
+const SMALLER_SCALE: f32 = 0.8;
+/// `max-w-prose`: 65ch of 16 px Helvetica, whose "0" is 0.556 em wide.
+pub const PROSE_MAX_WIDTH: f32 = 578.0;
+/// `<h1>`–`<h3>` font sizes relative to the inherited size (32/24/20 pt at
+/// the default 16).
+const H1_SCALE: f32 = 2.0;
+const H2_SCALE: f32 = 1.5;
+const H3_SCALE: f32 = 1.25;
+/// How far `<sub>` lowers and `<sup>` raises the baseline, as a fraction of
+/// the surrounding font size.
+const SUBSCRIPT_SHIFT: f32 = 0.2;
+const SUPERSCRIPT_SHIFT: f32 = 0.35;
+
+/// Document-wide defaults that tag styles are derived from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Left indent of each `<ul>` / `<ol>` level in px (default: 24).
+    /// Nested lists accumulate one step per level.
+    pub list_indent: f32,
+    /// Left indent (gutter) of `<blockquote>` in px (default: 24).
+    pub blockquote_indent: f32,
+    /// Space between a list marker and its item in px (default: 6). Lists
+    /// whose markers and gap are wider than `list_indent` are indented
+    /// further to fit them.
+    pub list_marker_gap: f32,
+    /// Multiplier for the default top and bottom margins of headings,
+    /// paragraphs, lists, list items, `<pre>` and `<blockquote>`
+    /// (default: 1).
+    pub spacing_scale: f32,
+    /// Background of every other body row of a table, starting with the
+    /// second; rows with a background of their own keep it (default: `None`).
+    pub table_zebra: Option<Color>,
+}
+
+/// Default space between a list marker and its item, in points.
+pub const LIST_MARKER_GAP: f32 = 6.0;
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            list_indent: 24.0,
+            blockquote_indent: 24.0,
+            list_marker_gap: LIST_MARKER_GAP,
+            spacing_scale: 1.0,
+            table_zebra: None,
         }
     }
 }
@@ -155,6 +355,51 @@ pub enum FontWeight {
     Bold,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListStyleType {
+    /// `•` at the top level; nested `<ul>`s step through circle and square.
+    Disc,
+    Circle,
+    Square,
+    Decimal,
+    LowerAlpha,
+    UpperAlpha,
+    LowerRoman,
+    UpperRoman,
+    None,
+}
+
+impl ListStyleType {
+    /// Parses a CSS `list-style-type` keyword.
+    fn parse(val: &str) -> Option<Self> {
+        Some(match val {
+            "disc" => ListStyleType::Disc,
+            "circle" => ListStyleType::Circle,
+            "square" => ListStyleType::Square,
+            "decimal" => ListStyleType::Decimal,
+            "lower-alpha" | "lower-latin" => ListStyleType::LowerAlpha,
+            "upper-alpha" | "upper-latin" => ListStyleType::UpperAlpha,
+            "lower-roman" => ListStyleType::LowerRoman,
+            "upper-roman" => ListStyleType::UpperRoman,
+            "none" => ListStyleType::None,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptionSide {
+    Top,
+    Bottom,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextAlign {
     Left,
@@ -166,6 +411,7 @@ pub enum TextAlign {
 pub enum TextDecoration {
     None,
     Underline,
+    LineThrough,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -174,11 +420,57 @@ pub enum FontStyle {
     Italic,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhiteSpace {
+    /// Collapse runs of whitespace and wrap at the box edge.
+    Normal,
+    /// Keep spaces, tabs and line breaks; never wrap.
+    Pre,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowWrap {
+    /// Words only break at spaces and soft hyphens, overflowing if need be.
+    Normal,
+    /// A word that does not fit on a line of its own is broken between
+    /// characters (`break-word` or `anywhere`).
+    BreakWord,
+}
+
+/// Inline base direction. Right-to-left text aligns right by default, flex
+/// rows run right to left and list markers hang on the right; characters are
+/// not reordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    Visible,
+    /// Clip content and children to the box (`hidden` or `clip`).
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Visible,
+    /// Laid out as usual but not drawn (`hidden` or `collapse`).
+    Hidden,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Dimension {
     Auto,
     Px(f32),
     Percent(f32),
+    /// As narrow as the content allows: the longest word.
+    MinContent,
+    /// As wide as the content without wrapping.
+    MaxContent,
+    /// `max-content`, but no wider than the available space.
+    FitContent,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -237,15 +529,454 @@ impl Color {
             None
         }
     }
+
+    /// Parse a CSS colour: hex, `rgb()` / `rgba()` with comma or space
+    /// separated channels, or one of the CSS 2.1 colour keywords (plus
+    /// `grey` and `transparent`).
+    pub fn from_css(val: &str) -> Option<Self> {
+        let val = val.trim();
+        if val.starts_with('#') {
+            return Self::from_hex(val);
+        }
+        let lower = val.to_ascii_lowercase();
+        if let Some(args) = lower
+            .strip_prefix("rgba(")
+            .or_else(|| lower.strip_prefix("rgb("))
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let parts: Vec<&str> = args
+                .split([',', ' ', '/'])
+                .filter(|p| !p.is_empty())
+                .collect();
+            let channel = |p: &str| match p.strip_suffix('%') {
+                Some(pct) => pct.parse::<f32>().ok().map(|v| v / 100.0),
+                None => p.parse::<f32>().ok().map(|v| v / 255.0),
+            };
+            let (r, g, b, a) = match parts.as_slice() {
+                [r, g, b] => (channel(r)?, channel(g)?, channel(b)?, 1.0),
+                [r, g, b, a] => {
+                    let a = match a.strip_suffix('%') {
+                        Some(pct) => pct.parse::<f32>().ok()? / 100.0,
+                        None => a.parse::<f32>().ok()?,
+                    };
+                    (channel(r)?, channel(g)?, channel(b)?, a)
+                }
+                _ => return None,
+            };
+            let unit = |v: f32| v.clamp(0.0, 1.0);
+            return Some(Self {
+                r: unit(r),
+                g: unit(g),
+                b: unit(b),
+                a: unit(a),
+            });
+        }
+        if lower == "transparent" {
+            return Some(Self::TRANSPARENT);
+        }
+        let rgb = match lower.as_str() {
+            "black" => 0x000000,
+            "silver" => 0xc0c0c0,
+            "gray" | "grey" => 0x808080,
+            "white" => 0xffffff,
+            "maroon" => 0x800000,
+            "red" => 0xff0000,
+            "purple" => 0x800080,
+            "fuchsia" => 0xff00ff,
+            "green" => 0x008000,
+            "lime" => 0x00ff00,
+            "olive" => 0x808000,
+            "yellow" => 0xffff00,
+            "navy" => 0x000080,
+            "blue" => 0x0000ff,
+            "teal" => 0x008080,
+            "aqua" => 0x00ffff,
+            "orange" => 0xffa500,
+            _ => return None,
+        };
+        Self::from_hex(&format!("{rgb:06x}"))
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Style resolution
 // ---------------------------------------------------------------------------
 
+/// The style change made by a fixed Tailwind class.
+type ClassRule = fn(&mut ComputedStyle);
+
+/// Tailwind classes matched verbatim by `apply_tailwind_class`, with the
+/// style change each one makes.
+const TAILWIND_CLASSES: &[(&str, ClassRule)] = &[
+    // Display
+    ("flex", |s| s.display = Display::Flex),
+    ("grid", |s| s.display = Display::Grid),
+    ("block", |s| s.display = Display::Block),
+    ("inline", |s| s.display = Display::Inline),
+    ("inline-block", |s| s.display = Display::InlineBlock),
+    ("hidden", |s| s.display = Display::None),
+    // Flex direction
+    ("flex-row", |s| s.flex_direction = FlexDirection::Row),
+    ("flex-col", |s| s.flex_direction = FlexDirection::Column),
+    // Flex wrap
+    ("flex-wrap", |s| s.flex_wrap = FlexWrap::Wrap),
+    ("flex-nowrap", |s| s.flex_wrap = FlexWrap::NoWrap),
+    // Flex grow/shrink
+    ("flex-grow", |s| s.flex_grow = 1.0),
+    ("grow", |s| s.flex_grow = 1.0),
+    ("flex-shrink", |s| s.flex_shrink = 1.0),
+    ("shrink", |s| s.flex_shrink = 1.0),
+    ("flex-1", |s| {
+        s.flex_grow = 1.0;
+        s.flex_shrink = 1.0;
+    }),
+    // Justify content
+    ("justify-start", |s| {
+        s.justify_content = JustifyContent::Start
+    }),
+    ("justify-end", |s| s.justify_content = JustifyContent::End),
+    ("justify-center", |s| {
+        s.justify_content = JustifyContent::Center
+    }),
+    ("justify-between", |s| {
+        s.justify_content = JustifyContent::SpaceBetween
+    }),
+    ("justify-around", |s| {
+        s.justify_content = JustifyContent::SpaceAround
+    }),
+    ("justify-evenly", |s| {
+        s.justify_content = JustifyContent::SpaceEvenly
+    }),
+    // Align items
+    ("items-start", |s| s.align_items = AlignItems::Start),
+    ("items-end", |s| s.align_items = AlignItems::End),
+    ("items-center", |s| s.align_items = AlignItems::Center),
+    ("items-stretch", |s| s.align_items = AlignItems::Stretch),
+    // Font weight
+    ("font-bold", |s| s.font_weight = FontWeight::Bold),
+    ("font-normal", |s| s.font_weight = FontWeight::Normal),
+    // Font style
+    ("italic", |s| s.font_style = FontStyle::Italic),
+    ("not-italic", |s| s.font_style = FontStyle::Normal),
+    // Text decoration
+    ("underline", |s| {
+        s.text_decoration = TextDecoration::Underline
+    }),
+    ("line-through", |s| {
+        s.text_decoration = TextDecoration::LineThrough
+    }),
+    ("no-underline", |s| s.text_decoration = TextDecoration::None),
+    // Text alignment
+    ("text-left", |s| s.text_align = TextAlign::Left),
+    ("text-center", |s| s.text_align = TextAlign::Center),
+    ("text-right", |s| s.text_align = TextAlign::Right),
+    // Vertical alignment (table cells)
+    ("align-top", |s| s.vertical_align = VerticalAlign::Top),
+    ("align-middle", |s| s.vertical_align = VerticalAlign::Middle),
+    ("align-bottom", |s| s.vertical_align = VerticalAlign::Bottom),
+    // Table captions
+    ("caption-top", |s| s.caption_side = CaptionSide::Top),
+    ("caption-bottom", |s| s.caption_side = CaptionSide::Bottom),
+    // Lists
+    ("list-disc", |s| s.list_style_type = ListStyleType::Disc),
+    ("list-decimal", |s| {
+        s.list_style_type = ListStyleType::Decimal
+    }),
+    ("list-none", |s| s.list_style_type = ListStyleType::None),
+    ("list-[a]", |s| {
+        s.list_style_type = ListStyleType::LowerAlpha
+    }),
+    ("list-[i]", |s| {
+        s.list_style_type = ListStyleType::LowerRoman
+    }),
+    // Font sizes
+    ("text-xs", |s| s.font_size = 12.0),
+    ("text-sm", |s| s.font_size = 14.0),
+    ("text-base", |s| s.font_size = 16.0),
+    ("text-lg", |s| s.font_size = 18.0),
+    ("text-xl", |s| s.font_size = 20.0),
+    ("text-2xl", |s| s.font_size = 24.0),
+    ("text-3xl", |s| s.font_size = 30.0),
+    ("text-4xl", |s| s.font_size = 36.0),
+    // Line height
+    ("leading-none", |s| s.line_height = 1.0),
+    ("leading-tight", |s| s.line_height = 1.25),
+    ("leading-snug", |s| s.line_height = 1.375),
+    ("leading-normal", |s| s.line_height = 1.5),
+    ("leading-relaxed", |s| s.line_height = 1.625),
+    ("leading-loose", |s| s.line_height = 2.0),
+    // Letter spacing
+    ("tracking-tighter", |s| s.letter_spacing = -0.05),
+    ("tracking-tight", |s| s.letter_spacing = -0.025),
+    ("tracking-normal", |s| s.letter_spacing = 0.0),
+    ("tracking-wide", |s| s.letter_spacing = 0.025),
+    ("tracking-wider", |s| s.letter_spacing = 0.05),
+    ("tracking-widest", |s| s.letter_spacing = 0.1),
+    // Width
+    ("w-full", |s| s.width = Dimension::Percent(100.0)),
+    ("w-auto", |s| s.width = Dimension::Auto),
+    ("w-min", |s| s.width = Dimension::MinContent),
+    ("w-max", |s| s.width = Dimension::MaxContent),
+    ("w-fit", |s| s.width = Dimension::FitContent),
+    ("w-1/2", |s| s.width = Dimension::Percent(50.0)),
+    ("w-1/3", |s| s.width = Dimension::Percent(33.333)),
+    ("w-2/3", |s| s.width = Dimension::Percent(66.666)),
+    ("w-1/4", |s| s.width = Dimension::Percent(25.0)),
+    ("w-3/4", |s| s.width = Dimension::Percent(75.0)),
+    ("min-w-full", |s| s.min_width = Dimension::Percent(100.0)),
+    ("max-w-none", |s| s.max_width = Dimension::Auto),
+    ("max-w-full", |s| s.max_width = Dimension::Percent(100.0)),
+    ("max-w-prose", |s| {
+        s.max_width = Dimension::Px(PROSE_MAX_WIDTH)
+    }),
+    // Borders
+    ("border", |s| {
+        set_border_widths(s, &["top", "right", "bottom", "left"], 1.0)
+    }),
+    ("border-t", |s| set_border_widths(s, &["top"], 1.0)),
+    ("border-r", |s| set_border_widths(s, &["right"], 1.0)),
+    ("border-b", |s| set_border_widths(s, &["bottom"], 1.0)),
+    ("border-l", |s| set_border_widths(s, &["left"], 1.0)),
+    ("border-x", |s| {
+        set_border_widths(s, &["left", "right"], 1.0)
+    }),
+    ("border-y", |s| {
+        set_border_widths(s, &["top", "bottom"], 1.0)
+    }),
+    // Overflow
+    ("overflow-x-auto", |s| s.scale_to_fit = true),
+    ("overflow-x-visible", |s| s.scale_to_fit = false),
+    ("overflow-hidden", |s| s.overflow = Overflow::Hidden),
+    ("overflow-clip", |s| s.overflow = Overflow::Hidden),
+    ("overflow-visible", |s| s.overflow = Overflow::Visible),
+    // Visibility
+    ("visible", |s| s.visibility = Visibility::Visible),
+    ("invisible", |s| s.visibility = Visibility::Hidden),
+    // Whitespace
+    ("whitespace-normal", |s| s.white_space = WhiteSpace::Normal),
+    ("whitespace-pre", |s| s.white_space = WhiteSpace::Pre),
+    // Word breaking
+    ("break-words", |s| s.overflow_wrap = OverflowWrap::BreakWord),
+    ("break-normal", |s| s.overflow_wrap = OverflowWrap::Normal),
+    // Images
+    ("object-fill", |s| s.object_fit = ObjectFit::Fill),
+    ("object-contain", |s| s.object_fit = ObjectFit::Contain),
+    ("object-cover", |s| s.object_fit = ObjectFit::Cover),
+    // Aspect ratio
+    ("aspect-auto", |s| s.aspect_ratio = None),
+    ("aspect-square", |s| s.aspect_ratio = Some(1.0)),
+    ("aspect-video", |s| s.aspect_ratio = Some(16.0 / 9.0)),
+    // Page break
+    ("break-before", |s| s.page_break_before = true),
+    ("break-after", |s| s.page_break_after = true),
+    ("break-inside-avoid", |s| s.page_break_inside_avoid = true),
+    ("break-after-avoid", |s| s.keep_with_next = true),
+    // Convenience classes for explicit page breaks in templates
+    ("page", |s| s.page_break_after = true),
+    ("page-break", |s| s.page_break_after = true),
+];
+
+/// Parameterised Tailwind class families handled by the `try_parse_*`
+/// helpers. `{n}` is a number on the 4 pt spacing scale; `{color}` is a
+/// palette name such as `gray-500`; `{value}` is an arbitrary CSS value;
+/// `{class}` is any other supported class.
+const TAILWIND_PATTERNS: &[&str] = &[
+    "p-{n}",
+    "px-{n}",
+    "py-{n}",
+    "pt-{n}",
+    "pr-{n}",
+    "pb-{n}",
+    "pl-{n}",
+    "m-{n}",
+    "-m-{n}",
+    "mx-{n}",
+    "my-{n}",
+    "mt-{n}",
+    "mr-{n}",
+    "mb-{n}",
+    "ml-{n}",
+    "-mx-{n}",
+    "-my-{n}",
+    "-mt-{n}",
+    "-mr-{n}",
+    "-mb-{n}",
+    "-ml-{n}",
+    "text-{color}",
+    "bg-{color}",
+    "border-{color}",
+    "border-{n}",
+    "border-t-{n}",
+    "border-r-{n}",
+    "border-b-{n}",
+    "border-l-{n}",
+    "border-x-{n}",
+    "border-y-{n}",
+    "gap-{n}",
+    "gap-x-{n}",
+    "gap-y-{n}",
+    "grid-cols-{n}",
+    "grid-cols-[{tracks}]",
+    "grid-rows-{n}",
+    "grid-rows-[{tracks}]",
+    "col-span-{n}",
+    "row-span-{n}",
+    "columns-{n}",
+    "leading-{n}",
+    "w-{n}",
+    "min-w-{n}",
+    "max-w-{n}",
+    "h-{n}",
+    "aspect-[{w}/{h}]",
+    "text-[{value}]",
+    "bg-[{value}]",
+    "border-[{value}]",
+    "w-[{value}]",
+    "min-w-[{value}]",
+    "max-w-[{value}]",
+    "h-[{value}]",
+    "p-[{value}]",
+    "px-[{value}]",
+    "py-[{value}]",
+    "pt-[{value}]",
+    "pr-[{value}]",
+    "pb-[{value}]",
+    "pl-[{value}]",
+    "m-[{value}]",
+    "mx-[{value}]",
+    "my-[{value}]",
+    "mt-[{value}]",
+    "mr-[{value}]",
+    "mb-[{value}]",
+    "ml-[{value}]",
+    "gap-[{value}]",
+    "gap-x-[{value}]",
+    "gap-y-[{value}]",
+    "leading-[{value}]",
+    "sm:{class}",
+    "md:{class}",
+    "lg:{class}",
+    "xl:{class}",
+    "2xl:{class}",
+    "print:{class}",
+    "hover:{class}",
+    "focus:{class}",
+    "focus-within:{class}",
+    "focus-visible:{class}",
+    "active:{class}",
+    "visited:{class}",
+    "disabled:{class}",
+    "group-hover:{class}",
+    "peer-hover:{class}",
+    "dark:{class}",
+];
+
+/// CSS properties recognised in inline `style` attributes.
+const CSS_PROPERTIES: &[&str] = &[
+    "display",
+    "flex-direction",
+    "font-size",
+    "font-weight",
+    "font-style",
+    "color",
+    "background-color",
+    "background",
+    "background-image",
+    "text-align",
+    "text-decoration",
+    "text-decoration-line",
+    "vertical-align",
+    "caption-side",
+    "list-style-type",
+    "list-style",
+    "width",
+    "min-width",
+    "max-width",
+    "height",
+    "aspect-ratio",
+    "margin",
+    "margin-top",
+    "margin-right",
+    "margin-bottom",
+    "margin-left",
+    "padding",
+    "padding-top",
+    "padding-right",
+    "padding-bottom",
+    "padding-left",
+    "border-width",
+    "border",
+    "border-color",
+    "border-top",
+    "border-right",
+    "border-bottom",
+    "border-left",
+    "border-top-width",
+    "border-right-width",
+    "border-bottom-width",
+    "border-left-width",
+    "border-top-color",
+    "border-right-color",
+    "border-bottom-color",
+    "border-left-color",
+    "line-height",
+    "letter-spacing",
+    "gap",
+    "row-gap",
+    "grid-template-columns",
+    "grid-template-rows",
+    "grid-column",
+    "grid-row",
+    "columns",
+    "column-count",
+    "column-gap",
+    "overflow-x",
+    "overflow",
+    "visibility",
+    "white-space",
+    "overflow-wrap",
+    "word-wrap",
+    "tab-size",
+    "direction",
+    "object-fit",
+    "break-after",
+    "break-before",
+    "page-break-before",
+    "page-break-after",
+    "page-break-inside",
+];
+
+/// Every fixed Tailwind utility class understood by the style resolver.
+///
+/// Intended for tooling such as editor autocompletion; parameterised
+/// families are listed separately by [`supported_tailwind_patterns`].
+pub fn supported_tailwind_classes() -> Vec<&'static str> {
+    TAILWIND_CLASSES.iter().map(|(class, _)| *class).collect()
+}
+
+/// Parameterised Tailwind class families understood by the style resolver,
+/// as templates such as `p-{n}`, `bg-{color}` or `md:{class}`.
+pub fn supported_tailwind_patterns() -> Vec<&'static str> {
+    TAILWIND_PATTERNS.to_vec()
+}
+
+/// Every CSS property honoured in inline `style` attributes.
+pub fn supported_css_properties() -> Vec<&'static str> {
+    CSS_PROPERTIES.to_vec()
+}
+
 /// Resolve the style for an element, inheriting text properties from its parent.
 pub fn resolve_style(element: &ElementNode, parent: Option<&ComputedStyle>) -> ComputedStyle {
-    let mut style = base_style_for_tag(&element.tag);
+    resolve_style_with_theme(element, parent, &Theme::default())
+}
+
+/// Like [`resolve_style`], but with tag defaults taken from `theme`.
+pub fn resolve_style_with_theme(
+    element: &ElementNode,
+    parent: Option<&ComputedStyle>,
+    theme: &Theme,
+) -> ComputedStyle {
+    let mut style = base_style_for_tag(&element.tag, theme);
 
     // Inherit text properties from parent
     if let Some(p) = parent {
@@ -255,43 +986,158 @@ pub fn resolve_style(element: &ElementNode, parent: Option<&ComputedStyle>) -> C
         style.color = p.color;
         style.text_align = p.text_align;
         style.line_height = p.line_height;
+        style.letter_spacing = p.letter_spacing;
         style.font_style = p.font_style;
+        style.caption_side = p.caption_side;
+        style.baseline_shift = p.baseline_shift;
+        style.list_style_type = p.list_style_type;
+        style.list_marker_gap = p.list_marker_gap;
+        style.white_space = p.white_space;
+        style.overflow_wrap = p.overflow_wrap;
+        style.tab_size = p.tab_size;
+        style.direction = p.direction;
+        style.visibility = p.visibility;
+        style.text_decoration = p.text_decoration;
+    }
+    let inherited_font_size = style.font_size;
+
+    // Tags whose text defaults replace the inherited values.
+    match element.tag {
+        Tag::H1 => style.font_size *= H1_SCALE,
+        Tag::H2 => style.font_size *= H2_SCALE,
+        Tag::H3 => style.font_size *= H3_SCALE,
+        Tag::Caption => style.text_align = TextAlign::Center,
+        Tag::Pre => style.white_space = WhiteSpace::Pre,
+        Tag::Ul => {
+            style.list_style_type = ListStyleType::Disc;
+            style.list_marker_gap = theme.list_marker_gap;
+        }
+        Tag::Ol => {
+            style.list_style_type = ListStyleType::Decimal;
+            style.list_marker_gap = theme.list_marker_gap;
+        }
+        Tag::Blockquote => {
+            style.font_style = FontStyle::Italic;
+            style.color = Color {
+                r: 0.294,
+                g: 0.333,
+                b: 0.388,
+                a: 1.0,
+            };
+        }
+        Tag::B | Tag::Strong => style.font_weight = FontWeight::Bold,
+        Tag::I | Tag::Em => style.font_style = FontStyle::Italic,
+        Tag::Small => style.font_size *= SMALLER_SCALE,
+        Tag::Del => style.text_decoration = TextDecoration::LineThrough,
+        Tag::Ins => style.text_decoration = TextDecoration::Underline,
+        Tag::Sub | Tag::Sup => {
+            let parent_size = style.font_size;
+            style.font_size = parent_size * SCRIPT_SCALE;
+            style.baseline_shift += if element.tag == Tag::Sup {
+                SUPERSCRIPT_SHIFT * parent_size
+            } else {
+                -SUBSCRIPT_SHIFT * parent_size
+            };
+        }
+        // The legacy `width` attribute is a presentational hint, so classes
+        // and inline styles still override it.
+        Tag::Col => {
+            if let Some(width) = element.attributes.get("width") {
+                style.width = parse_dimension(width);
+            }
+        }
+        _ => {}
     }
 
-    // Apply Tailwind classes
-    for class in element.classes() {
-        apply_tailwind_class(&mut style, class);
+    let inherited_direction = style.direction;
+    match element
+        .attributes
+        .get("dir")
+        .map(|d| d.trim().to_ascii_lowercase())
+    {
+        Some(d) if d == "rtl" => style.direction = Direction::Rtl,
+        Some(d) if d == "ltr" => style.direction = Direction::Ltr,
+        _ => {}
+    }
+    // Like the `width` attribute, `hidden` is a hint that classes and inline
+    // styles can override.
+    if element.has_attr("hidden") {
+        style.display = Display::None;
     }
 
-    // Apply inline style attribute
-    if let Some(inline) = element.inline_style() {
-        apply_inline_style(&mut style, inline);
+    // Apply Tailwind classes, then the inline style attribute.
+    let mut classes = element.classes();
+    classes.sort_by_key(|class| variant_layer(class));
+    let apply_authored = |s: &mut ComputedStyle| {
+        for class in &classes {
+            apply_tailwind_class(s, class);
+        }
+        if let Some(inline) = element.inline_style() {
+            apply_inline_style(s, inline, inherited_font_size);
+        }
+    };
+    let unauthored = style.clone();
+    apply_authored(&mut style);
+    // Line heights given in points, such as `leading-6`, are kept as a
+    // multiple of the font size, so they must see the element's final one
+    // even when a later class sets it: author a copy that starts from that
+    // size and take its line height.
+    let mut sized = unauthored.clone();
+    sized.font_size = style.font_size;
+    apply_authored(&mut sized);
+    style.line_height = sized.line_height;
+
+    // Text aligned to the start of its line and list indents follow the
+    // direction unless classes or inline styles set them, even to the value
+    // they already had. Authoring a copy whose alignment differs and whose
+    // inline padding is unset (NaN) shows which of them were set.
+    let flip_align = style.direction != inherited_direction;
+    let swap_padding =
+        matches!(element.tag, Tag::Ul | Tag::Ol) && style.direction == Direction::Rtl;
+    if flip_align || swap_padding {
+        let mut probe = unauthored;
+        probe.text_align = match probe.text_align {
+            TextAlign::Left => TextAlign::Right,
+            _ => TextAlign::Left,
+        };
+        (probe.padding_left, probe.padding_right) = (f32::NAN, f32::NAN);
+        apply_authored(&mut probe);
+        if flip_align && probe.text_align != style.text_align {
+            style.text_align = match (style.text_align, style.direction) {
+                (TextAlign::Left, Direction::Rtl) => TextAlign::Right,
+                (TextAlign::Right, Direction::Ltr) => TextAlign::Left,
+                (align, _) => align,
+            };
+        }
+        if swap_padding && probe.padding_left.is_nan() && probe.padding_right.is_nan() {
+            (style.padding_left, style.padding_right) = (style.padding_right, style.padding_left);
+        }
     }
 
     style
 }
 
 /// Default styles based on tag semantics.
-fn base_style_for_tag(tag: &Tag) -> ComputedStyle {
+fn base_style_for_tag(tag: &Tag, theme: &Theme) -> ComputedStyle {
     let mut s = ComputedStyle::default();
     match tag {
         Tag::H1 => {
-            s.font_size = 32.0;
             s.font_weight = FontWeight::Bold;
             s.margin_top = 16.0;
             s.margin_bottom = 12.0;
+            s.keep_with_next = true;
         }
         Tag::H2 => {
-            s.font_size = 24.0;
             s.font_weight = FontWeight::Bold;
             s.margin_top = 14.0;
             s.margin_bottom = 10.0;
+            s.keep_with_next = true;
         }
         Tag::H3 => {
-            s.font_size = 20.0;
             s.font_weight = FontWeight::Bold;
             s.margin_top = 12.0;
             s.margin_bottom = 8.0;
+            s.keep_with_next = true;
         }
         Tag::P => {
             s.margin_top = 0.0;
@@ -300,7 +1146,23 @@ fn base_style_for_tag(tag: &Tag) -> ComputedStyle {
         Tag::Ul | Tag::Ol => {
             s.margin_top = 0.0;
             s.margin_bottom = 10.0;
-            s.padding_left = 24.0;
+            s.padding_left = theme.list_indent;
+        }
+        Tag::Pre => {
+            s.margin_bottom = 10.0;
+        }
+        Tag::Blockquote => {
+            // The accent bar counts towards the indent so the text starts
+            // `blockquote_indent` in from the edge.
+            s.margin_bottom = 10.0;
+            s.border_left_width = BLOCKQUOTE_BAR_WIDTH;
+            s.border_left_color = Color {
+                r: 0.831,
+                g: 0.843,
+                b: 0.871,
+                a: 1.0,
+            };
+            s.padding_left = (theme.blockquote_indent - BLOCKQUOTE_BAR_WIDTH).max(0.0);
         }
         Tag::Li => {
             s.display = Display::ListItem;
@@ -308,9 +1170,13 @@ fn base_style_for_tag(tag: &Tag) -> ComputedStyle {
         }
         Tag::Table => {
             s.display = Display::Grid;
-            s.border_width = 1.0;
+            s.set_border_width(1.0);
             s.page_break_inside_avoid = false; // tables can split
         }
+        Tag::Caption => {
+            s.padding_top = 4.0;
+            s.padding_bottom = 4.0;
+        }
         Tag::Tr => {
             s.display = Display::TableRow;
         }
@@ -320,7 +1186,7 @@ fn base_style_for_tag(tag: &Tag) -> ComputedStyle {
             s.padding_right = 8.0;
             s.padding_bottom = 4.0;
             s.padding_left = 8.0;
-            s.border_width = 1.0;
+            s.set_border_width(1.0);
             if *tag == Tag::Th {
                 s.font_weight = FontWeight::Bold;
                 s.background_color = Color {
@@ -331,130 +1197,108 @@ fn base_style_for_tag(tag: &Tag) -> ComputedStyle {
                 };
             }
         }
-        Tag::Span => {
+        Tag::Span
+        | Tag::A
+        | Tag::Sub
+        | Tag::Sup
+        | Tag::B
+        | Tag::Strong
+        | Tag::I
+        | Tag::Em
+        | Tag::Del
+        | Tag::Ins
+        | Tag::Small => {
+            s.display = Display::Inline;
+        }
+        Tag::Mark => {
             s.display = Display::Inline;
+            s.background_color = MARK_BACKGROUND;
         }
         Tag::Img => {
             s.display = Display::InlineBlock;
         }
-        Tag::Div | Tag::Body | Tag::Html | Tag::Head => {}
-        Tag::Unknown(_) => {
+        Tag::Div | Tag::Colgroup | Tag::Col | Tag::Body | Tag::Html | Tag::Head => {}
+        Tag::Script | Tag::Style | Tag::Unknown(_) => {
             // Silently skip unrecognised elements – treat as display:none.
             s.display = Display::None;
         }
     }
+    s.margin_top *= theme.spacing_scale;
+    s.margin_bottom *= theme.spacing_scale;
     s
 }
 
-/// Apply a single Tailwind utility class.
-fn apply_tailwind_class(s: &mut ComputedStyle, class: &str) {
-    match class {
-        // Display
-        "flex" => s.display = Display::Flex,
-        "grid" => s.display = Display::Grid,
-        "block" => s.display = Display::Block,
-        "inline" => s.display = Display::Inline,
-        "inline-block" => s.display = Display::InlineBlock,
-        "hidden" => s.display = Display::None,
-
-        // Flex direction
-        "flex-row" => s.flex_direction = FlexDirection::Row,
-        "flex-col" => s.flex_direction = FlexDirection::Column,
-
-        // Flex wrap
-        "flex-wrap" => s.flex_wrap = FlexWrap::Wrap,
-        "flex-nowrap" => s.flex_wrap = FlexWrap::NoWrap,
-
-        // Flex grow/shrink
-        "flex-grow" | "grow" => s.flex_grow = 1.0,
-        "flex-shrink" | "shrink" => s.flex_shrink = 1.0,
-        "flex-1" => {
-            s.flex_grow = 1.0;
-            s.flex_shrink = 1.0;
-        }
-
-        // Justify content
-        "justify-start" => s.justify_content = JustifyContent::Start,
-        "justify-end" => s.justify_content = JustifyContent::End,
-        "justify-center" => s.justify_content = JustifyContent::Center,
-        "justify-between" => s.justify_content = JustifyContent::SpaceBetween,
-        "justify-around" => s.justify_content = JustifyContent::SpaceAround,
-        "justify-evenly" => s.justify_content = JustifyContent::SpaceEvenly,
-
-        // Align items
-        "items-start" => s.align_items = AlignItems::Start,
-        "items-end" => s.align_items = AlignItems::End,
-        "items-center" => s.align_items = AlignItems::Center,
-        "items-stretch" => s.align_items = AlignItems::Stretch,
-
-        // Font weight
-        "font-bold" => s.font_weight = FontWeight::Bold,
-        "font-normal" => s.font_weight = FontWeight::Normal,
-
-        // Font style
-        "italic" => s.font_style = FontStyle::Italic,
-        "not-italic" => s.font_style = FontStyle::Normal,
-
-        // Text decoration
-        "underline" => s.text_decoration = TextDecoration::Underline,
-        "no-underline" => s.text_decoration = TextDecoration::None,
-
-        // Text alignment
-        "text-left" => s.text_align = TextAlign::Left,
-        "text-center" => s.text_align = TextAlign::Center,
-        "text-right" => s.text_align = TextAlign::Right,
-
-        // Font sizes
-        "text-xs" => s.font_size = 12.0,
-        "text-sm" => s.font_size = 14.0,
-        "text-base" => s.font_size = 16.0,
-        "text-lg" => s.font_size = 18.0,
-        "text-xl" => s.font_size = 20.0,
-        "text-2xl" => s.font_size = 24.0,
-        "text-3xl" => s.font_size = 30.0,
-        "text-4xl" => s.font_size = 36.0,
-
-        // Width
-        "w-full" => s.width = Dimension::Percent(100.0),
-        "w-auto" => s.width = Dimension::Auto,
-        "w-1/2" => s.width = Dimension::Percent(50.0),
-        "w-1/3" => s.width = Dimension::Percent(33.333),
-        "w-2/3" => s.width = Dimension::Percent(66.666),
-        "w-1/4" => s.width = Dimension::Percent(25.0),
-        "w-3/4" => s.width = Dimension::Percent(75.0),
-
-        // Page break
-        "break-before" => s.page_break_before = true,
-        "break-after" => s.page_break_after = true,
-        "break-inside-avoid" => s.page_break_inside_avoid = true,
-        // Convenience classes for explicit page breaks in templates
-        "page" | "page-break" => s.page_break_after = true,
-
-        _ => {
-            // Dynamic patterns
-            try_parse_spacing_class(s, class);
-            try_parse_color_class(s, class);
-            try_parse_gap_class(s, class);
-            try_parse_grid_cols_class(s, class);
-            try_parse_width_class(s, class);
-            try_parse_height_class(s, class);
-        }
-    }
-}
-
-fn try_parse_spacing_class(s: &mut ComputedStyle, class: &str) {
-    // p-{n}, px-{n}, py-{n}, pt-{n}, etc.  (1 unit = 4px)
-    // m-{n}, mx-{n}, my-{n}, mt-{n}, etc.
-    let parts: Vec<&str> = class.rsplitn(2, '-').collect();
-    if parts.len() != 2 {
-        return;
-    }
-    let value_str = parts[0];
-    let prefix = parts[1];
-    let value: f32 = match value_str.parse::<f32>() {
-        Ok(v) => v * 4.0,
-        Err(_) => return,
+/// Apply a single Tailwind utility class. Returns `false` for classes we
+/// don't recognise (which are ignored).
+fn apply_tailwind_class(s: &mut ComputedStyle, class: &str) -> bool {
+    if let Some((_, apply)) = TAILWIND_CLASSES.iter().find(|(name, _)| *name == class) {
+        apply(s);
+        return true;
+    }
+    // Dynamic patterns
+    try_parse_variant_class(s, class)
+        || try_parse_spacing_class(s, class)
+        || try_parse_color_class(s, class)
+        || try_parse_gap_class(s, class)
+        || try_parse_border_width_class(s, class)
+        || try_parse_grid_template_class(s, class)
+        || try_parse_grid_span_class(s, class)
+        || try_parse_columns_class(s, class)
+        || try_parse_leading_class(s, class)
+        || try_parse_width_class(s, class)
+        || try_parse_height_class(s, class)
+        || try_parse_aspect_class(s, class)
+        || try_parse_arbitrary_class(s, class)
+}
+
+/// Variant-prefixed classes such as `md:flex` or `hover:bg-blue-500`.
+///
+/// A page is laid out once, at a fixed width, so breakpoint prefixes apply
+/// their utility on top of the mobile-first base, as on a wide screen;
+/// `print:` always applies. Interaction and theme states never occur on
+/// paper, so those classes are recognised but have no effect.
+fn try_parse_variant_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let Some((variant, utility)) = class.split_once(':') else {
+        return false;
     };
+    match variant {
+        "sm" | "md" | "lg" | "xl" | "2xl" | "print" => apply_tailwind_class(s, utility),
+        "hover" | "focus" | "focus-within" | "focus-visible" | "active" | "visited"
+        | "disabled" | "group-hover" | "peer-hover" | "dark" => true,
+        _ => false,
+    }
+}
+
+/// When a class applies relative to the element's others: unprefixed
+/// utilities first, then breakpoint variants from `sm:` up to `2xl:`, then
+/// `print:`, so a wider breakpoint wins whatever the order in `class`.
+fn variant_layer(class: &str) -> usize {
+    let variant = class.split_once(':').map_or("", |(variant, _)| variant);
+    ["", "sm", "md", "lg", "xl", "2xl", "print"]
+        .iter()
+        .position(|&layer| layer == variant)
+        .unwrap_or(0)
+}
+
+fn try_parse_spacing_class(s: &mut ComputedStyle, class: &str) -> bool {
+    // p-{n}, px-{n}, py-{n}, pt-{n}, etc.  (1 unit = 4px, n may be 0.5, 2.5…)
+    // m-{n}, mx-{n}, my-{n}, mt-{n}, etc.; -m-{n}, -mt-{n}… negate margins.
+    let (negative, class) = match class.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, class),
+    };
+    let Some((prefix, value_str)) = class.rsplit_once('-') else {
+        return false;
+    };
+    let Some(mut value) = spacing_value(value_str) else {
+        return false;
+    };
+    if negative {
+        if !prefix.starts_with('m') {
+            return false;
+        }
+        value = -value;
+    }
 
     match prefix {
         "p" => {
@@ -493,191 +1337,479 @@ fn try_parse_spacing_class(s: &mut ComputedStyle, class: &str) {
         "mr" => s.margin_right = value,
         "mb" => s.margin_bottom = value,
         "ml" => s.margin_left = value,
+        _ => return false,
+    }
+    true
+}
+
+/// A spacing-scale step such as `4` or `0.5`, in px (1 step = 4px).
+fn spacing_value(step: &str) -> Option<f32> {
+    if step.is_empty() || !step.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return None;
+    }
+    step.parse::<f32>().ok().map(|v| v * 4.0)
+}
+
+/// Shades of each hue in [`PALETTE`], in order.
+const PALETTE_SHADES: [u16; 11] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900, 950];
+
+/// Tailwind's default colour palette as `0xRRGGBB`, one entry per shade in
+/// [`PALETTE_SHADES`].
+const PALETTE: &[(&str, [u32; 11])] = &[
+    (
+        "slate",
+        [
+            0xf8fafc, 0xf1f5f9, 0xe2e8f0, 0xcbd5e1, 0x94a3b8, 0x64748b, 0x475569, 0x334155,
+            0x1e293b, 0x0f172a, 0x020617,
+        ],
+    ),
+    (
+        "gray",
+        [
+            0xf9fafb, 0xf3f4f6, 0xe5e7eb, 0xd1d5db, 0x9ca3af, 0x6b7280, 0x4b5563, 0x374151,
+            0x1f2937, 0x111827, 0x030712,
+        ],
+    ),
+    (
+        "zinc",
+        [
+            0xfafafa, 0xf4f4f5, 0xe4e4e7, 0xd4d4d8, 0xa1a1aa, 0x71717a, 0x52525b, 0x3f3f46,
+            0x27272a, 0x18181b, 0x09090b,
+        ],
+    ),
+    (
+        "neutral",
+        [
+            0xfafafa, 0xf5f5f5, 0xe5e5e5, 0xd4d4d4, 0xa3a3a3, 0x737373, 0x525252, 0x404040,
+            0x262626, 0x171717, 0x0a0a0a,
+        ],
+    ),
+    (
+        "stone",
+        [
+            0xfafaf9, 0xf5f5f4, 0xe7e5e4, 0xd6d3d1, 0xa8a29e, 0x78716c, 0x57534e, 0x44403c,
+            0x292524, 0x1c1917, 0x0c0a09,
+        ],
+    ),
+    (
+        "red",
+        [
+            0xfef2f2, 0xfee2e2, 0xfecaca, 0xfca5a5, 0xf87171, 0xef4444, 0xdc2626, 0xb91c1c,
+            0x991b1b, 0x7f1d1d, 0x450a0a,
+        ],
+    ),
+    (
+        "orange",
+        [
+            0xfff7ed, 0xffedd5, 0xfed7aa, 0xfdba74, 0xfb923c, 0xf97316, 0xea580c, 0xc2410c,
+            0x9a3412, 0x7c2d12, 0x431407,
+        ],
+    ),
+    (
+        "amber",
+        [
+            0xfffbeb, 0xfef3c7, 0xfde68a, 0xfcd34d, 0xfbbf24, 0xf59e0b, 0xd97706, 0xb45309,
+            0x92400e, 0x78350f, 0x451a03,
+        ],
+    ),
+    (
+        "yellow",
+        [
+            0xfefce8, 0xfef9c3, 0xfef08a, 0xfde047, 0xfacc15, 0xeab308, 0xca8a04, 0xa16207,
+            0x854d0e, 0x713f12, 0x422006,
+        ],
+    ),
+    (
+        "lime",
+        [
+            0xf7fee7, 0xecfccb, 0xd9f99d, 0xbef264, 0xa3e635, 0x84cc16, 0x65a30d, 0x4d7c0f,
+            0x3f6212, 0x365314, 0x1a2e05,
+        ],
+    ),
+    (
+        "green",
+        [
+            0xf0fdf4, 0xdcfce7, 0xbbf7d0, 0x86efac, 0x4ade80, 0x22c55e, 0x16a34a, 0x15803d,
+            0x166534, 0x14532d, 0x052e16,
+        ],
+    ),
+    (
+        "emerald",
+        [
+            0xecfdf5, 0xd1fae5, 0xa7f3d0, 0x6ee7b7, 0x34d399, 0x10b981, 0x059669, 0x047857,
+            0x065f46, 0x064e3b, 0x022c22,
+        ],
+    ),
+    (
+        "teal",
+        [
+            0xf0fdfa, 0xccfbf1, 0x99f6e4, 0x5eead4, 0x2dd4bf, 0x14b8a6, 0x0d9488, 0x0f766e,
+            0x115e59, 0x134e4a, 0x042f2e,
+        ],
+    ),
+    (
+        "cyan",
+        [
+            0xecfeff, 0xcffafe, 0xa5f3fc, 0x67e8f9, 0x22d3ee, 0x06b6d4, 0x0891b2, 0x0e7490,
+            0x155e75, 0x164e63, 0x083344,
+        ],
+    ),
+    (
+        "sky",
+        [
+            0xf0f9ff, 0xe0f2fe, 0xbae6fd, 0x7dd3fc, 0x38bdf8, 0x0ea5e9, 0x0284c7, 0x0369a1,
+            0x075985, 0x0c4a6e, 0x082f49,
+        ],
+    ),
+    (
+        "blue",
+        [
+            0xeff6ff, 0xdbeafe, 0xbfdbfe, 0x93c5fd, 0x60a5fa, 0x3b82f6, 0x2563eb, 0x1d4ed8,
+            0x1e40af, 0x1e3a8a, 0x172554,
+        ],
+    ),
+    (
+        "indigo",
+        [
+            0xeef2ff, 0xe0e7ff, 0xc7d2fe, 0xa5b4fc, 0x818cf8, 0x6366f1, 0x4f46e5, 0x4338ca,
+            0x3730a3, 0x312e81, 0x1e1b4b,
+        ],
+    ),
+    (
+        "violet",
+        [
+            0xf5f3ff, 0xede9fe, 0xddd6fe, 0xc4b5fd, 0xa78bfa, 0x8b5cf6, 0x7c3aed, 0x6d28d9,
+            0x5b21b6, 0x4c1d95, 0x2e1065,
+        ],
+    ),
+    (
+        "purple",
+        [
+            0xfaf5ff, 0xf3e8ff, 0xe9d5ff, 0xd8b4fe, 0xc084fc, 0xa855f7, 0x9333ea, 0x7e22ce,
+            0x6b21a8, 0x581c87, 0x3b0764,
+        ],
+    ),
+    (
+        "fuchsia",
+        [
+            0xfdf4ff, 0xfae8ff, 0xf5d0fe, 0xf0abfc, 0xe879f9, 0xd946ef, 0xc026d3, 0xa21caf,
+            0x86198f, 0x701a75, 0x4a044e,
+        ],
+    ),
+    (
+        "pink",
+        [
+            0xfdf2f8, 0xfce7f3, 0xfbcfe8, 0xf9a8d4, 0xf472b6, 0xec4899, 0xdb2777, 0xbe185d,
+            0x9d174d, 0x831843, 0x500724,
+        ],
+    ),
+    (
+        "rose",
+        [
+            0xfff1f2, 0xffe4e6, 0xfecdd3, 0xfda4af, 0xfb7185, 0xf43f5e, 0xe11d48, 0xbe123c,
+            0x9f1239, 0x881337, 0x4c0519,
+        ],
+    ),
+];
+
+/// A palette colour by class suffix: `{hue}-{shade}`, `white` or `black`.
+fn palette_color(name: &str) -> Option<Color> {
+    match name {
+        "white" => return Some(Color::WHITE),
+        "black" => return Some(Color::BLACK),
         _ => {}
     }
+    let (hue, shade) = name.rsplit_once('-')?;
+    let shade: u16 = shade.parse().ok()?;
+    let shade = PALETTE_SHADES.iter().position(|&s| s == shade)?;
+    let (_, shades) = PALETTE.iter().find(|(name, _)| *name == hue)?;
+    let channel = |shift: u32| ((shades[shade] >> shift) & 0xff) as f32 / 255.0;
+    Some(Color {
+        r: channel(16),
+        g: channel(8),
+        b: channel(0),
+        a: 1.0,
+    })
 }
 
-fn try_parse_color_class(s: &mut ComputedStyle, class: &str) {
-    // Tailwind color subset: text-{color}, bg-{color}
-    let colors = [
-        (
-            "red-500",
-            Color {
-                r: 0.937,
-                g: 0.267,
-                b: 0.267,
-                a: 1.0,
-            },
-        ),
-        (
-            "red-700",
-            Color {
-                r: 0.725,
-                g: 0.110,
-                b: 0.110,
-                a: 1.0,
-            },
-        ),
-        (
-            "blue-500",
-            Color {
-                r: 0.231,
-                g: 0.510,
-                b: 0.965,
-                a: 1.0,
-            },
-        ),
-        (
-            "blue-700",
-            Color {
-                r: 0.102,
-                g: 0.306,
-                b: 0.827,
-                a: 1.0,
-            },
-        ),
-        (
-            "green-500",
-            Color {
-                r: 0.133,
-                g: 0.773,
-                b: 0.369,
-                a: 1.0,
-            },
-        ),
-        (
-            "green-700",
-            Color {
-                r: 0.082,
-                g: 0.533,
-                b: 0.247,
-                a: 1.0,
-            },
-        ),
-        (
-            "gray-100",
-            Color {
-                r: 0.953,
-                g: 0.957,
-                b: 0.961,
-                a: 1.0,
-            },
-        ),
-        (
-            "gray-200",
-            Color {
-                r: 0.898,
-                g: 0.906,
-                b: 0.922,
-                a: 1.0,
-            },
-        ),
-        (
-            "gray-300",
-            Color {
-                r: 0.831,
-                g: 0.843,
-                b: 0.871,
-                a: 1.0,
-            },
-        ),
-        (
-            "gray-500",
-            Color {
-                r: 0.424,
-                g: 0.447,
-                b: 0.502,
-                a: 1.0,
-            },
-        ),
-        (
-            "gray-700",
-            Color {
-                r: 0.216,
-                g: 0.255,
-                b: 0.318,
-                a: 1.0,
-            },
-        ),
-        (
-            "gray-900",
-            Color {
-                r: 0.067,
-                g: 0.094,
-                b: 0.153,
-                a: 1.0,
-            },
-        ),
-        ("white", Color::WHITE),
-        ("black", Color::BLACK),
-        (
-            "yellow-500",
-            Color {
-                r: 0.918,
-                g: 0.788,
-                b: 0.153,
-                a: 1.0,
-            },
-        ),
-    ];
+fn try_parse_color_class(s: &mut ComputedStyle, class: &str) -> bool {
+    // text-{color}, bg-{color}, border-{color}
+    if let Some(color) = class.strip_prefix("text-").and_then(palette_color) {
+        s.color = color;
+    } else if let Some(color) = class.strip_prefix("bg-").and_then(palette_color) {
+        s.background_color = color;
+    } else if let Some(color) = class.strip_prefix("border-").and_then(palette_color) {
+        s.set_border_color(color);
+    } else {
+        return false;
+    }
+    true
+}
 
-    for (name, color) in &colors {
-        if class == format!("text-{}", name) {
-            s.color = *color;
-            return;
-        }
-        if class == format!("bg-{}", name) {
-            s.background_color = *color;
-            return;
+/// `border`, `border-2`, `border-t`, `border-x-4`, …: border widths in px on
+/// all or some sides (a bare side means 1 px).
+fn try_parse_border_width_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let rest = match class.strip_prefix("border") {
+        Some("") => "",
+        Some(rest) => match rest.strip_prefix('-') {
+            Some(rest) => rest,
+            None => return false,
+        },
+        None => return false,
+    };
+    let (side, width) = match rest.split_once('-') {
+        Some((side, n)) => (side, n.parse::<f32>().ok()),
+        None => match rest.parse::<f32>() {
+            Ok(n) => ("", Some(n)),
+            Err(_) => (rest, Some(1.0)),
+        },
+    };
+    let sides: &[&str] = match side {
+        "" => &["top", "right", "bottom", "left"],
+        "t" => &["top"],
+        "r" => &["right"],
+        "b" => &["bottom"],
+        "l" => &["left"],
+        "x" => &["left", "right"],
+        "y" => &["top", "bottom"],
+        _ => return false,
+    };
+    let Some(width) = width else {
+        return false;
+    };
+    set_border_widths(s, sides, width);
+    true
+}
+
+fn set_border_widths(s: &mut ComputedStyle, sides: &[&str], width: f32) {
+    for side in sides {
+        if let Some((w, _)) = border_side(s, side) {
+            *w = width;
         }
     }
+}
 
-    // border-{color}
-    for (name, color) in &colors {
-        if class == format!("border-{}", name) {
-            s.border_color = *color;
-            return;
+fn try_parse_gap_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let Some(rest) = class.strip_prefix("gap-") else {
+        return false;
+    };
+    let (axis, n) = match rest.split_once('-') {
+        Some((axis @ ("x" | "y"), n)) => (axis, n),
+        _ => ("", rest),
+    };
+    let Some(v) = spacing_value(n) else {
+        return false;
+    };
+    if axis != "y" {
+        s.column_gap = Some(v);
+    }
+    if axis != "x" {
+        s.row_gap = v;
+    }
+    true
+}
+
+fn try_parse_columns_class(s: &mut ComputedStyle, class: &str) -> bool {
+    if let Some(rest) = class.strip_prefix("columns-") {
+        if let Ok(n) = rest.parse::<u32>() {
+            s.column_count = n.max(1);
+            return true;
         }
     }
+    false
 }
 
-fn try_parse_gap_class(s: &mut ComputedStyle, class: &str) {
-    if let Some(rest) = class.strip_prefix("gap-") {
-        if let Ok(v) = rest.parse::<f32>() {
-            s.gap = v * 4.0;
+/// `leading-{n}`: a line height of `n` × 4px, like CSS `line-height: 24px`,
+/// whatever font size the element's other classes set.
+fn try_parse_leading_class(s: &mut ComputedStyle, class: &str) -> bool {
+    if let Some(rest) = class.strip_prefix("leading-") {
+        if let Ok(n) = rest.parse::<f32>() {
+            s.line_height = n * 4.0 / s.font_size;
+            return true;
         }
     }
+    false
 }
 
-fn try_parse_grid_cols_class(s: &mut ComputedStyle, class: &str) {
-    if let Some(rest) = class.strip_prefix("grid-cols-") {
-        if let Ok(n) = rest.parse::<usize>() {
-            s.grid_template_columns = vec![GridTrack::Fr(1.0); n];
+fn try_parse_grid_span_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let (span, rest) = if let Some(rest) = class.strip_prefix("col-span-") {
+        (&mut s.grid_column_span, rest)
+    } else if let Some(rest) = class.strip_prefix("row-span-") {
+        (&mut s.grid_row_span, rest)
+    } else {
+        return false;
+    };
+    match rest.parse::<u16>() {
+        Ok(n) => {
+            *span = n.max(1);
+            true
         }
+        Err(_) => false,
     }
 }
 
-fn try_parse_width_class(s: &mut ComputedStyle, class: &str) {
-    if let Some(rest) = class.strip_prefix("w-") {
-        if let Ok(v) = rest.parse::<f32>() {
-            s.width = Dimension::Px(v * 4.0);
+/// The `n` of a `grid-column` / `grid-row` value containing `span n`.
+fn parse_grid_span(val: &str) -> Option<u16> {
+    let mut parts = val.split_whitespace();
+    parts.find(|&part| part == "span")?;
+    parts.next()?.parse::<u16>().ok().map(|n| n.max(1))
+}
+
+/// `grid-cols-{n}` / `grid-rows-{n}` for `n` equal tracks, or
+/// `grid-cols-[200px_1fr]` for arbitrary tracks separated by `_`.
+fn try_parse_grid_template_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let (template, rest) = if let Some(rest) = class.strip_prefix("grid-cols-") {
+        (&mut s.grid_template_columns, rest)
+    } else if let Some(rest) = class.strip_prefix("grid-rows-") {
+        (&mut s.grid_template_rows, rest)
+    } else {
+        return false;
+    };
+    let tracks = match rest.parse::<usize>() {
+        Ok(n) => Some(vec![GridTrack::Fr(1.0); n]),
+        Err(_) => rest
+            .strip_prefix('[')
+            .and_then(|r| r.strip_suffix(']'))
+            .and_then(|r| parse_grid_tracks(&r.replace('_', " "))),
+    };
+    match tracks {
+        Some(tracks) => {
+            *template = tracks;
+            true
         }
+        None => false,
     }
 }
 
-fn try_parse_height_class(s: &mut ComputedStyle, class: &str) {
+/// Parse a whitespace-separated track list such as `200px 1fr auto`.
+fn parse_grid_tracks(val: &str) -> Option<Vec<GridTrack>> {
+    val.split_whitespace()
+        .map(|track| {
+            if track == "auto" {
+                Some(GridTrack::Auto)
+            } else if let Some(fr) = track.strip_suffix("fr") {
+                fr.parse().ok().map(GridTrack::Fr)
+            } else {
+                parse_px(track).map(GridTrack::Px)
+            }
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|tracks| !tracks.is_empty())
+}
+
+fn try_parse_width_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let (target, rest) = if let Some(rest) = class.strip_prefix("min-w-") {
+        (&mut s.min_width, rest)
+    } else if let Some(rest) = class.strip_prefix("max-w-") {
+        (&mut s.max_width, rest)
+    } else if let Some(rest) = class.strip_prefix("w-") {
+        (&mut s.width, rest)
+    } else {
+        return false;
+    };
+    if let Ok(v) = rest.parse::<f32>() {
+        *target = Dimension::Px(v * 4.0);
+        return true;
+    }
+    false
+}
+
+fn try_parse_height_class(s: &mut ComputedStyle, class: &str) -> bool {
     if let Some(rest) = class.strip_prefix("h-") {
         if let Ok(v) = rest.parse::<f32>() {
             s.height = Dimension::Px(v * 4.0);
+            return true;
+        }
+    }
+    false
+}
+
+/// Arbitrary values such as `w-[250px]`, `text-[14px]` or `bg-[#123456]`:
+/// the bracketed value, with `_` standing for spaces, is applied as the CSS
+/// property the prefix abbreviates. `text-` and `border-` take a colour or a
+/// size.
+fn try_parse_arbitrary_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let Some((prefix, value)) = class
+        .strip_suffix(']')
+        .and_then(|class| class.split_once("-["))
+    else {
+        return false;
+    };
+    let value = value.replace('_', " ");
+    let is_color = Color::from_css(&value).is_some();
+    let props: &[&str] = match prefix {
+        "text" if is_color => &["color"],
+        "text" => &["font-size"],
+        "bg" => &["background-color"],
+        "border" if is_color => &["border-color"],
+        "border" => &["border-width"],
+        "w" => &["width"],
+        "min-w" => &["min-width"],
+        "max-w" => &["max-width"],
+        "h" => &["height"],
+        "p" => &["padding"],
+        "px" => &["padding-left", "padding-right"],
+        "py" => &["padding-top", "padding-bottom"],
+        "pt" => &["padding-top"],
+        "pr" => &["padding-right"],
+        "pb" => &["padding-bottom"],
+        "pl" => &["padding-left"],
+        "m" => &["margin"],
+        "mx" => &["margin-left", "margin-right"],
+        "my" => &["margin-top", "margin-bottom"],
+        "mt" => &["margin-top"],
+        "mr" => &["margin-right"],
+        "mb" => &["margin-bottom"],
+        "ml" => &["margin-left"],
+        "gap" => &["gap"],
+        "gap-x" => &["column-gap"],
+        "gap-y" => &["row-gap"],
+        "leading" => &["line-height"],
+        _ => return false,
+    };
+    let font_size = s.font_size;
+    let mut handled = false;
+    for prop in props {
+        handled |= apply_css_property(s, prop, &value, font_size);
+    }
+    handled
+}
+
+/// `aspect-[4/3]`: an arbitrary width / height ratio.
+fn try_parse_aspect_class(s: &mut ComputedStyle, class: &str) -> bool {
+    let ratio = class
+        .strip_prefix("aspect-[")
+        .and_then(|r| r.strip_suffix(']'))
+        .and_then(parse_aspect_ratio);
+    match ratio {
+        Some(ratio) => {
+            s.aspect_ratio = ratio;
+            true
         }
+        None => false,
     }
 }
 
+/// Parse a CSS `aspect-ratio` value: `auto`, `16 / 9` or a single number.
+/// A zero or negative ratio is degenerate and resolves to `auto`.
+fn parse_aspect_ratio(val: &str) -> Option<Option<f32>> {
+    if val.trim() == "auto" {
+        return Some(None);
+    }
+    let ratio = match val.split_once('/') {
+        Some((w, h)) => w.trim().parse::<f32>().ok()? / h.trim().parse::<f32>().ok()?,
+        None => val.trim().parse::<f32>().ok()?,
+    };
+    Some((ratio.is_finite() && ratio > 0.0).then_some(ratio))
+}
+
 // ---------------------------------------------------------------------------
 // Inline style parsing (limited subset)
 // ---------------------------------------------------------------------------
 
-fn apply_inline_style(s: &mut ComputedStyle, style_str: &str) {
+/// Apply a `style` attribute; `parent_font_size` is the inherited size that
+/// relative sizes such as `font-size: smaller` scale.
+fn apply_inline_style(s: &mut ComputedStyle, style_str: &str, parent_font_size: f32) {
     for decl in style_str.split(';') {
         let decl = decl.trim();
         if decl.is_empty() {
@@ -692,11 +1824,18 @@ fn apply_inline_style(s: &mut ComputedStyle, style_str: &str) {
             Some(v) => v.trim(),
             None => continue,
         };
-        apply_css_property(s, prop, val);
+        apply_css_property(s, prop, val, parent_font_size);
     }
 }
 
-fn apply_css_property(s: &mut ComputedStyle, prop: &str, val: &str) {
+/// Apply a single CSS declaration. Returns `false`, leaving `s` unchanged,
+/// for unsupported properties and for values that do not parse.
+fn apply_css_property(s: &mut ComputedStyle, prop: &str, val: &str, parent_font_size: f32) -> bool {
+    // Only listed properties are honoured, so `supported_css_properties`
+    // cannot fall behind the arms below.
+    if !CSS_PROPERTIES.contains(&prop) {
+        return false;
+    }
     match prop {
         "display" => {
             s.display = match val {
@@ -706,21 +1845,26 @@ fn apply_css_property(s: &mut ComputedStyle, prop: &str, val: &str) {
                 "inline" => Display::Inline,
                 "inline-block" => Display::InlineBlock,
                 "none" => Display::None,
-                _ => s.display,
+                _ => return false,
             }
         }
         "flex-direction" => {
             s.flex_direction = match val {
                 "row" => FlexDirection::Row,
                 "column" => FlexDirection::Column,
-                _ => s.flex_direction,
+                _ => return false,
             }
         }
-        "font-size" => {
-            if let Some(px) = parse_px(val) {
+        "font-size" => match val {
+            "smaller" => s.font_size = parent_font_size * SMALLER_SCALE,
+            "larger" => s.font_size = parent_font_size / SMALLER_SCALE,
+            _ => {
+                let Some(px) = parse_px(val) else {
+                    return false;
+                };
                 s.font_size = px;
             }
-        }
+        },
         "font-weight" => {
             s.font_weight = match val {
                 "bold" | "700" | "800" | "900" => FontWeight::Bold,
@@ -734,15 +1878,26 @@ fn apply_css_property(s: &mut ComputedStyle, prop: &str, val: &str) {
             }
         }
         "color" => {
-            if let Some(c) = Color::from_hex(val) {
-                s.color = c;
-            }
+            let Some(c) = Color::from_css(val) else {
+                return false;
+            };
+            s.color = c;
         }
-        "background-color" | "background" => {
-            if let Some(c) = Color::from_hex(val) {
+        "background-color" | "background" | "background-image" => {
+            if let Some(g) = parse_linear_gradient(val) {
+                s.background_gradient = Some(g);
+            } else if let Some(c) = Color::from_css(val) {
                 s.background_color = c;
+            } else {
+                return false;
             }
         }
+        "text-decoration" | "text-decoration-line" => match val {
+            "underline" => s.text_decoration = TextDecoration::Underline,
+            "line-through" => s.text_decoration = TextDecoration::LineThrough,
+            "none" => s.text_decoration = TextDecoration::None,
+            _ => return false,
+        },
         "text-align" => {
             s.text_align = match val {
                 "center" => TextAlign::Center,
@@ -750,90 +1905,285 @@ fn apply_css_property(s: &mut ComputedStyle, prop: &str, val: &str) {
                 _ => TextAlign::Left,
             }
         }
+        "vertical-align" => {
+            s.vertical_align = match val {
+                "middle" => VerticalAlign::Middle,
+                "bottom" => VerticalAlign::Bottom,
+                _ => VerticalAlign::Top,
+            }
+        }
+        "caption-side" => {
+            s.caption_side = match val {
+                "bottom" => CaptionSide::Bottom,
+                _ => CaptionSide::Top,
+            }
+        }
+        "list-style-type" => {
+            let Some(kind) = ListStyleType::parse(val) else {
+                return false;
+            };
+            s.list_style_type = kind;
+        }
+        // Only the type part of the shorthand is supported.
+        "list-style" => {
+            let Some(kind) = val.split_whitespace().find_map(ListStyleType::parse) else {
+                return false;
+            };
+            s.list_style_type = kind;
+        }
         "width" => {
-            s.width = parse_dimension(val);
+            let Some(width) = try_parse_dimension(val) else {
+                return false;
+            };
+            s.width = width;
+        }
+        "min-width" => {
+            let Some(width) = try_parse_dimension(val) else {
+                return false;
+            };
+            s.min_width = width;
+        }
+        "max-width" => {
+            // `none` is not a length; it means no limit.
+            let Some(width) =
+                try_parse_dimension(val).or((val == "none").then_some(Dimension::Auto))
+            else {
+                return false;
+            };
+            s.max_width = width;
         }
         "height" => {
-            s.height = parse_dimension(val);
-        }
-        "margin" => apply_shorthand_spacing(
-            val,
-            &mut s.margin_top,
-            &mut s.margin_right,
-            &mut s.margin_bottom,
-            &mut s.margin_left,
-        ),
+            let Some(height) = try_parse_dimension(val) else {
+                return false;
+            };
+            s.height = height;
+        }
+        "margin" => {
+            return apply_shorthand_spacing(
+                val,
+                &mut s.margin_top,
+                &mut s.margin_right,
+                &mut s.margin_bottom,
+                &mut s.margin_left,
+            )
+        }
         "margin-top" => {
-            if let Some(px) = parse_px(val) {
-                s.margin_top = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.margin_top = px;
         }
         "margin-right" => {
-            if let Some(px) = parse_px(val) {
-                s.margin_right = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.margin_right = px;
         }
         "margin-bottom" => {
-            if let Some(px) = parse_px(val) {
-                s.margin_bottom = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.margin_bottom = px;
         }
         "margin-left" => {
-            if let Some(px) = parse_px(val) {
-                s.margin_left = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.margin_left = px;
+        }
+        "padding" => {
+            return apply_shorthand_spacing(
+                val,
+                &mut s.padding_top,
+                &mut s.padding_right,
+                &mut s.padding_bottom,
+                &mut s.padding_left,
+            )
         }
-        "padding" => apply_shorthand_spacing(
-            val,
-            &mut s.padding_top,
-            &mut s.padding_right,
-            &mut s.padding_bottom,
-            &mut s.padding_left,
-        ),
         "padding-top" => {
-            if let Some(px) = parse_px(val) {
-                s.padding_top = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.padding_top = px;
         }
         "padding-right" => {
-            if let Some(px) = parse_px(val) {
-                s.padding_right = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.padding_right = px;
         }
         "padding-bottom" => {
-            if let Some(px) = parse_px(val) {
-                s.padding_bottom = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.padding_bottom = px;
         }
         "padding-left" => {
-            if let Some(px) = parse_px(val) {
-                s.padding_left = px;
-            }
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.padding_left = px;
         }
-        "border-width" | "border" => {
-            if let Some(px) = parse_px(val) {
-                s.border_width = px;
-            }
+        "border" => return apply_border_shorthand(s, val, &["top", "right", "bottom", "left"]),
+        "border-top" | "border-right" | "border-bottom" | "border-left" => {
+            return apply_border_shorthand(s, val, &[&prop["border-".len()..]]);
+        }
+        "border-width" => {
+            return apply_shorthand_spacing(
+                val,
+                &mut s.border_top_width,
+                &mut s.border_right_width,
+                &mut s.border_bottom_width,
+                &mut s.border_left_width,
+            )
+        }
+        "border-top-width" | "border-right-width" | "border-bottom-width" | "border-left-width" => {
+            let side = &prop["border-".len()..prop.len() - "-width".len()];
+            let (Some(px), Some((width, _))) = (parse_px(val), border_side(s, side)) else {
+                return false;
+            };
+            *width = px;
         }
         "border-color" => {
-            if let Some(c) = Color::from_hex(val) {
-                s.border_color = c;
-            }
+            let Some(c) = Color::from_css(val) else {
+                return false;
+            };
+            s.set_border_color(c);
+        }
+        "border-top-color" | "border-right-color" | "border-bottom-color" | "border-left-color" => {
+            let side = &prop["border-".len()..prop.len() - "-color".len()];
+            let (Some(c), Some((_, color))) = (Color::from_css(val), border_side(s, side)) else {
+                return false;
+            };
+            *color = c;
         }
         "line-height" => {
             if let Ok(v) = val.parse::<f32>() {
                 s.line_height = v;
+            } else if let Some(Ok(pct)) = val.strip_suffix('%').map(str::parse::<f32>) {
+                s.line_height = pct / 100.0;
             } else if let Some(px) = parse_px(val) {
                 s.line_height = px / s.font_size;
+            } else {
+                return false;
             }
         }
-        "gap" => {
-            if let Some(px) = parse_px(val) {
-                s.gap = px;
+        "letter-spacing" => {
+            if val == "normal" {
+                s.letter_spacing = 0.0;
+            } else if let Some(Ok(em)) = val.strip_suffix("em").map(str::parse::<f32>) {
+                s.letter_spacing = em;
+            } else if let Some(px) = parse_px(val) {
+                s.letter_spacing = px / s.font_size;
+            } else {
+                return false;
             }
         }
-        "break-after" => {
+        // `gap: <row> <column>`; a single value sets both.
+        "gap" => {
+            let mut parts = val.split_whitespace().map(parse_px);
+            let Some(Some(row)) = parts.next() else {
+                return false;
+            };
+            s.row_gap = row;
+            s.column_gap = Some(parts.next().flatten().unwrap_or(row));
+        }
+        "grid-template-columns" => {
+            let Some(tracks) = parse_grid_tracks(val) else {
+                return false;
+            };
+            s.grid_template_columns = tracks;
+        }
+        "grid-template-rows" => {
+            let Some(tracks) = parse_grid_tracks(val) else {
+                return false;
+            };
+            s.grid_template_rows = tracks;
+        }
+        // Only `span n` placement is supported; explicit lines are ignored.
+        "grid-column" => {
+            let Some(n) = parse_grid_span(val) else {
+                return false;
+            };
+            s.grid_column_span = n;
+        }
+        "grid-row" => {
+            let Some(n) = parse_grid_span(val) else {
+                return false;
+            };
+            s.grid_row_span = n;
+        }
+        "row-gap" => {
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.row_gap = px;
+        }
+        // `columns` is a count/width shorthand; only the count is honoured.
+        "columns" | "column-count" => {
+            let Some(n) = val
+                .split_whitespace()
+                .find_map(|part| part.parse::<u32>().ok())
+            else {
+                return false;
+            };
+            s.column_count = n.max(1);
+        }
+        "column-gap" => {
+            let Some(px) = parse_px(val) else {
+                return false;
+            };
+            s.column_gap = Some(px);
+        }
+        "overflow-x" => {
+            s.scale_to_fit = val == "auto";
+        }
+        "overflow" => match val {
+            "hidden" | "clip" => s.overflow = Overflow::Hidden,
+            "visible" => s.overflow = Overflow::Visible,
+            _ => return false,
+        },
+        "visibility" => match val {
+            "hidden" | "collapse" => s.visibility = Visibility::Hidden,
+            "visible" => s.visibility = Visibility::Visible,
+            _ => return false,
+        },
+        "tab-size" => {
+            let Ok(n) = val.trim().parse::<u32>() else {
+                return false;
+            };
+            s.tab_size = n;
+        }
+        "direction" => match val {
+            "rtl" => s.direction = Direction::Rtl,
+            "ltr" => s.direction = Direction::Ltr,
+            _ => return false,
+        },
+        "white-space" => match val {
+            "pre" => s.white_space = WhiteSpace::Pre,
+            "normal" => s.white_space = WhiteSpace::Normal,
+            _ => return false,
+        },
+        "overflow-wrap" | "word-wrap" => match val {
+            "break-word" | "anywhere" => s.overflow_wrap = OverflowWrap::BreakWord,
+            "normal" => s.overflow_wrap = OverflowWrap::Normal,
+            _ => return false,
+        },
+        "aspect-ratio" => {
+            let Some(ratio) = parse_aspect_ratio(val) else {
+                return false;
+            };
+            s.aspect_ratio = ratio;
+        }
+        "object-fit" => match val {
+            "contain" => s.object_fit = ObjectFit::Contain,
+            "cover" => s.object_fit = ObjectFit::Cover,
+            "fill" => s.object_fit = ObjectFit::Fill,
+            _ => return false,
+        },
+        "break-after" | "page-break-after" => {
             s.page_break_after = val == "always" || val == "page";
+            s.keep_with_next = val == "avoid";
         }
         "break-before" => {
             s.page_break_before = val == "always" || val == "page";
@@ -841,14 +2191,135 @@ fn apply_css_property(s: &mut ComputedStyle, prop: &str, val: &str) {
         "page-break-before" => {
             s.page_break_before = val == "always" || val == "page";
         }
-        "page-break-after" => {
-            s.page_break_after = val == "always" || val == "page";
-        }
         "page-break-inside" => {
             s.page_break_inside_avoid = val == "avoid";
         }
-        _ => {}
+        _ => return false,
+    }
+    true
+}
+
+/// Parse `linear-gradient([<angle> | to <side-or-corner>,] <stop>, <stop>)`.
+/// The direction defaults to `to bottom` (180deg). Stops take any
+/// [`Color::from_css`] colour; a stop position after the colour is ignored,
+/// since the two stops always span the whole box.
+fn parse_linear_gradient(val: &str) -> Option<LinearGradient> {
+    let args = val
+        .trim()
+        .strip_prefix("linear-gradient(")?
+        .strip_suffix(')')?;
+    let parts = split_top_level_commas(args);
+    let (angle, stops) = match parts.as_slice() {
+        [dir, from, to] => (parse_gradient_direction(dir)?, [*from, *to]),
+        [from, to] => (180.0, [*from, *to]),
+        _ => return None,
+    };
+    let stop = |stop: &str| {
+        let color = match stop.rfind(')') {
+            Some(end) => &stop[..=end],
+            None => stop.split_whitespace().next()?,
+        };
+        Color::from_css(color).map(|c| [c.r, c.g, c.b, c.a])
+    };
+    Some(LinearGradient {
+        angle,
+        from: stop(stops[0])?,
+        to: stop(stops[1])?,
+    })
+}
+
+/// Split `args` at the commas that are not inside parentheses, trimming
+/// each part.
+fn split_top_level_commas(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, ch) in args.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
     }
+    parts.push(args[start..].trim());
+    parts
+}
+
+/// A gradient direction in CSS degrees: an angle in `deg`, `grad`, `rad` or
+/// `turn`, or `to` a side or corner. Corners are taken as the 45° diagonals
+/// rather than the box's own diagonal.
+fn parse_gradient_direction(dir: &str) -> Option<f32> {
+    let dir = dir.trim().to_ascii_lowercase();
+    if let Some(sides) = dir.strip_prefix("to ") {
+        let (mut x, mut y) = (0.0f32, 0.0f32);
+        for side in sides.split_whitespace() {
+            match side {
+                "top" => y += 1.0,
+                "bottom" => y -= 1.0,
+                "right" => x += 1.0,
+                "left" => x -= 1.0,
+                _ => return None,
+            }
+        }
+        if x == 0.0 && y == 0.0 {
+            return None;
+        }
+        return Some(x.atan2(y).to_degrees().rem_euclid(360.0));
+    }
+    // `grad` also ends in `rad`, so it is tried first.
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ];
+    for (unit, degrees) in units {
+        if let Some(value) = dir.strip_suffix(unit) {
+            return value.trim().parse::<f32>().ok().map(|v| v * degrees);
+        }
+    }
+    (dir == "0").then_some(0.0)
+}
+
+/// The width and colour fields of one border edge, by CSS side name.
+fn border_side<'a>(s: &'a mut ComputedStyle, side: &str) -> Option<(&'a mut f32, &'a mut Color)> {
+    match side {
+        "top" => Some((&mut s.border_top_width, &mut s.border_top_color)),
+        "right" => Some((&mut s.border_right_width, &mut s.border_right_color)),
+        "bottom" => Some((&mut s.border_bottom_width, &mut s.border_bottom_color)),
+        "left" => Some((&mut s.border_left_width, &mut s.border_left_color)),
+        _ => None,
+    }
+}
+
+/// Apply a `<width> <style> <color>` border shorthand to `sides`. Any part
+/// may be omitted; the line style is ignored (borders are always solid).
+fn apply_border_shorthand(s: &mut ComputedStyle, val: &str, sides: &[&str]) -> bool {
+    let mut width = None;
+    let mut color = None;
+    for token in val.split_whitespace() {
+        if token == "none" {
+            width = Some(0.0);
+        } else if let Some(px) = parse_px(token) {
+            width = Some(px);
+        } else if let Some(c) = Color::from_css(token) {
+            color = Some(c);
+        }
+    }
+    for side in sides {
+        if let Some((w, c)) = border_side(s, side) {
+            if let Some(px) = width {
+                *w = px;
+            }
+            if let Some(col) = color {
+                *c = col;
+            }
+        }
+    }
+    width.is_some() || color.is_some()
 }
 
 fn parse_px(s: &str) -> Option<f32> {
@@ -857,16 +2328,20 @@ fn parse_px(s: &str) -> Option<f32> {
 }
 
 fn parse_dimension(s: &str) -> Dimension {
+    try_parse_dimension(s).unwrap_or(Dimension::Auto)
+}
+
+fn try_parse_dimension(s: &str) -> Option<Dimension> {
     let s = s.trim();
-    if s == "auto" {
-        Dimension::Auto
-    } else if s.ends_with('%') {
-        s.trim_end_matches('%')
-            .parse::<f32>()
-            .map(Dimension::Percent)
-            .unwrap_or(Dimension::Auto)
-    } else {
-        parse_px(s).map(Dimension::Px).unwrap_or(Dimension::Auto)
+    match s {
+        "auto" => Some(Dimension::Auto),
+        "min-content" => Some(Dimension::MinContent),
+        "max-content" => Some(Dimension::MaxContent),
+        "fit-content" => Some(Dimension::FitContent),
+        _ => match s.strip_suffix('%') {
+            Some(pct) => pct.parse::<f32>().ok().map(Dimension::Percent),
+            None => parse_px(s).map(Dimension::Px),
+        },
     }
 }
 
@@ -876,8 +2351,8 @@ fn apply_shorthand_spacing(
     right: &mut f32,
     bottom: &mut f32,
     left: &mut f32,
-) {
-    let parts: Vec<f32> = val.split_whitespace().filter_map(|p| parse_px(p)).collect();
+) -> bool {
+    let parts: Vec<f32> = val.split_whitespace().filter_map(parse_px).collect();
     match parts.len() {
         1 => {
             *top = parts[0];
@@ -897,8 +2372,9 @@ fn apply_shorthand_spacing(
             *bottom = parts[2];
             *left = parts[3];
         }
-        _ => {}
+        _ => return false,
     }
+    true
 }
 
 // ---------------------------------------------------------------------------
@@ -906,8 +2382,15 @@ fn apply_shorthand_spacing(
 // ---------------------------------------------------------------------------
 
 /// A DOM node annotated with its computed style.
+///
+/// Produced by [`build_styled_tree`] or [`crate::pipeline::style_html`] and
+/// consumed by layout, which reads only these fields. Each node's style is
+/// already resolved, so an edit affects that node alone: changing an
+/// inherited property such as `color` or `font_size` does not reach its
+/// descendants the way the equivalent class or inline style would.
 #[derive(Debug, Clone)]
 pub enum StyledNode {
+    /// An element with its resolved style, inherited values included.
     Element {
         tag: Tag,
         style: ComputedStyle,
@@ -915,23 +2398,41 @@ pub enum StyledNode {
         /// Original attributes (for images src, etc.)
         attrs: std::collections::HashMap<String, String>,
     },
-    Text {
-        text: String,
-        style: ComputedStyle,
-    },
+    /// A run of text, styled as text directly inside its parent.
+    Text { text: String, style: ComputedStyle },
 }
 
 /// Build a styled tree from a DOM tree, resolving styles top-down.
+///
+/// Elements that resolve to `display: none` are omitted along with their
+/// children.
 pub fn build_styled_tree(
     nodes: &[DomNode],
     parent_style: Option<&ComputedStyle>,
+) -> Vec<StyledNode> {
+    build_styled_tree_with_theme(nodes, parent_style, &Theme::default())
+}
+
+/// Like [`build_styled_tree`], but with tag defaults taken from `theme`.
+pub fn build_styled_tree_with_theme(
+    nodes: &[DomNode],
+    parent_style: Option<&ComputedStyle>,
+    theme: &Theme,
 ) -> Vec<StyledNode> {
     let mut result = Vec::new();
-    for node in nodes {
+    for (i, node) in nodes.iter().enumerate() {
         match node {
             DomNode::Element(e) => {
-                let style = resolve_style(e, parent_style);
-                let children = build_styled_tree(&e.children, Some(&style));
+                let style = resolve_style_with_theme(e, parent_style, theme);
+                // `display: none` (including unknown tags) removes the whole
+                // subtree; nothing below it is laid out or measured.
+                if style.display == Display::None {
+                    continue;
+                }
+                let mut children = build_styled_tree_with_theme(&e.children, Some(&style), theme);
+                if let (Tag::Table, Some(zebra)) = (&e.tag, theme.table_zebra) {
+                    stripe_rows(&mut children, zebra);
+                }
                 result.push(StyledNode::Element {
                     tag: e.tag.clone(),
                     style,
@@ -940,26 +2441,8 @@ pub fn build_styled_tree(
                 });
             }
             DomNode::Text(text) => {
-                let trimmed = text.trim();
-                if !trimmed.is_empty() {
-                    let mut style = parent_style.cloned().unwrap_or_default();
-                    // Text nodes render inline — clear all box-model properties
-                    // that must not be inherited (border, background, spacing).
-                    style.border_width = 0.0;
-                    style.background_color = Color {
-                        r: 0.0,
-                        g: 0.0,
-                        b: 0.0,
-                        a: 0.0,
-                    };
-                    style.margin_top = 0.0;
-                    style.margin_right = 0.0;
-                    style.margin_bottom = 0.0;
-                    style.margin_left = 0.0;
-                    style.padding_top = 0.0;
-                    style.padding_right = 0.0;
-                    style.padding_bottom = 0.0;
-                    style.padding_left = 0.0;
+                if !text.trim().is_empty() || separates_inline_siblings(nodes, i, parent_style) {
+                    let style = parent_style.cloned().unwrap_or_default().text_style();
                     result.push(StyledNode::Text {
                         text: text.clone(),
                         style,
@@ -971,6 +2454,46 @@ pub fn build_styled_tree(
     result
 }
 
+/// Tint the second, fourth, … body rows among a table's `children` with
+/// `color`. Header rows (all `<th>`) are not counted, and rows with a
+/// background of their own are counted but keep it.
+fn stripe_rows(children: &mut [StyledNode], color: Color) {
+    let is_header_cell =
+        |cell: &StyledNode| matches!(cell, StyledNode::Element { tag: Tag::Th, .. });
+    let body_rows = children.iter_mut().filter_map(|child| match child {
+        StyledNode::Element {
+            tag: Tag::Tr,
+            style,
+            children,
+            ..
+        } if !children.iter().all(is_header_cell) => Some(style),
+        _ => None,
+    });
+    for style in body_rows.skip(1).step_by(2) {
+        if style.background_color.is_transparent() {
+            style.background_color = color;
+        }
+    }
+}
+
+/// Whether the whitespace-only text at `nodes[i]` sits between two inline
+/// siblings in flow content, where it is the space between their words.
+/// Flex, grid and table-row containers drop it like any other whitespace.
+fn separates_inline_siblings(
+    nodes: &[DomNode],
+    i: usize,
+    parent_style: Option<&ComputedStyle>,
+) -> bool {
+    let in_flow = parent_style
+        .is_none_or(|s| !matches!(s.display, Display::Flex | Display::Grid | Display::TableRow));
+    let inline = |node: Option<&DomNode>| match node {
+        Some(DomNode::Element(e)) => e.tag.is_inline(),
+        Some(DomNode::Text(t)) => !t.trim().is_empty(),
+        None => false,
+    };
+    in_flow && i > 0 && inline(nodes.get(i - 1)) && inline(nodes.get(i + 1))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -983,18 +2506,346 @@ mod tests {
         assert_eq!(s.padding_left, 16.0);
     }
 
+    #[test]
+    fn tailwind_fractional_and_negative_spacing() {
+        let mut s = ComputedStyle::default();
+        for class in ["p-0.5", "px-1.5", "mt-2.5", "-mb-4", "gap-0.5"] {
+            assert!(apply_tailwind_class(&mut s, class), "{class}");
+        }
+        assert_eq!(s.padding_top, 2.0);
+        assert_eq!(s.padding_left, 6.0);
+        assert_eq!(s.margin_top, 10.0);
+        assert_eq!(s.margin_bottom, -16.0);
+        assert_eq!((s.column_gap, s.row_gap), (Some(2.0), 2.0));
+        for class in ["-p-4", "m-inf", "p-NaN", "mt--4"] {
+            assert!(!apply_tailwind_class(&mut s, class), "{class}");
+        }
+    }
+
     #[test]
     fn inline_style_font_size() {
         let mut s = ComputedStyle::default();
-        apply_inline_style(&mut s, "font-size: 24px; color: #ff0000");
+        apply_inline_style(&mut s, "font-size: 24px; color: #ff0000", 16.0);
         assert_eq!(s.font_size, 24.0);
         assert!((s.color.r - 1.0).abs() < 0.01);
     }
 
+    #[test]
+    fn small_and_relative_font_sizes_scale_the_inherited_size() {
+        let html = r#"<p class="text-xl">Big <small>fine print</small>
+            <span style="font-size: smaller">less</span>
+            <span style="font-size: larger">more</span></p>"#;
+        let styled = build_styled_tree(&crate::dom::parse_html(html), None);
+        let StyledNode::Element {
+            style, children, ..
+        } = &styled[0]
+        else {
+            panic!("expected a paragraph");
+        };
+        let sizes: Vec<f32> = children
+            .iter()
+            .filter_map(|child| match child {
+                StyledNode::Element { style, .. } => Some(style.font_size),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(style.font_size, 20.0);
+        assert_eq!(sizes, [16.0, 16.0, 25.0]);
+    }
+
+    #[test]
+    fn leading_classes_set_the_line_height() {
+        let line_height = |class| {
+            let mut s = ComputedStyle::default();
+            assert!(apply_tailwind_class(&mut s, class), "{class}");
+            s.line_height
+        };
+        assert!(line_height("leading-loose") > line_height("leading-tight"));
+        assert_eq!(line_height("leading-none"), 1.0);
+        // 6 × 4px over the default 16px text.
+        assert_eq!(line_height("leading-6"), 1.5);
+
+        // Absolute leading holds for the final font size, in any class order.
+        let nodes = crate::dom::parse_html(
+            r#"<p class="leading-6 text-xl"></p><p class="leading-[22px] text-sm"></p>
+            <p class="leading-6 leading-loose text-xl"></p>"#,
+        );
+        let lines: Vec<f32> = nodes
+            .iter()
+            .filter_map(|node| match node {
+                crate::dom::DomNode::Element(e) => Some(resolve_style(e, None)),
+                _ => None,
+            })
+            .map(|s| s.line_height * s.font_size)
+            .collect();
+        assert_eq!(lines, [24.0, 22.0, 40.0]);
+    }
+
+    #[test]
+    fn tracking_classes_set_the_letter_spacing() {
+        let letter_spacing = |class| {
+            let mut s = ComputedStyle::default();
+            assert!(apply_tailwind_class(&mut s, class), "{class}");
+            s.letter_spacing
+        };
+        assert!(letter_spacing("tracking-widest") > letter_spacing("tracking-tight"));
+        assert_eq!(letter_spacing("tracking-normal"), 0.0);
+
+        let mut s = ComputedStyle::default();
+        apply_css_property(&mut s, "letter-spacing", "2px", 16.0);
+        assert_eq!(s.letter_spacing, 0.125);
+    }
+
+    #[test]
+    fn arbitrary_values_apply_as_css() {
+        let mut s = ComputedStyle::default();
+        for class in [
+            "w-[250px]",
+            "text-[14px]",
+            "bg-[#123456]",
+            "px-[10px]",
+            "text-[#ff0000]",
+        ] {
+            assert!(apply_tailwind_class(&mut s, class), "{class}");
+        }
+        assert_eq!(s.width, Dimension::Px(250.0));
+        assert_eq!(s.font_size, 14.0);
+        assert_eq!(s.background_color, Color::from_hex("#123456").unwrap());
+        assert_eq!(
+            (s.padding_left, s.padding_right, s.padding_top),
+            (10.0, 10.0, 0.0)
+        );
+        assert_eq!(s.color, Color::from_hex("#ff0000").unwrap());
+
+        assert!(apply_tailwind_class(&mut s, "w-[50%]"));
+        assert_eq!(s.width, Dimension::Percent(50.0));
+        assert!(!apply_tailwind_class(&mut s, "rotate-[45deg]"));
+    }
+
+    #[test]
+    fn arbitrary_colours_accept_any_css_colour() {
+        let red = Color::from_hex("#ff0000").unwrap();
+        for class in ["text-[red]", "text-[rgb(255,0,0)]"] {
+            let mut s = ComputedStyle::default();
+            assert!(apply_tailwind_class(&mut s, class), "{class}");
+            assert_eq!((s.color, s.font_size), (red, 16.0), "{class}");
+        }
+        let mut s = ComputedStyle::default();
+        assert!(apply_tailwind_class(&mut s, "border-[navy]"));
+        assert_eq!(s.border_top_color, Color::from_css("navy").unwrap());
+    }
+
+    #[test]
+    fn invalid_arbitrary_values_are_not_handled() {
+        let mut s = ComputedStyle {
+            width: Dimension::Px(100.0),
+            ..ComputedStyle::default()
+        };
+        for class in ["w-[garbage]", "text-[garbage]", "p-[x]", "leading-[tall]"] {
+            assert!(!apply_tailwind_class(&mut s, class), "{class}");
+        }
+        assert_eq!(s.width, Dimension::Px(100.0));
+    }
+
+    #[test]
+    fn palette_covers_every_hue_and_shade() {
+        let rgb = |c: Color| [c.r, c.g, c.b].map(|v| (v * 255.0).round() as u8);
+        let mut s = ComputedStyle::default();
+        assert!(apply_tailwind_class(&mut s, "text-indigo-600"));
+        assert!(apply_tailwind_class(&mut s, "bg-emerald-100"));
+        assert!(apply_tailwind_class(&mut s, "border-rose-950"));
+        assert_eq!(rgb(s.color), [0x4f, 0x46, 0xe5]);
+        assert_eq!(rgb(s.background_color), [0xd1, 0xfa, 0xe5]);
+        assert_eq!(rgb(s.border_top_color), [0x4c, 0x05, 0x19]);
+        assert_eq!(rgb(palette_color("slate-50").unwrap()), [0xf8, 0xfa, 0xfc]);
+
+        assert!(!apply_tailwind_class(&mut s, "text-indigo-550"));
+        assert!(!apply_tailwind_class(&mut s, "bg-mauve-500"));
+    }
+
+    #[test]
+    fn variant_prefixes_apply_breakpoints_and_drop_states() {
+        let mut s = ComputedStyle::default();
+        assert!(apply_tailwind_class(&mut s, "md:flex"));
+        assert_eq!(s.display, Display::Flex);
+
+        let mut s = ComputedStyle::default();
+        for class in [
+            "block",
+            "lg:hidden",
+            "print:block",
+            "hover:bg-blue-500",
+            "md:focus:hidden",
+        ] {
+            assert!(apply_tailwind_class(&mut s, class), "{class}");
+        }
+        assert_eq!(s.display, Display::Block);
+        assert_eq!(
+            s.background_color,
+            ComputedStyle::default().background_color
+        );
+        assert!(!apply_tailwind_class(&mut s, "md:no-such-class"));
+        assert!(!apply_tailwind_class(&mut s, "weird:flex"));
+
+        // Variants layer over the base utilities in breakpoint order, not in
+        // the order the classes are written.
+        let nodes = crate::dom::parse_html(
+            r#"<div class="md:flex block"></div><div class="lg:p-8 md:p-2"></div>"#,
+        );
+        let styles: Vec<ComputedStyle> = nodes
+            .iter()
+            .map(|node| match node {
+                crate::dom::DomNode::Element(e) => resolve_style(e, None),
+                _ => panic!("expected an element"),
+            })
+            .collect();
+        assert_eq!(styles[0].display, Display::Flex);
+        assert_eq!(styles[1].padding_left, 32.0);
+    }
+
+    #[test]
+    fn hidden_attribute_resolves_to_display_none() {
+        let nodes =
+            crate::dom::parse_html(r#"<div hidden>x</div><div hidden class="block">y</div>"#);
+        let styles: Vec<Display> = nodes
+            .iter()
+            .map(|node| match node {
+                crate::dom::DomNode::Element(e) => resolve_style(e, None).display,
+                _ => panic!("expected an element"),
+            })
+            .collect();
+        assert_eq!(styles, [Display::None, Display::Block]);
+        assert_eq!(build_styled_tree(&nodes, None).len(), 1);
+    }
+
+    #[test]
+    fn rtl_keeps_an_explicit_text_align_and_list_padding() {
+        let html = r#"<p dir="rtl">a</p><p dir="rtl" class="text-left">b</p>
+            <p dir="rtl" style="text-align: left">c</p><ul dir="rtl">d</ul>
+            <ul dir="rtl" class="pl-6">e</ul>"#;
+        let styles: Vec<ComputedStyle> = crate::dom::parse_html(html)
+            .iter()
+            .filter_map(|node| match node {
+                crate::dom::DomNode::Element(e) => Some(resolve_style(e, None)),
+                _ => None,
+            })
+            .collect();
+        let aligns: Vec<TextAlign> = styles[..3].iter().map(|s| s.text_align).collect();
+        assert_eq!(aligns, [TextAlign::Right, TextAlign::Left, TextAlign::Left]);
+        assert_eq!(
+            (styles[3].padding_left, styles[3].padding_right),
+            (0.0, 24.0)
+        );
+        assert_eq!(
+            (styles[4].padding_left, styles[4].padding_right),
+            (24.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn supported_lists_match_resolver() {
+        assert!(supported_tailwind_classes().contains(&"flex"));
+        assert!(supported_tailwind_patterns().contains(&"bg-{color}"));
+        assert!(supported_css_properties().contains(&"font-size"));
+        for pattern in TAILWIND_PATTERNS {
+            let value = if pattern.starts_with("bg-") {
+                "red"
+            } else {
+                "4px"
+            };
+            let class = pattern
+                .replace("{n}", "4")
+                .replace("{color}", "gray-500")
+                .replace("{value}", value)
+                .replace("{tracks}", "1fr_2fr")
+                .replace("{w}/{h}", "4/3")
+                .replace("{class}", "flex");
+            assert!(!class.contains('{'), "unknown placeholder in {pattern:?}");
+            assert!(
+                apply_tailwind_class(&mut ComputedStyle::default(), &class),
+                "listed pattern {pattern:?} is not handled"
+            );
+        }
+        let values = [
+            "0",
+            "auto",
+            "block",
+            "row",
+            "red",
+            "bold",
+            "underline",
+            "center",
+            "disc",
+            "1fr",
+            "span 2",
+            "hidden",
+            "pre",
+            "normal",
+            "rtl",
+            "cover",
+            "always",
+            "avoid",
+        ];
+        for prop in CSS_PROPERTIES {
+            assert!(
+                values.iter().any(|val| apply_css_property(
+                    &mut ComputedStyle::default(),
+                    prop,
+                    val,
+                    16.0
+                )),
+                "listed property {prop:?} is not handled"
+            );
+        }
+        assert!(!apply_tailwind_class(
+            &mut ComputedStyle::default(),
+            "no-such-class"
+        ));
+    }
+
+    #[test]
+    fn gradients_accept_css_colours_angles_and_corners() {
+        let gradient = |val| parse_linear_gradient(val).unwrap();
+        let g = gradient("linear-gradient(0.25turn, rgb(255, 0, 0) 10%, blue)");
+        assert_eq!(g.angle, 90.0);
+        assert_eq!(g.from, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(g.to, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(
+            gradient("linear-gradient(to top right, red, navy)").angle,
+            45.0
+        );
+        assert_eq!(
+            gradient("linear-gradient(to left, #fff, #000)").angle,
+            270.0
+        );
+        assert_eq!(gradient("linear-gradient(200grad, red, blue)").angle, 180.0);
+        let g = gradient("linear-gradient(rgba(0 0 0 / 50%), transparent)");
+        assert_eq!((g.angle, g.from[3], g.to[3]), (180.0, 0.5, 0.0));
+        assert!(parse_linear_gradient("linear-gradient(to middle, red, blue)").is_none());
+    }
+
     #[test]
     fn color_from_hex() {
         let c = Color::from_hex("#ff8800").unwrap();
         assert!((c.r - 1.0).abs() < 0.01);
         assert!((c.g - 0.533).abs() < 0.01);
     }
+
+    #[test]
+    fn strong_and_em_are_bold_and_italic_inline() {
+        let dom = crate::dom::parse_html("<p><strong>a</strong><em>b</em></p>");
+        let styled = build_styled_tree(&dom, None);
+        let StyledNode::Element { children, .. } = &styled[0] else {
+            panic!("expected <p>");
+        };
+        let styles: Vec<&ComputedStyle> = children
+            .iter()
+            .map(|child| match child {
+                StyledNode::Element { style, .. } | StyledNode::Text { style, .. } => style,
+            })
+            .collect();
+        assert_eq!(styles[0].display, Display::Inline);
+        assert_eq!(styles[0].font_weight, FontWeight::Bold);
+        assert_eq!(styles[1].font_style, FontStyle::Italic);
+    }
 }
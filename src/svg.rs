@@ -0,0 +1,76 @@
+//! SVG support – `data:image/svg+xml;base64,…` images are rasterized with
+//! `resvg` so they can be embedded like any other bitmap.
+//!
+//! Text inside SVGs is not rendered (no font database is loaded).
+
+use resvg::{tiny_skia, usvg};
+
+/// Default rasterization resolution for SVG images, in dots per inch.
+pub const DEFAULT_SVG_DPI: f32 = 144.0;
+
+/// Whether a data URI declares the `image/svg+xml` MIME type.
+pub fn is_svg_data_uri(src: &str) -> bool {
+    src.strip_prefix("data:")
+        .and_then(|rest| rest.split([';', ',']).next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("image/svg+xml"))
+}
+
+/// Intrinsic `(width, height)` of an SVG document in CSS px (1 px = 1 pt).
+pub fn intrinsic_size(bytes: &[u8]) -> Result<(f32, f32), String> {
+    let tree = parse(bytes)?;
+    Ok((tree.size().width(), tree.size().height()))
+}
+
+/// Rasterize an SVG document to PNG bytes at `dpi`.
+pub fn rasterize_to_png(bytes: &[u8], dpi: f32) -> Result<Vec<u8>, String> {
+    rasterize(bytes, dpi).map(|(_, png)| png)
+}
+
+/// Like [`rasterize_to_png`], but also returns the [`intrinsic_size`], so the
+/// document is parsed only once.
+pub fn rasterize(bytes: &[u8], dpi: f32) -> Result<((f32, f32), Vec<u8>), String> {
+    let tree = parse(bytes)?;
+    let size = (tree.size().width(), tree.size().height());
+    let scale = dpi.max(1.0) / 72.0;
+    let width = (tree.size().width() * scale).ceil() as u32;
+    let height = (tree.size().height() * scale).ceil() as u32;
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| format!("SVG raster size {width}×{height} is invalid"))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    let png = pixmap
+        .encode_png()
+        .map_err(|e| format!("SVG PNG encode error: {e}"))?;
+    Ok((size, png))
+}
+
+fn parse(bytes: &[u8]) -> Result<usvg::Tree, String> {
+    usvg::Tree::from_data(bytes, &usvg::Options::default())
+        .map_err(|e| format!("SVG parse error: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: &[u8] = br#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20"><rect width="40" height="20" fill="red"/></svg>"#;
+
+    #[test]
+    fn rasterizes_at_requested_dpi() {
+        assert_eq!(intrinsic_size(SQUARE).unwrap(), (40.0, 20.0));
+        let png = rasterize_to_png(SQUARE, 144.0).unwrap();
+        let img = ::image::load_from_memory(&png).unwrap();
+        assert_eq!((img.width(), img.height()), (80, 40));
+        assert!(rasterize_to_png(b"<svg", 144.0).is_err());
+    }
+
+    #[test]
+    fn detects_svg_mime() {
+        assert!(is_svg_data_uri("data:image/svg+xml;base64,PHN2Zz4="));
+        assert!(!is_svg_data_uri("data:image/png;base64,AAAA"));
+        assert!(!is_svg_data_uri("image/svg+xml"));
+    }
+}
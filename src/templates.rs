@@ -1,7 +1,67 @@
-//! Sample HTML templates for testing and demonstration.
+//! Sample HTML templates for testing and demonstration, plus simple
+//! `{{ key }}` variable substitution via [`render_template`].
 //!
 //! Each template exercises different supported elements and styles.
 
+use std::collections::HashMap;
+
+/// Substitute mustache-style placeholders in `html` with values from `vars`.
+///
+/// - `{{ key }}` inserts the value HTML-escaped (`&`, `<`, `>`, `"`, `'`).
+/// - `{{{ key }}}` inserts the value verbatim, for pre-sanitized HTML.
+///
+/// Whitespace around the key is ignored. A placeholder whose key is missing
+/// from `vars` is left in the output as written and logged as a warning, as
+/// is an unterminated `{{`.
+pub fn render_template(html: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        let (open, close, raw) = if after.starts_with("{{{") {
+            ("{{{", "}}}", true)
+        } else {
+            ("{{", "}}", false)
+        };
+
+        let Some(end) = after[open.len()..].find(close) else {
+            rest = after;
+            break;
+        };
+        let key = after[open.len()..open.len() + end].trim();
+        let placeholder_len = open.len() + end + close.len();
+        match vars.get(key) {
+            Some(value) if raw => out.push_str(value),
+            Some(value) => out.push_str(&escape_html(value)),
+            None => {
+                log::warn!("template variable '{key}' is not set; leaving it in place");
+                out.push_str(&after[..placeholder_len]);
+            }
+        }
+        rest = &after[placeholder_len..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
 /// Simple invoice-style template with headings, paragraphs, and a table.
 pub fn invoice_template() -> &'static str {
     r##"
@@ -325,6 +385,31 @@ pub fn all_elements_template() -> &'static str {
 mod tests {
     use super::*;
 
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn render_template_escapes_values() {
+        let html = render_template(
+            "<p>{{ customer }} / {{total}}</p>",
+            &vars(&[("customer", "Smith & <Co>"), ("total", "$9")]),
+        );
+        assert_eq!(html, "<p>Smith &amp; &lt;Co&gt; / $9</p>");
+    }
+
+    #[test]
+    fn render_template_raw_and_missing_keys() {
+        let html = render_template(
+            "<div>{{{ notes }}}[{{ missing }}]{{ unterminated</div>",
+            &vars(&[("notes", "<b>Paid</b>")]),
+        );
+        assert_eq!(html, "<div><b>Paid</b>[{{ missing }}]{{ unterminated</div>");
+    }
+
     #[test]
     fn templates_are_valid_html() {
         let templates: Vec<(&str, &str)> = vec![
@@ -6,10 +6,19 @@
 //! - All supported elements produce correct output
 //! - Pagination works correctly
 
+use std::path::Path;
+
+use pdf_forge::builder::Document;
 use pdf_forge::dom::{parse_html, DomNode, Tag};
-use pdf_forge::layout_config::LayoutConfig;
-use pdf_forge::pipeline::{compute_layout_config, generate_pdf, PipelineConfig};
-use pdf_forge::render::render_pdf;
+use pdf_forge::error::ForgeError;
+use pdf_forge::fonts::FontManager;
+use pdf_forge::layout_config::{LayoutConfig, ObjectFit, Watermark};
+use pdf_forge::pipeline::{
+    compute_layout_config, compute_layout_config_with_fonts, generate_pdf,
+    generate_pdf_from_styled, generate_pdf_verbose, generate_pdf_with_fonts, preflight_images,
+    PageBand, PipelineConfig,
+};
+use pdf_forge::render::{render_pdf, ImageIssueKind};
 use pdf_forge::templates;
 
 // =====================================================================
@@ -263,7 +272,7 @@ fn generate_pdf_from_multipage_template() {
     assert_valid_pdf(&bytes);
     // This template has enough content for multiple pages
     assert!(
-        config.pages.len() >= 1,
+        !config.pages.is_empty(),
         "Multi-page template should produce at least 1 page"
     );
 }
@@ -290,6 +299,38 @@ fn render_from_layout_config_json() {
     assert_valid_pdf(&bytes);
 }
 
+#[test]
+fn bad_layout_json_is_reported_as_such() {
+    let err = LayoutConfig::from_json("{\"pages\": [").unwrap_err();
+    assert!(matches!(err, ForgeError::InvalidLayoutJson(_)), "{err:?}");
+    assert!(err.to_string().starts_with("invalid layout JSON"));
+}
+
+#[test]
+fn validate_rejects_nan_coordinates() {
+    let mut config = compute_layout_config("<p>Hello</p>", &default_config());
+    assert_eq!(config.validate(), Ok(()));
+    config.pages[0].boxes[0].x = f32::NAN;
+    let err = config.validate().unwrap_err();
+    assert!(matches!(err, ForgeError::InvalidLayout(_)), "{err:?}");
+    assert!(err.to_string().contains("pages[0].boxes[0].x"), "{err}");
+}
+
+#[test]
+fn from_json_rejects_out_of_range_layouts() {
+    let mut config = compute_layout_config("<p>Hello</p>", &default_config());
+    config.pages[0].boxes[0].width = -5.0;
+    let err = LayoutConfig::from_json(&config.to_json()).unwrap_err();
+    assert!(err.to_string().contains("width"), "{err}");
+
+    config.pages.clear();
+    let err = LayoutConfig::from_json(&config.to_json()).unwrap_err();
+    assert_eq!(
+        err,
+        ForgeError::InvalidLayout("layout has no pages".to_string())
+    );
+}
+
 // =====================================================================
 // Golden-sample stability test
 // =====================================================================
@@ -311,6 +352,95 @@ fn pdf_output_is_deterministic() {
     );
 }
 
+#[test]
+fn deterministic_output_is_byte_identical() {
+    let png = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+    let html = format!(
+        r#"<h1>Report</h1>
+        <div style="background: linear-gradient(90deg, #ff0000, #0000ff)">Gradient</div>
+        <img src="{png}" style="width: 10px; height: 10px" />"#
+    );
+    let tuffy =
+        std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/Tuffy.ttf"))
+            .unwrap();
+    let mut fonts = FontManager::default();
+    fonts.load_font("Helvetica", true, false, tuffy).unwrap();
+    let mut cfg = default_config();
+    cfg.watermark = Some(Watermark::new("DRAFT"));
+    cfg.deterministic = true;
+
+    let (bytes1, _) = generate_pdf_with_fonts(&html, &cfg, &fonts).unwrap();
+    let (bytes2, _) = generate_pdf_with_fonts(&html, &cfg, &fonts).unwrap();
+    assert_valid_pdf(&bytes1);
+    assert!(bytes1 == bytes2, "deterministic runs differ");
+}
+
+#[test]
+fn hundred_page_document_keeps_page_order() {
+    let html: String = (1..=100)
+        .map(|n| format!("<p>Page {n}</p><div class=\"page-break\"></div>"))
+        .collect();
+    let (bytes, layout) = generate_pdf(&html, &default_config()).unwrap();
+    assert_eq!(layout.pages.len(), 100);
+
+    let doc = lopdf::Document::load_mem(&bytes).unwrap();
+    let pages = doc.get_pages();
+    assert_eq!(pages.len(), 100);
+    for n in 1..=100u32 {
+        let text = doc.extract_text(&[n]).unwrap();
+        assert_eq!(text.trim(), format!("Page {n}"), "page {n}");
+    }
+}
+
+#[test]
+fn internal_link_jumps_to_the_target_page() {
+    let html = r##"<h1>Contents</h1>
+        <ul><li><a href="#results">Results</a></li></ul>
+        <div class="page-break"></div>
+        <p>Intro</p>
+        <div class="page-break"></div>
+        <h2 id="results">Results</h2><p>Done.</p>"##;
+    let (bytes, layout) = generate_pdf(html, &default_config()).unwrap();
+    assert_eq!(
+        layout.headings(),
+        [("Contents".to_string(), 0), ("Results".to_string(), 2)]
+    );
+
+    let doc = lopdf::Document::load_mem(&bytes).unwrap();
+    let pages = doc.get_pages();
+    let page = doc.get_dictionary(pages[&1]).unwrap();
+    let annots = page.get(b"Annots").unwrap().as_array().unwrap();
+    assert_eq!(annots.len(), 1);
+    let annot = match &annots[0] {
+        lopdf::Object::Reference(id) => doc.get_dictionary(*id).unwrap(),
+        obj => obj.as_dict().unwrap(),
+    };
+    let action = annot.get(b"A").unwrap().as_dict().unwrap();
+    assert_eq!(action.get(b"S").unwrap().as_name().unwrap(), b"GoTo");
+    let dest = action.get(b"D").unwrap().as_array().unwrap();
+    assert_eq!(dest[0].as_reference().unwrap(), pages[&3]);
+}
+
+#[test]
+fn compression_shrinks_output() {
+    let html = templates::multi_page_template();
+    let mut cfg = default_config();
+    let (compressed, _) = generate_pdf(html, &cfg).unwrap();
+    cfg.compress = false;
+    let (plain, _) = generate_pdf(html, &cfg).unwrap();
+
+    assert_valid_pdf(&compressed);
+    assert_valid_pdf(&plain);
+    assert!(
+        compressed.len() < plain.len(),
+        "{} >= {} bytes",
+        compressed.len(),
+        plain.len()
+    );
+    let doc = lopdf::Document::load_mem(&compressed).unwrap();
+    assert!(doc.get_pages().len() > 1);
+}
+
 // =====================================================================
 // Text / inline tests
 // =====================================================================
@@ -368,6 +498,21 @@ fn table_produces_grid_layout() {
     );
 }
 
+#[test]
+fn hidden_elements_produce_no_boxes() {
+    let visible = compute_layout_config("<p>Shown</p>", &default_config());
+    let html = r#"<p>Shown</p><div class="hidden"><p>Gone</p></div><section>Unknown</section>"#;
+    let config = compute_layout_config(html, &default_config());
+    assert_eq!(count_boxes(&config), count_boxes(&visible));
+    for lbox in &config.pages[0].boxes {
+        visit_box(lbox, &mut |b| {
+            if let Some(text) = &b.text {
+                assert!(text.lines.iter().all(|l| l.text == "Shown"));
+            }
+        });
+    }
+}
+
 fn count_boxes(config: &LayoutConfig) -> usize {
     let mut count = 0;
     for page in &config.pages {
@@ -409,6 +554,43 @@ fn image_produces_image_content() {
     assert!(found_image, "Should find image content");
 }
 
+#[test]
+fn object_fit_reaches_image_content() {
+    let html = r#"<div><img src="a.png" class="object-contain" style="width: 80px; height: 40px" /><img src="b.png" style="width: 80px; height: 40px; object-fit: cover" /></div>"#;
+    let config = compute_layout_config(html, &default_config());
+    let mut fits = Vec::new();
+    visit_box(&config.pages[0].boxes[0], &mut |b| {
+        if let Some(img) = &b.image {
+            fits.push(img.object_fit);
+        }
+    });
+    assert_eq!(fits, vec![ObjectFit::Contain, ObjectFit::Cover]);
+}
+
+#[test]
+fn inline_svg_image_has_intrinsic_dimensions() {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="60" height="30"><circle cx="15" cy="15" r="10" fill="blue"/></svg>"#;
+    let src = format!("data:image/svg+xml;base64,{}", STANDARD.encode(svg));
+    let html = format!(r#"<div><img src="{src}" /></div>"#);
+    let config = compute_layout_config(&html, &default_config());
+
+    let mut dims = None;
+    for page in &config.pages {
+        for lbox in &page.boxes {
+            visit_box(lbox, &mut |b| {
+                if let Some(img) = &b.image {
+                    dims = Some((img.width, img.height));
+                }
+            });
+        }
+    }
+    assert_eq!(dims, Some((60.0, 30.0)));
+    assert!(preflight_images(&html, &default_config()).is_empty());
+    assert_valid_pdf(&render_pdf(&config).unwrap());
+}
+
 // =====================================================================
 // List layout tests
 // =====================================================================
@@ -458,3 +640,533 @@ fn all_templates_render_successfully() {
         assert_valid_pdf(&bytes);
     }
 }
+
+// =====================================================================
+// Image preflight
+// =====================================================================
+
+#[test]
+fn preflight_reports_only_bad_images() {
+    let html = r#"
+        <div>
+            <img src="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==" style="width: 10px; height: 10px" />
+            <img src="https://example.com/logo.png" style="width: 10px; height: 10px" />
+        </div>
+    "#;
+    let issues = preflight_images(html, &default_config());
+    assert_eq!(issues.len(), 1, "Expected exactly one issue: {issues:?}");
+    assert_eq!(issues[0].src, "https://example.com/logo.png");
+    assert_eq!(issues[0].kind, ImageIssueKind::NotDataUri);
+}
+
+#[test]
+fn skipped_image_is_reported_as_a_warning() {
+    let html =
+        r#"<div><img src="data:image/png;base64,!!!" style="width: 10px; height: 10px" /></div>"#;
+    let (bytes, _, warnings) =
+        generate_pdf_verbose(html, &default_config(), &FontManager::default()).unwrap();
+    assert_valid_pdf(&bytes);
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(
+        warnings[0].message.starts_with("Skipping image"),
+        "{}",
+        warnings[0]
+    );
+
+    let html = r#"<div><img src="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==" /></div>"#;
+    let (_, _, warnings) =
+        generate_pdf_verbose(html, &default_config(), &FontManager::default()).unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+}
+
+// =====================================================================
+// Over-wide tables
+// =====================================================================
+
+#[test]
+fn overwide_table_is_scaled_to_content_width() {
+    let cfg = default_config();
+    let content_width = cfg.page_width - 2.0 * cfg.page_margin;
+    let html = r#"
+        <table class="overflow-x-auto" style="width: 800px">
+            <tr><th>A</th><th>B</th><th>C</th></tr>
+            <tr><td>1</td><td>2</td><td>3</td></tr>
+        </table>
+    "#;
+    let config = compute_layout_config(html, &cfg);
+    let table = &config.pages[0].boxes[0];
+    let scale = table
+        .scale_x
+        .expect("table should carry a horizontal scale");
+    assert!(
+        (table.width * scale.factor - content_width).abs() < 0.5,
+        "Scaled width {} should match content width {}",
+        table.width * scale.factor,
+        content_width
+    );
+    assert_valid_pdf(&render_pdf(&config).unwrap());
+
+    // Without the opt-in class the table keeps its natural width.
+    let plain = compute_layout_config(&html.replace("overflow-x-auto", ""), &cfg);
+    assert!(plain.pages[0].boxes[0].scale_x.is_none());
+}
+
+// =====================================================================
+// Cover page
+// =====================================================================
+
+fn page_text(page: &pdf_forge::layout_config::PageLayout) -> String {
+    let mut text = String::new();
+    for lbox in &page.boxes {
+        visit_box(lbox, &mut |b| {
+            if let Some(t) = &b.text {
+                for line in &t.lines {
+                    text.push_str(&line.text);
+                    text.push(' ');
+                }
+            }
+        });
+    }
+    text
+}
+
+#[test]
+fn cover_page_precedes_main_content() {
+    let cfg = PipelineConfig {
+        cover_html: Some("<h1>Annual Report</h1>".to_string()),
+        ..default_config()
+    };
+    let config = compute_layout_config("<p>Main body</p>", &cfg);
+    assert_eq!(config.pages.len(), 2);
+    assert!(page_text(&config.pages[0]).contains("Annual Report"));
+    assert!(!page_text(&config.pages[0]).contains("Main body"));
+    assert!(page_text(&config.pages[1]).contains("Main body"));
+    assert_eq!(config.pages[1].page_index, 1);
+}
+
+#[test]
+fn page_numbers_start_after_the_cover() {
+    let cfg = PipelineConfig {
+        cover_html: Some("<h1>Annual Report</h1>".to_string()),
+        footer: PageBand {
+            right: Some("<p>Page {{ page }} of {{ pages }}</p>".to_string()),
+            ..PageBand::default()
+        },
+        ..default_config()
+    };
+    let config = compute_layout_config("<p>Main body</p>", &cfg);
+    assert!(!page_text(&config.pages[0]).contains("Page"));
+    assert!(page_text(&config.pages[1]).contains("Page 1 of 1"));
+}
+
+// =====================================================================
+// Document builder
+// =====================================================================
+
+#[test]
+fn builder_invoice_produces_layout() {
+    use pdf_forge::style::StyledNode;
+
+    let rows: Vec<Vec<String>> = (1..=3)
+        .map(|i| vec![format!("Item {i}"), format!("${i}00.00")])
+        .collect();
+    let doc = Document::new()
+        .heading(1, "Invoice #2024-001")
+        .with_class("mb-4")
+        .paragraph("Bill to: Client Inc")
+        .table_with_header(&["Item", "Price"], &rows)
+        .with_class("w-full")
+        .paragraph("Total: $600.00")
+        .with_class("text-right font-bold");
+
+    let config = doc.layout_config(&default_config());
+    assert_eq!(config.pages.len(), 1);
+    assert!(count_boxes(&config) > 5, "Expected many boxes");
+    let text = page_text(&config.pages[0]);
+    assert!(text.contains("Invoice #2024-001"));
+    assert!(text.contains("Item 3"));
+
+    // Same output as the equivalent HTML.
+    let html = r#"<h1 class="mb-4">Invoice #2024-001</h1><p>Bill to: Client Inc</p>"#;
+    let from_html = compute_layout_config(html, &default_config());
+    let from_builder = Document::new()
+        .heading(1, "Invoice #2024-001")
+        .with_class("mb-4")
+        .paragraph("Bill to: Client Inc")
+        .layout_config(&default_config());
+    assert_eq!(from_html.to_json(), from_builder.to_json());
+
+    // The inspectable tree is styled with the same config as the layout.
+    let small = PipelineConfig {
+        base_font_size: 12.0,
+        ..default_config()
+    };
+    let styled = Document::new().paragraph("Body").styled_tree(&small);
+    let StyledNode::Element { style, .. } = &styled[0] else {
+        panic!("expected the paragraph element");
+    };
+    assert_eq!(style.font_size, 12.0);
+
+    let (bytes, _) = doc.generate_pdf(&default_config()).unwrap();
+    assert_valid_pdf(&bytes);
+}
+
+#[test]
+fn hand_built_styled_tree_renders() {
+    use pdf_forge::style::{ComputedStyle, FontWeight, StyledNode};
+
+    let para_style = ComputedStyle {
+        font_size: 18.0,
+        font_weight: FontWeight::Bold,
+        margin_bottom: 12.0,
+        ..ComputedStyle::default()
+    };
+    let styled = vec![StyledNode::Element {
+        tag: Tag::P,
+        style: para_style.clone(),
+        children: vec![StyledNode::Text {
+            text: "Built without HTML".to_string(),
+            style: para_style.text_style(),
+        }],
+        attrs: Default::default(),
+    }];
+
+    let (bytes, config) = generate_pdf_from_styled(&styled, &default_config()).unwrap();
+    assert_valid_pdf(&bytes);
+    assert_eq!(config.pages.len(), 1);
+    assert!(page_text(&config.pages[0]).contains("Built without HTML"));
+    let text = config.pages[0].boxes[0].text.as_ref().unwrap();
+    assert_eq!((text.font_size, text.bold), (18.0, true));
+}
+
+// =====================================================================
+// Table cell styling
+// =====================================================================
+
+#[test]
+fn cell_background_and_alignment_override_row_defaults() {
+    let html = r#"
+        <table class="w-full">
+            <tr class="bg-blue-100">
+                <td class="p-2">Item</td>
+                <td class="bg-red-100 text-right p-2">Total</td>
+            </tr>
+        </table>
+    "#;
+    let config = compute_layout_config(html, &default_config());
+    let row = &config.pages[0].boxes[0].children[0];
+    let (plain, special) = (&row.children[0], &row.children[1]);
+
+    assert!(plain.background_color.is_none());
+    let bg = special.background_color.expect("cell background");
+    assert!((bg[0] - 0.996).abs() < 0.01 && (bg[1] - 0.886).abs() < 0.01);
+
+    // The text box hugs the cell's right content edge (border 1 + padding 8).
+    let text = &special.children[0];
+    assert_eq!(text.text.as_ref().unwrap().text_align, "right");
+    let inner_right = special.x + special.width - 9.0;
+    assert!(
+        (text.x + text.width - inner_right).abs() <= 1.0,
+        "text ends at {}, cell content ends at {inner_right}",
+        text.x + text.width
+    );
+    // The left cell keeps its default left alignment.
+    assert!((plain.children[0].x - (plain.x + 9.0)).abs() <= 1.0);
+}
+
+#[test]
+fn caption_is_stacked_above_the_first_row() {
+    let html = r#"
+        <table class="w-full">
+            <caption>Quarterly totals</caption>
+            <tr><td>Q1</td><td>Q2</td></tr>
+        </table>
+    "#;
+    let config = compute_layout_config(html, &default_config());
+    let table = &config.pages[0].boxes[0];
+    let (caption, row) = (&table.children[0], &table.children[1]);
+    assert_eq!(
+        row.children.len(),
+        2,
+        "caption is not part of the row model"
+    );
+    assert!(caption.y + caption.height <= row.y + 0.5);
+    assert_eq!(caption.width, table.width);
+
+    let text = &caption.children[0];
+    assert_eq!(
+        text.text.as_ref().unwrap().lines[0].text,
+        "Quarterly totals"
+    );
+    let centre = text.x + text.width / 2.0;
+    assert!((centre - (caption.x + caption.width / 2.0)).abs() <= 1.0);
+
+    let bottom = html.replace("<caption>", r#"<caption class="caption-bottom">"#);
+    let config = compute_layout_config(&bottom, &default_config());
+    let table = &config.pages[0].boxes[0];
+    let (row, caption) = (&table.children[0], &table.children[1]);
+    assert_eq!(row.children.len(), 2);
+    assert!(caption.y >= row.y + row.height - 0.5, "caption moved below");
+}
+
+#[test]
+fn col_widths_override_equal_columns() {
+    let html = r#"
+        <table class="w-full">
+            <colgroup><col style="width: 30%"><col></colgroup>
+            <tr><td>Name</td><td>Description</td></tr>
+            <tr><td>A</td><td>B</td></tr>
+        </table>
+    "#;
+    let config = compute_layout_config(html, &default_config());
+    let table = &config.pages[0].boxes[0];
+    assert_eq!(table.children.len(), 2, "colgroup produces no box");
+
+    for row in &table.children {
+        let (first, second) = (&row.children[0], &row.children[1]);
+        let expected = row.width * 0.3;
+        assert!(
+            (first.width - expected).abs() <= 1.0,
+            "first column {} vs {expected}",
+            first.width
+        );
+        assert!((first.width + second.width - row.width).abs() <= 1.0);
+    }
+}
+
+#[test]
+fn nested_table_fills_its_cell() {
+    let html = r#"
+        <table class="w-full">
+            <tr>
+                <td>Outer</td>
+                <td class="text-right">
+                    <table>
+                        <tr><td>a</td><td>b</td></tr>
+                        <tr><td>c</td><td>d</td></tr>
+                    </table>
+                </td>
+            </tr>
+        </table>
+    "#;
+    let config = compute_layout_config(html, &default_config());
+    let cell = &config.pages[0].boxes[0].children[0].children[1];
+    let inner = &cell.children[0];
+    assert_eq!(inner.children.len(), 2, "inner table rows");
+
+    // The inner table spans the cell's content box (border 1 + padding 8).
+    let (left, right) = (cell.x + 9.0, cell.x + cell.width - 9.0);
+    assert!((inner.x - left).abs() <= 1.0 && (inner.x + inner.width - right).abs() <= 1.0);
+    for row in &inner.children {
+        assert_eq!(row.children.len(), 2);
+        for inner_cell in &row.children {
+            assert!(inner_cell.x >= left - 1.0 && inner_cell.x + inner_cell.width <= right + 1.0);
+            assert!(
+                inner_cell.y >= cell.y && inner_cell.y + inner_cell.height <= cell.y + cell.height
+            );
+        }
+    }
+}
+
+// =====================================================================
+// Indentation theme
+// =====================================================================
+
+#[test]
+fn list_and_blockquote_indents_follow_theme() {
+    let mut cfg = default_config();
+    cfg.theme.list_indent = 30.0;
+    cfg.theme.blockquote_indent = 50.0;
+    let left = cfg.page_margin;
+
+    let html = "<ul><li>One<ul><li>Two</li></ul></li></ul>";
+    let config = compute_layout_config(html, &cfg);
+    let mut marker_xs = Vec::new();
+    for lbox in &config.pages[0].boxes {
+        visit_box(lbox, &mut |b| {
+            if b.text.as_ref().is_some_and(|t| t.list_marker.is_some()) {
+                marker_xs.push(b.x);
+            }
+        });
+    }
+    assert_eq!(marker_xs, vec![left + 30.0, left + 60.0]);
+
+    let config = compute_layout_config("<blockquote>Quoted</blockquote>", &cfg);
+    let quote = &config.pages[0].boxes[0];
+    assert_eq!(quote.children[0].x, left + 50.0);
+}
+
+// =====================================================================
+// Local image files
+// =====================================================================
+
+#[test]
+fn relative_image_path_is_embedded_when_allowed() {
+    let html = r#"<div><img src="pixel.png" style="width: 20px" /></div>"#;
+    let find_src = |config: &LayoutConfig| {
+        let mut src = None;
+        visit_box(&config.pages[0].boxes[0], &mut |b| {
+            if let Some(img) = &b.image {
+                src = Some(img.src.clone());
+            }
+        });
+        src.expect("image box")
+    };
+
+    // Disabled by default: the path is passed through and skipped at render.
+    let config = compute_layout_config(html, &default_config());
+    assert_eq!(find_src(&config), "pixel.png");
+
+    let cfg = PipelineConfig {
+        allow_local_images: true,
+        base_dir: Some(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")),
+        ..default_config()
+    };
+    let config = compute_layout_config(html, &cfg);
+    assert!(find_src(&config).starts_with("data:image/png;base64,"));
+    assert!(preflight_images(html, &cfg).is_empty());
+    assert_valid_pdf(&render_pdf(&config).unwrap());
+
+    // file:// URIs work too; missing files fall back to the skip path.
+    let abs = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pixel.png");
+    let uri_html = format!(r#"<div><img src="file://{}" /></div>"#, abs.display());
+    assert!(preflight_images(&uri_html, &cfg).is_empty());
+    let encoded = abs.display().to_string().replace("pixel", "pi%78el");
+    let encoded_html = format!(r#"<div><img src="file://localhost{encoded}" /></div>"#);
+    assert!(preflight_images(&encoded_html, &cfg).is_empty());
+    let missing = r#"<div><img src="missing.png" /></div>"#;
+    assert_eq!(preflight_images(missing, &cfg).len(), 1);
+}
+
+// =====================================================================
+// Remote images (`remote-images` feature)
+// =====================================================================
+
+#[cfg(feature = "remote-images")]
+#[test]
+fn remote_image_is_fetched_and_embedded() {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let png = std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pixel.png"))
+        .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf).unwrap();
+        let header = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            png.len()
+        );
+        stream.write_all(header.as_bytes()).unwrap();
+        stream.write_all(&png).unwrap();
+    });
+
+    let html = format!(
+        r#"<div><img src="http://{addr}/pixel.png" style="width: 20px; height: 20px" /></div>"#
+    );
+    let cfg = PipelineConfig {
+        allow_remote_images: true,
+        ..default_config()
+    };
+    let (bytes, layout) = generate_pdf(&html, &cfg).unwrap();
+    server.join().unwrap();
+
+    let mut src = String::new();
+    visit_box(&layout.pages[0].boxes[0], &mut |b| {
+        if let Some(img) = &b.image {
+            src = img.src.clone();
+        }
+    });
+    assert!(src.starts_with("data:image/png;base64,"), "src = {src}");
+    let pdf = String::from_utf8_lossy(&bytes);
+    assert!(pdf.contains("/Subtype /Image") || pdf.contains("/Subtype/Image"));
+}
+
+// =====================================================================
+// Header / footer bands
+// =====================================================================
+
+#[test]
+fn header_snippets_align_to_edges_on_every_page() {
+    let cfg = PipelineConfig {
+        header: PageBand {
+            left: Some(r#"<span class="font-bold">Acme</span>"#.to_string()),
+            right: Some("<p>Page {{ page }} of {{ pages }}</p>".to_string()),
+            ..PageBand::default()
+        },
+        ..default_config()
+    };
+    let html: String = (0..80).map(|i| format!("<p>Paragraph {i}</p>")).collect();
+    let config = compute_layout_config(&html, &cfg);
+    let pages = config.pages.len();
+    assert!(pages > 1);
+
+    let right_edge = cfg.page_width - cfg.page_margin;
+    for (i, page) in config.pages.iter().enumerate() {
+        let mut acme = None;
+        let mut counter = None;
+        for lbox in &page.boxes {
+            visit_box(lbox, &mut |b| {
+                let Some(t) = &b.text else { return };
+                let text: String = t.lines.iter().map(|l| l.text.as_str()).collect();
+                if text == "Acme" {
+                    acme = Some((b.x, b.y + b.height));
+                } else if text == format!("Page {} of {pages}", i + 1) {
+                    counter = Some((b.x + b.width, b.y + b.height));
+                }
+            });
+        }
+        let (acme_x, acme_bottom) = acme.expect("left header on every page");
+        let (counter_right, counter_bottom) = counter.expect("right header on every page");
+        assert!((acme_x - cfg.page_margin).abs() < 0.5, "left at {acme_x}");
+        assert!(
+            (counter_right - right_edge).abs() < 0.5,
+            "right at {counter_right}"
+        );
+        assert!(acme_bottom <= cfg.page_margin && counter_bottom <= cfg.page_margin);
+    }
+}
+
+// =====================================================================
+// Caller-supplied fonts
+// =====================================================================
+
+#[test]
+fn loaded_font_changes_wrapping_and_is_embedded() {
+    let html = &format!(r#"<div class="w-40">{}</div>"#, ["WWW"; 12].join(" "));
+    let line_count = |config: &LayoutConfig| {
+        let mut lines = 0;
+        for lbox in &config.pages[0].boxes {
+            visit_box(lbox, &mut |b| {
+                lines += b.text.as_ref().map_or(0, |t| t.lines.len());
+            });
+        }
+        lines
+    };
+
+    let tuffy =
+        std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/Tuffy.ttf"))
+            .unwrap();
+    let mut fonts = FontManager::new();
+    fonts.load_font("Helvetica", false, false, tuffy).unwrap();
+
+    let heuristic = compute_layout_config(html, &default_config());
+    let measured = compute_layout_config_with_fonts(html, &default_config(), &fonts);
+    assert!(
+        line_count(&measured) > line_count(&heuristic),
+        "wide glyphs wrap sooner than the 0.5 em estimate"
+    );
+
+    let (bytes, layout) = generate_pdf_with_fonts(html, &default_config(), &fonts).unwrap();
+    assert_valid_pdf(&bytes);
+    assert_eq!(line_count(&layout), line_count(&measured));
+    let doc = lopdf::Document::load_mem(&bytes).unwrap();
+    let embedded = doc.objects.values().any(|obj| {
+        obj.as_dict()
+            .is_ok_and(|d| d.has(b"FontFile2") || d.has(b"FontFile3"))
+    });
+    assert!(embedded, "the loaded font is embedded");
+}